@@ -0,0 +1,466 @@
+#[macro_use] extern crate log;
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use kiwi_gb::bios;
+use kiwi_gb::frontend::{AudioSink, Null};
+use kiwi_gb::frontend::extractor::Extractor;
+use kiwi_gb::frontend::recorder::Recorder;
+use kiwi_gb::gb::GameBoy;
+use kiwi_gb::gbs::{self, GbsPlayer};
+use kiwi_gb::mmu::BOOT_ROM_SIZE;
+use kiwi_gb::model::Model;
+use kiwi_gb::serial::printer::Printer;
+use kiwi_gb::spu::DEFAULT_SAMPLE_RATE;
+use kiwi_gb::MemoryBus;
+
+const USAGE: &str = "usage: kiwi-gb-headless [--model dmg0|dmg|mgb|sgb|sgb2|cgb] [--boot | --boot-rom FILE] \
+[--frames N] [--until ADDR=VALUE | --until-serial TEXT] [--hash] [--serial] [--dump-mem ADDR:LEN] [--dump-frame FILE] [--printer DIR] [--record FILE [--stems]] [--midi FILE] [--low-pass HZ] [--track N] [--output FILE] ROM|GBS";
+
+const DEFAULT_FRAMES: u64 = 60 * 60;
+
+// Exit codes, besides success
+const EXIT_ERROR: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_TIMEOUT: i32 = 3;
+
+enum Boot {
+    Skip,
+    Internal,
+    External(PathBuf),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    /// The byte at the address holds the value
+    Memory { addr: u16, value: u8 },
+
+    /// The text was sent over serial (Blargg test roms print their results)
+    Serial(String),
+}
+
+impl Condition {
+    fn is_met(&self, gameboy: &GameBoy) -> bool {
+        match self {
+            Condition::Memory { addr, value } => gameboy.read(*addr) == *value,
+            Condition::Serial(text) => gameboy.mmu().serial.captured_text()
+                .is_some_and(|captured| captured.contains(text.as_str())),
+        }
+    }
+}
+
+/// Memory region to dump
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Region {
+    addr: u16,
+    len: usize,
+}
+
+struct Options {
+    rom_path: PathBuf,
+    model: Model,
+    boot: Boot,
+
+    // Frames to run, or the most to wait for the condition
+    frames: u64,
+    until: Option<Condition>,
+
+    // Printed to the output once stopped
+    hash: bool,
+    serial: bool,
+    dump_mem: Option<Region>,
+    output: Option<PathBuf>,
+
+    // Raw ARGB frame buffer, "-" for stdout
+    dump_frame: Option<PathBuf>,
+
+    // Printer on the serial port, writing its pages to the directory
+    printer: Option<PathBuf>,
+
+    // Audio of the whole run as WAV, along with a file per channel with stems
+    record: Option<PathBuf>,
+    stems: bool,
+
+    // Notes of each channel over the whole run as a MIDI file
+    midi: Option<PathBuf>,
+
+    // Cut the audio above a frequency, the way a speaker would
+    low_pass: Option<u32>,
+
+    // Song of a GBS file, 1-based, the first one of its header by default
+    track: Option<u8>,
+}
+
+/// Hex number, with an optional 0x or $ prefix
+fn parse_hex(s: &str) -> Result<u16, String> {
+    let digits = s.trim_start_matches("0x").trim_start_matches('$');
+    u16::from_str_radix(digits, 16).map_err(|_| format!("invalid hex number {}", s))
+}
+
+fn parse_condition(s: &str) -> Result<Condition, String> {
+    let (addr, value) = s.split_at(s.find('=').ok_or("--until expects ADDR=VALUE")?);
+    let value = parse_hex(&value[1..])?;
+    if value > 0xFF {
+        return Err(format!("value {} does not fit a byte", &s[addr.len() + 1..]));
+    }
+    Ok(Condition::Memory { addr: parse_hex(addr)?, value: value as u8 })
+}
+
+fn parse_region(s: &str) -> Result<Region, String> {
+    let (addr, len) = s.split_at(s.find(':').ok_or("--dump-mem expects ADDR:LEN")?);
+    let addr = parse_hex(addr)?;
+    let len = parse_hex(&len[1..])? as usize;
+    if addr as usize + len > 0x10000 {
+        return Err(format!("region {} goes past the end of memory", s));
+    }
+    Ok(Region { addr, len })
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut rom_path = None;
+        let mut model = Model::default();
+        let mut boot = Boot::Skip;
+        let mut frames = DEFAULT_FRAMES;
+        let mut until = None;
+        let mut hash = false;
+        let mut serial = false;
+        let mut dump_mem = None;
+        let mut dump_frame = None;
+        let mut output = None;
+        let mut printer = None;
+        let mut record = None;
+        let mut stems = false;
+        let mut midi = None;
+        let mut low_pass = None;
+        let mut track = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--model" => {
+                    model = args.next().ok_or("--model expects a model name")?.parse()?;
+                }
+                "--boot" => boot = Boot::Internal,
+                "--boot-rom" => {
+                    let path = args.next().ok_or("--boot-rom expects a file")?;
+                    boot = Boot::External(PathBuf::from(path));
+                }
+                "--frames" => {
+                    frames = args.next()
+                        .and_then(|s| s.parse::<u64>().ok())
+                        .ok_or("--frames expects a number of frames")?;
+                }
+                "--until" => until = Some(parse_condition(&args.next().ok_or("--until expects ADDR=VALUE")?)?),
+                "--until-serial" => until = Some(Condition::Serial(args.next().ok_or("--until-serial expects a text")?)),
+                "--hash" => hash = true,
+                "--serial" => serial = true,
+                "--dump-mem" => dump_mem = Some(parse_region(&args.next().ok_or("--dump-mem expects ADDR:LEN")?)?),
+                "--dump-frame" => dump_frame = Some(PathBuf::from(args.next().ok_or("--dump-frame expects a file")?)),
+                "--printer" => printer = Some(PathBuf::from(args.next().ok_or("--printer expects a directory")?)),
+                "--record" => record = Some(PathBuf::from(args.next().ok_or("--record expects a file")?)),
+                "--stems" => stems = true,
+                "--midi" => midi = Some(PathBuf::from(args.next().ok_or("--midi expects a file")?)),
+                "--low-pass" => {
+                    low_pass = Some(args.next()
+                        .and_then(|s| s.parse::<u32>().ok())
+                        .filter(|&cutoff| cutoff > 0)
+                        .ok_or("--low-pass expects a cutoff in Hz")?);
+                }
+                "--track" => {
+                    track = Some(args.next()
+                        .and_then(|s| s.parse::<u8>().ok())
+                        .filter(|&track| track > 0)
+                        .ok_or("--track expects a song number from 1")?);
+                }
+                "--output" => output = Some(PathBuf::from(args.next().ok_or("--output expects a file")?)),
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ => rom_path = Some(PathBuf::from(arg)),
+            }
+        }
+
+        if stems && record.is_none() {
+            return Err("--stems needs --record".to_string());
+        }
+
+        Ok(Self {
+            rom_path: rom_path.ok_or("missing rom path")?,
+            model,
+            boot,
+            frames,
+            until,
+            hash,
+            serial,
+            dump_mem,
+            dump_frame,
+            output,
+            printer,
+            record,
+            stems,
+            midi,
+            low_pass,
+            track,
+        })
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// 64-bit FNV-1a, stable across runs and platforms unlike the std hasher
+fn fnv1a(data: &[u8]) -> u64 {
+    fnv1a_update(FNV_OFFSET, data)
+}
+
+fn fnv1a_update(hash: u64, data: &[u8]) -> u64 {
+    data.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Audio sink hashing the samples, GBS runs are compared on their sound
+struct AudioHash {
+    hash: u64,
+}
+
+impl AudioSink for AudioHash {
+    fn sample_rate(&self) -> u32 { DEFAULT_SAMPLE_RATE }
+
+    fn queue(&mut self, samples: &[i16]) {
+        let bytes: Vec<u8> = samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        self.hash = fnv1a_update(self.hash, &bytes);
+    }
+}
+
+/// Hex dump, 16 bytes per line prefixed by the address
+fn write_region(out: &mut dyn Write, bus: &dyn MemoryBus, region: Region) -> io::Result<()> {
+    let bytes: Vec<u8> = (0..region.len).map(|i| bus.read(region.addr + i as u16)).collect();
+    for (i, line) in bytes.chunks(16).enumerate() {
+        write!(out, "{:04X}:", region.addr as usize + i * 16)?;
+        for byte in line {
+            write!(out, " {:02X}", byte)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn write_results(options: &Options, gameboy: &GameBoy) -> io::Result<()> {
+    if let Some(path) = &options.dump_frame {
+        if path.as_os_str() == "-" {
+            io::stdout().write_all(gameboy.frame_buffer())?;
+        } else {
+            std::fs::write(path, &gameboy.frame_buffer()[..])?;
+        }
+    }
+
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    if options.hash {
+        writeln!(out, "{:016x}", fnv1a(gameboy.frame_buffer()))?;
+    }
+    if options.serial {
+        write!(out, "{}", gameboy.mmu().serial.captured_text().unwrap_or_default())?;
+    }
+    if let Some(region) = options.dump_mem {
+        write_region(&mut out, gameboy, region)?;
+    }
+    out.flush()
+}
+
+fn main() {
+    pretty_env_logger::init();
+
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+
+    let boot_rom: Option<Box<[u8; BOOT_ROM_SIZE]>> = match &options.boot {
+        Boot::Skip => None,
+        Boot::Internal => Some(Box::new(bios::DMG_BIOS)),
+        Boot::External(path) => match bios::read_boot_rom(path) {
+            Ok(boot_rom) => Some(boot_rom),
+            Err(err) => {
+                error!("Failed to load boot rom {}: {}", path.display(), err);
+                std::process::exit(EXIT_ERROR);
+            }
+        },
+    };
+
+    let rom = match std::fs::read(&options.rom_path) {
+        Ok(rom) => rom,
+        Err(err) => {
+            error!("Failed to read {}: {}", options.rom_path.display(), err);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+    if gbs::is_gbs(&rom) {
+        play_gbs(&options, rom);
+        return;
+    }
+    if options.track.is_some() {
+        eprintln!("--track needs a gbs file\n{}", USAGE);
+        std::process::exit(EXIT_USAGE);
+    }
+
+    let mut gameboy = GameBoy::new(options.model, boot_rom);
+    if let Err(err) = gameboy.load_rom(rom) {
+        error!("Failed to load {}: {}", options.rom_path.display(), err);
+        std::process::exit(EXIT_ERROR);
+    }
+    gameboy.mmu_mut().spu.set_low_pass(options.low_pass);
+
+    if let Some(dir) = &options.printer {
+        gameboy.mmu_mut().serial.connect(Box::new(Printer::with_output_dir(dir.clone())));
+    }
+
+    let mut audio = Recorder::new(Extractor::new(Null));
+    if let Some(path) = &options.record {
+        if let Err(err) = audio.start(path, options.stems) {
+            error!("Failed to record to {}: {}", path.display(), err);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+    if let Some(path) = &options.midi {
+        if let Err(err) = audio.inner_mut().start(path) {
+            error!("Failed to extract notes to {}: {}", path.display(), err);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+
+    let mut frames = 0;
+    let mut met = false;
+    while frames < options.frames && !met {
+        gameboy.run_next_frame(&mut Null, &mut Null, &mut audio);
+        frames += 1;
+        met = options.until.as_ref().is_some_and(|until| until.is_met(&gameboy));
+    }
+    info!("Stopped after {} frames", frames);
+
+    if let Err(err) = audio.stop() {
+        error!("Failed to write the recording: {}", err);
+        std::process::exit(EXIT_ERROR);
+    }
+    if let Err(err) = audio.inner_mut().stop() {
+        error!("Failed to write the notes: {}", err);
+        std::process::exit(EXIT_ERROR);
+    }
+
+    // Paper still in the printer
+    if let Some(printer) = gameboy.mmu_mut().serial.device_mut().as_any_mut().downcast_mut::<Printer>() {
+        printer.cut();
+    }
+
+    if let Err(err) = write_results(&options, &gameboy) {
+        error!("Failed to write results: {}", err);
+        std::process::exit(EXIT_ERROR);
+    }
+
+    if options.until.is_some() && !met {
+        warn!("Condition not met within {} frames", options.frames);
+        std::process::exit(EXIT_TIMEOUT);
+    }
+}
+
+/// Play a song of the GBS file for the frames asked, the hash is the one of its audio
+fn play_gbs(options: &Options, data: Vec<u8>) {
+    if options.until.is_some() || options.serial || options.dump_frame.is_some() || options.printer.is_some() {
+        eprintln!("--until, --until-serial, --serial, --dump-frame and --printer need a rom\n{}", USAGE);
+        std::process::exit(EXIT_USAGE);
+    }
+
+    let mut player = match GbsPlayer::load(data) {
+        Ok(player) => player,
+        Err(err) => {
+            error!("Failed to load {}: {}", options.rom_path.display(), err);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+    if let Some(track) = options.track {
+        if track > player.header().song_count {
+            error!("No track {}, {} has {}", track, options.rom_path.display(), player.header().song_count);
+            std::process::exit(EXIT_ERROR);
+        }
+        player.start_song(track - 1);
+    }
+    player.spu_mut().set_charge_factor(options.model.charge_factor());
+    player.spu_mut().set_low_pass(options.low_pass);
+    info!("{} - {}, track {}/{}", player.header().title, player.header().author, player.song() + 1, player.header().song_count);
+
+    let mut audio = Recorder::new(Extractor::new(AudioHash { hash: FNV_OFFSET }));
+    if let Some(path) = &options.record {
+        if let Err(err) = audio.start(path, options.stems) {
+            error!("Failed to record to {}: {}", path.display(), err);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+    if let Some(path) = &options.midi {
+        if let Err(err) = audio.inner_mut().start(path) {
+            error!("Failed to extract notes to {}: {}", path.display(), err);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+
+    for _ in 0..options.frames {
+        player.run_next_frame(&mut audio);
+    }
+
+    if let Err(err) = audio.stop() {
+        error!("Failed to write the recording: {}", err);
+        std::process::exit(EXIT_ERROR);
+    }
+    if let Err(err) = audio.inner_mut().stop() {
+        error!("Failed to write the notes: {}", err);
+        std::process::exit(EXIT_ERROR);
+    }
+
+    if let Err(err) = write_gbs_results(options, &player, audio.inner().inner().hash) {
+        error!("Failed to write results: {}", err);
+        std::process::exit(EXIT_ERROR);
+    }
+}
+
+fn write_gbs_results(options: &Options, player: &GbsPlayer, audio_hash: u64) -> io::Result<()> {
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    if options.hash {
+        writeln!(out, "{:016x}", audio_hash)?;
+    }
+    if let Some(region) = options.dump_mem {
+        write_region(&mut out, player, region)?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_condition_test() {
+        assert_eq!(Ok(Condition::Memory { addr: 0xA000, value: 0x80 }), parse_condition("A000=80"));
+        assert_eq!(Ok(Condition::Memory { addr: 0xFF44, value: 0x90 }), parse_condition("0xff44=$90"));
+        assert!(parse_condition("A000").is_err());
+        assert!(parse_condition("A000=100").is_err());
+    }
+
+    #[test]
+    fn parse_region_test() {
+        assert_eq!(Ok(Region { addr: 0xC000, len: 0x100 }), parse_region("C000:100"));
+        assert!(parse_region("FFFF:2").is_err());
+    }
+
+    #[test]
+    fn fnv1a_test() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, fnv1a(b""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, fnv1a(b"a"));
+    }
+}
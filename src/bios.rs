@@ -1,5 +1,10 @@
-#[allow(dead_code)]
-pub const DMG_BIOS: [u8; 0x100] = [
+use crate::mmu::BOOT_ROM_SIZE;
+
+use std::convert::TryInto;
+use std::io;
+use std::path::Path;
+
+pub const DMG_BIOS: [u8; BOOT_ROM_SIZE] = [
     0x31, 0xfe, 0xff, // LD SP, $fffe      ; $0000 Setup Stack
     0xaf,             // XOR A             ; $0003 Zero the memory from $8000-$9fff (VRAM)
     0x21, 0xff, 0x9f, // LD HL, $9ffff     ; $0004
@@ -175,3 +180,10 @@ pub const DMG_BIOS: [u8; 0x100] = [
     0x3e, 0x01,        // LD A, $01        ; $00fc
     0xe0, 0x50,        // LD ($FF00+50), A ; $00fe Turn Off DMG ROM
 ];
+
+/// Read a boot rom dump, it must be exactly BOOT_ROM_SIZE bytes
+pub fn read_boot_rom(path: &Path) -> io::Result<Box<[u8; BOOT_ROM_SIZE]>> {
+    std::fs::read(path)?.into_boxed_slice().try_into().map_err(|data: Box<[u8]>| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("expected {} bytes, got {}", BOOT_ROM_SIZE, data.len()))
+    })
+}
@@ -0,0 +1,124 @@
+pub mod header;
+pub mod mbc1;
+pub mod mbc2;
+pub mod mbc3;
+pub mod mbc5;
+pub mod rom_only;
+pub mod rtc;
+pub mod save;
+
+use header::{CartridgeHeader, Mbc};
+use mbc1::Mbc1;
+use mbc2::Mbc2;
+use mbc3::Mbc3;
+use mbc5::Mbc5;
+use rom_only::RomOnly;
+use rtc::Rtc;
+
+use crate::MemoryBus;
+
+use std::fmt;
+
+/// Game Pak plugged into the cartridge slot.
+///
+/// The cartridge is mapped by the MMU on two address ranges:
+/// - $0000..=$7FFF (ROM, writes go to the bank controller registers)
+/// - $A000..=$BFFF (External RAM)
+pub trait Cartridge: MemoryBus + fmt::Debug + Send {
+    fn header(&self) -> &CartridgeHeader;
+
+    /// Copy of the whole cartridge state, for save states
+    fn box_clone(&self) -> Box<dyn Cartridge>;
+
+    fn rom(&self) -> &[u8];
+    fn rom_mut(&mut self) -> &mut [u8];
+
+    fn ram(&self) -> &[u8];
+    fn ram_mut(&mut self) -> &mut [u8];
+
+    fn rtc(&self) -> Option<&Rtc> { None }
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> { None }
+
+    /// Rumble motor state, on cartridges that have one
+    fn rumble(&self) -> bool { false }
+
+    /// Whether RAM or the clock took a write since the last call, clearing it
+    fn take_dirty(&mut self) -> bool { false }
+
+    /// Advance the cartridge hardware (e.g. the real time clock) by the emulated ticks
+    fn step(&mut self, _ticks: u64) { }
+}
+
+impl Clone for Box<dyn Cartridge> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CartridgeError {
+    /// ROM image does not contain a complete header ($0100..=$014F)
+    MissingHeader(usize),
+
+    /// Cartridge type code ($0147) is not a known value
+    UnknownType(u8),
+
+    /// Cartridge type is known but its bank controller is not emulated
+    UnsupportedType(u8, Mbc),
+
+    /// ROM size code ($0148) is not a known value
+    UnknownRomSize(u8),
+
+    /// RAM size code ($0149) is not a known value
+    UnknownRamSize(u8),
+
+    /// ROM image is smaller than the size declared on the header
+    TruncatedRom { expected: usize, actual: usize },
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingHeader(len) =>
+                write!(f, "rom image too small to contain a header ({} bytes)", len),
+            Self::UnknownType(code) =>
+                write!(f, "unknown cartridge type ${:02X}", code),
+            Self::UnsupportedType(code, mbc) =>
+                write!(f, "unsupported cartridge type ${:02X} ({:?})", code, mbc),
+            Self::UnknownRomSize(code) =>
+                write!(f, "unknown rom size code ${:02X}", code),
+            Self::UnknownRamSize(code) =>
+                write!(f, "unknown ram size code ${:02X}", code),
+            Self::TruncatedRom { expected, actual } =>
+                write!(f, "truncated rom image (expected {} bytes, got {})", expected, actual),
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError { }
+
+/// Parse the cartridge header and build the matching bank controller.
+pub fn load(rom: Vec<u8>) -> Result<Box<dyn Cartridge>, CartridgeError> {
+    let header = CartridgeHeader::parse(&rom)?;
+
+    if rom.len() < header.rom_size {
+        return Err(CartridgeError::TruncatedRom { expected: header.rom_size, actual: rom.len() });
+    }
+
+    if !header.is_header_checksum_valid(&rom) {
+        warn!("cartridge header checksum mismatch (${:02X})", header.header_checksum);
+    }
+
+    if !header.is_global_checksum_valid(&rom) {
+        debug!("cartridge global checksum mismatch (${:04X})", header.global_checksum);
+    }
+
+    match header.cartridge_type.mbc {
+        Mbc::None => Ok(Box::new(RomOnly::new(header, rom))),
+        Mbc::Mbc1 => Ok(Box::new(Mbc1::new(header, rom))),
+        Mbc::Mbc2 => Ok(Box::new(Mbc2::new(header, rom))),
+        Mbc::Mbc3 => Ok(Box::new(Mbc3::new(header, rom))),
+        Mbc::Mbc5 => Ok(Box::new(Mbc5::new(header, rom))),
+        mbc => Err(CartridgeError::UnsupportedType(header.cartridge_type.code, mbc)),
+    }
+}
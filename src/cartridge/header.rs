@@ -0,0 +1,299 @@
+use super::CartridgeError;
+
+pub const HEADER_END: usize = 0x0150;
+
+const TITLE_ADDR: usize = 0x0134;
+const CGB_FLAG_ADDR: usize = 0x0143;
+const NEW_LICENSEE_ADDR: usize = 0x0144;
+const SGB_FLAG_ADDR: usize = 0x0146;
+const CARTRIDGE_TYPE_ADDR: usize = 0x0147;
+const ROM_SIZE_ADDR: usize = 0x0148;
+const RAM_SIZE_ADDR: usize = 0x0149;
+const DESTINATION_ADDR: usize = 0x014A;
+const OLD_LICENSEE_ADDR: usize = 0x014B;
+const VERSION_ADDR: usize = 0x014C;
+const HEADER_CHECKSUM_ADDR: usize = 0x014D;
+const GLOBAL_CHECKSUM_ADDR: usize = 0x014E;
+
+/// Old licensee value telling that the new licensee code ($0144..=$0145) is used instead
+const USE_NEW_LICENSEE: u8 = 0x33;
+
+/// Memory Bank Controller
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mbc {
+    None,
+    Mbc1,
+    Mbc2,
+    Mmm01,
+    Mbc3,
+    Mbc5,
+    Mbc6,
+    Mbc7,
+    PocketCamera,
+    Tama5,
+    HuC3,
+    HuC1,
+}
+
+/// 0147 - Cartridge Type
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CartridgeType {
+    pub code: u8,
+    pub mbc: Mbc,
+    pub ram: bool,
+    pub battery: bool,
+    pub timer: bool,
+    pub rumble: bool,
+}
+
+impl CartridgeType {
+    pub fn from_code(code: u8) -> Option<Self> {
+        //                              MBC                 RAM    BATT   TIMER  RUMBLE
+        let (mbc, ram, battery, timer, rumble) = match code {
+            0x00 => (Mbc::None,         false, false, false, false),
+            0x01 => (Mbc::Mbc1,         false, false, false, false),
+            0x02 => (Mbc::Mbc1,         true,  false, false, false),
+            0x03 => (Mbc::Mbc1,         true,  true,  false, false),
+            0x05 => (Mbc::Mbc2,         false, false, false, false),
+            0x06 => (Mbc::Mbc2,         false, true,  false, false),
+            0x08 => (Mbc::None,         true,  false, false, false),
+            0x09 => (Mbc::None,         true,  true,  false, false),
+            0x0B => (Mbc::Mmm01,        false, false, false, false),
+            0x0C => (Mbc::Mmm01,        true,  false, false, false),
+            0x0D => (Mbc::Mmm01,        true,  true,  false, false),
+            0x0F => (Mbc::Mbc3,         false, true,  true,  false),
+            0x10 => (Mbc::Mbc3,         true,  true,  true,  false),
+            0x11 => (Mbc::Mbc3,         false, false, false, false),
+            0x12 => (Mbc::Mbc3,         true,  false, false, false),
+            0x13 => (Mbc::Mbc3,         true,  true,  false, false),
+            0x19 => (Mbc::Mbc5,         false, false, false, false),
+            0x1A => (Mbc::Mbc5,         true,  false, false, false),
+            0x1B => (Mbc::Mbc5,         true,  true,  false, false),
+            0x1C => (Mbc::Mbc5,         false, false, false, true),
+            0x1D => (Mbc::Mbc5,         true,  false, false, true),
+            0x1E => (Mbc::Mbc5,         true,  true,  false, true),
+            0x20 => (Mbc::Mbc6,         true,  true,  false, false),
+            0x22 => (Mbc::Mbc7,         true,  true,  false, true),
+            0xFC => (Mbc::PocketCamera, true,  true,  false, false),
+            0xFD => (Mbc::Tama5,        true,  true,  true,  false),
+            0xFE => (Mbc::HuC3,         true,  true,  true,  false),
+            0xFF => (Mbc::HuC1,         true,  true,  false, false),
+            _ => return None,
+        };
+        Some(Self { code, mbc, ram, battery, timer, rumble })
+    }
+}
+
+/// 0143 - CGB Flag
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CgbFlag {
+    /// DMG game
+    None,
+    /// Game supports CGB functions, but works on old gameboys also ($80)
+    Supported,
+    /// Game works on CGB only ($C0)
+    Required,
+}
+
+impl From<u8> for CgbFlag {
+    fn from(value: u8) -> Self {
+        match value {
+            0x80 => Self::Supported,
+            0xC0 => Self::Required,
+            _ => Self::None,
+        }
+    }
+}
+
+/// 014B - Old Licensee Code, 0144-0145 - New Licensee Code
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Licensee {
+    Old(u8),
+    New([u8; 2]),
+}
+
+/// Cartridge Header ($0100..=$014F)
+#[derive(Clone, Debug, PartialEq)]
+pub struct CartridgeHeader {
+    pub title: String,
+    pub cgb_flag: CgbFlag,
+    pub sgb_flag: bool,
+    pub cartridge_type: CartridgeType,
+    pub rom_size: usize,
+    pub ram_size: usize,
+    pub japanese: bool,
+    pub licensee: Licensee,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+}
+
+impl CartridgeHeader {
+    pub fn parse(rom: &[u8]) -> Result<Self, CartridgeError> {
+        if rom.len() < HEADER_END {
+            return Err(CartridgeError::MissingHeader(rom.len()));
+        }
+
+        let cgb_flag = CgbFlag::from(rom[CGB_FLAG_ADDR]);
+
+        // On CGB cartridges the last byte of the title is reused as the CGB flag
+        let title_end = match cgb_flag {
+            CgbFlag::None => CGB_FLAG_ADDR + 1,
+            _ => CGB_FLAG_ADDR,
+        };
+        let title: String = rom[TITLE_ADDR..title_end].iter()
+            .take_while(|c| **c != 0)
+            .map(|c| if c.is_ascii_graphic() || *c == b' ' { *c as char } else { '?' })
+            .collect();
+
+        let code = rom[CARTRIDGE_TYPE_ADDR];
+        let cartridge_type = CartridgeType::from_code(code)
+            .ok_or(CartridgeError::UnknownType(code))?;
+
+        let code = rom[ROM_SIZE_ADDR];
+        let rom_size = rom_size(code).ok_or(CartridgeError::UnknownRomSize(code))?;
+
+        let code = rom[RAM_SIZE_ADDR];
+        let ram_size = ram_size(code).ok_or(CartridgeError::UnknownRamSize(code))?;
+
+        let licensee = match rom[OLD_LICENSEE_ADDR] {
+            USE_NEW_LICENSEE => Licensee::New([rom[NEW_LICENSEE_ADDR], rom[NEW_LICENSEE_ADDR + 1]]),
+            code => Licensee::Old(code),
+        };
+
+        Ok(Self {
+            title,
+            cgb_flag,
+            sgb_flag: rom[SGB_FLAG_ADDR] == 0x03,
+            cartridge_type,
+            rom_size,
+            ram_size,
+            japanese: rom[DESTINATION_ADDR] == 0x00,
+            licensee,
+            version: rom[VERSION_ADDR],
+            header_checksum: rom[HEADER_CHECKSUM_ADDR],
+            global_checksum: u16::from_be_bytes([rom[GLOBAL_CHECKSUM_ADDR], rom[GLOBAL_CHECKSUM_ADDR + 1]]),
+        })
+    }
+
+    /// x=0:FOR i=0134h TO 014Ch:x=x-MEM[i]-1:NEXT
+    pub fn compute_header_checksum(rom: &[u8]) -> u8 {
+        rom[TITLE_ADDR..HEADER_CHECKSUM_ADDR].iter()
+            .fold(0u8, |x, b| x.wrapping_sub(*b).wrapping_sub(1))
+    }
+
+    /// Sum of all rom bytes, except the two checksum bytes
+    pub fn compute_global_checksum(rom: &[u8]) -> u16 {
+        rom.iter()
+            .enumerate()
+            .filter(|(i, _)| *i != GLOBAL_CHECKSUM_ADDR && *i != GLOBAL_CHECKSUM_ADDR + 1)
+            .fold(0u16, |x, (_, b)| x.wrapping_add(*b as u16))
+    }
+
+    /// Checked by the boot rom, which locks up on mismatch
+    pub fn is_header_checksum_valid(&self, rom: &[u8]) -> bool {
+        self.header_checksum == Self::compute_header_checksum(rom)
+    }
+
+    /// Not checked by the hardware
+    pub fn is_global_checksum_valid(&self, rom: &[u8]) -> bool {
+        self.global_checksum == Self::compute_global_checksum(rom)
+    }
+}
+
+/// 0148 - ROM Size
+fn rom_size(code: u8) -> Option<usize> {
+    match code {
+        0x00..=0x08 => Some(0x8000 << code),
+        0x52 => Some(72 * 0x4000),
+        0x53 => Some(80 * 0x4000),
+        0x54 => Some(96 * 0x4000),
+        _ => None,
+    }
+}
+
+/// 0149 - RAM Size
+fn ram_size(code: u8) -> Option<usize> {
+    match code {
+        0x00 => Some(0),
+        0x01 => Some(0x800),
+        0x02 => Some(0x2000),
+        0x03 => Some(0x8000),
+        0x04 => Some(0x20000),
+        0x05 => Some(0x10000),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom(cartridge_type: u8, rom_size: u8, ram_size: u8) -> Vec<u8> {
+        let mut rom = vec![0; 0x8000];
+        rom[TITLE_ADDR..TITLE_ADDR + 6].copy_from_slice(b"TETRIS");
+        rom[CARTRIDGE_TYPE_ADDR] = cartridge_type;
+        rom[ROM_SIZE_ADDR] = rom_size;
+        rom[RAM_SIZE_ADDR] = ram_size;
+        rom[DESTINATION_ADDR] = 0x01;
+        rom[OLD_LICENSEE_ADDR] = 0x01;
+        rom[HEADER_CHECKSUM_ADDR] = CartridgeHeader::compute_header_checksum(&rom);
+        rom
+    }
+
+    #[test]
+    fn parse_test() {
+        let rom = rom(0x03, 0x02, 0x03);
+        let header = CartridgeHeader::parse(&rom).unwrap();
+
+        assert_eq!("TETRIS", header.title);
+        assert_eq!(CgbFlag::None, header.cgb_flag);
+        assert_eq!(Mbc::Mbc1, header.cartridge_type.mbc);
+        assert!(header.cartridge_type.ram);
+        assert!(header.cartridge_type.battery);
+        assert!(!header.cartridge_type.timer);
+        assert_eq!(0x20000, header.rom_size);
+        assert_eq!(0x8000, header.ram_size);
+        assert_eq!(Licensee::Old(0x01), header.licensee);
+        assert!(!header.japanese);
+        assert!(header.is_header_checksum_valid(&rom));
+    }
+
+    #[test]
+    fn parse_cgb_title_test() {
+        let mut rom = rom(0x00, 0x00, 0x00);
+        rom[TITLE_ADDR..CGB_FLAG_ADDR].copy_from_slice(b"POKEMON_SLVAAXE");
+        rom[CGB_FLAG_ADDR] = 0x80;
+        rom[OLD_LICENSEE_ADDR] = USE_NEW_LICENSEE;
+        rom[NEW_LICENSEE_ADDR] = b'0';
+        rom[NEW_LICENSEE_ADDR + 1] = b'1';
+
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert_eq!("POKEMON_SLVAAXE", header.title);
+        assert_eq!(CgbFlag::Supported, header.cgb_flag);
+        assert_eq!(Licensee::New([b'0', b'1']), header.licensee);
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(Err(CartridgeError::MissingHeader(0x100)), CartridgeHeader::parse(&[0; 0x100]));
+        assert_eq!(Err(CartridgeError::UnknownType(0x04)), CartridgeHeader::parse(&rom(0x04, 0x00, 0x00)));
+        assert_eq!(Err(CartridgeError::UnknownRomSize(0x09)), CartridgeHeader::parse(&rom(0x00, 0x09, 0x00)));
+        assert_eq!(Err(CartridgeError::UnknownRamSize(0x06)), CartridgeHeader::parse(&rom(0x00, 0x00, 0x06)));
+    }
+
+    #[test]
+    fn checksum_test() {
+        let mut rom = rom(0x00, 0x00, 0x00);
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert!(header.is_header_checksum_valid(&rom));
+
+        rom[TITLE_ADDR] = b'X';
+        assert!(!header.is_header_checksum_valid(&rom));
+
+        let global_checksum = CartridgeHeader::compute_global_checksum(&rom);
+        rom[GLOBAL_CHECKSUM_ADDR..GLOBAL_CHECKSUM_ADDR + 2].copy_from_slice(&global_checksum.to_be_bytes());
+        assert_eq!(global_checksum, CartridgeHeader::compute_global_checksum(&rom));
+        assert!(CartridgeHeader::parse(&rom).unwrap().is_global_checksum_valid(&rom));
+    }
+}
@@ -0,0 +1,256 @@
+use super::Cartridge;
+use super::header::CartridgeHeader;
+
+use crate::MemoryBus;
+use crate::bios::DMG_BIOS;
+
+const ROM_BANK_SIZE: usize = 0x4000;
+const RAM_BANK_SIZE: usize = 0x2000;
+
+/// Nintendo logo, used to detect the games packed on a multicart
+const LOGO_BEGIN: usize = 0xA8;
+const LOGO_END: usize = 0xD8;
+const LOGO_ADDR: usize = 0x0104;
+
+/// MBC1 (max 2MByte ROM and/or 32KByte RAM)
+///
+/// - 0000-1FFF - RAM Enable (Write Only)
+///   00h  Disable RAM (default)
+///   0Ah  Enable RAM
+/// - 2000-3FFF - ROM Bank Number (Write Only)
+///   Lower 5 bits of the ROM Bank Number, 00h is translated to 01h
+/// - 4000-5FFF - RAM Bank Number - or - Upper Bits of ROM Bank Number (Write Only)
+/// - 6000-7FFF - ROM/RAM Mode Select (Write Only)
+///   00h = Simple Banking Mode (default)
+///   01h = RAM Banking Mode / Advanced ROM Banking Mode
+///
+/// MBC1M multicarts (1MByte) wire only 4 bits of the lower bank register, so the
+/// upper register selects one of the four 256KByte games.
+#[derive(Clone, Debug)]
+pub struct Mbc1 {
+    header: CartridgeHeader,
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+
+    multicart: bool,
+
+    ram_enable: bool,
+    dirty: bool,
+    bank1: u8,
+    bank2: u8,
+    mode: bool,
+}
+
+impl Mbc1 {
+    pub fn new(header: CartridgeHeader, rom: Vec<u8>) -> Self {
+        let ram = vec![0; if header.cartridge_type.ram { header.ram_size } else { 0 }];
+        let multicart = is_multicart(&rom);
+        if multicart {
+            info!("MBC1M multicart detected");
+        }
+
+        Self {
+            header,
+            rom,
+            ram,
+            multicart,
+            ram_enable: false,
+            dirty: false,
+            bank1: 1,
+            bank2: 0,
+            mode: false,
+        }
+    }
+
+    pub fn is_multicart(&self) -> bool {
+        self.multicart
+    }
+
+    fn bank2_shift(&self) -> u32 {
+        if self.multicart { 4 } else { 5 }
+    }
+
+    /// Bank mapped at $0000..=$3FFF
+    pub fn rom_bank_0(&self) -> usize {
+        if self.mode {
+            (self.bank2 as usize) << self.bank2_shift()
+        } else {
+            0
+        }
+    }
+
+    /// Bank mapped at $4000..=$7FFF
+    pub fn rom_bank_n(&self) -> usize {
+        let bank1 = if self.multicart { self.bank1 & 0x0F } else { self.bank1 };
+        (self.bank2 as usize) << self.bank2_shift() | bank1 as usize
+    }
+
+    /// Bank mapped at $A000..=$BFFF
+    pub fn ram_bank(&self) -> usize {
+        if self.mode { self.bank2 as usize } else { 0 }
+    }
+
+    fn rom_offset(&self, bank: usize, addr: u16) -> usize {
+        let banks = (self.rom.len() / ROM_BANK_SIZE).max(1);
+        (bank % banks) * ROM_BANK_SIZE + (addr as usize % ROM_BANK_SIZE)
+    }
+
+    fn ram_offset(&self, addr: u16) -> usize {
+        (self.ram_bank() * RAM_BANK_SIZE + (addr as usize - 0xA000)) % self.ram.len()
+    }
+}
+
+/// A multicart has another game header (and logo) on the bank $10
+fn is_multicart(rom: &[u8]) -> bool {
+    const GAME_SIZE: usize = 0x10 * ROM_BANK_SIZE;
+    if rom.len() != 0x100000 {
+        return false;
+    }
+
+    let logo = &DMG_BIOS[LOGO_BEGIN..LOGO_END];
+    (1..4).any(|game| {
+        let addr = game * GAME_SIZE + LOGO_ADDR;
+        &rom[addr..addr + logo.len()] == logo
+    })
+}
+
+impl MemoryBus for Mbc1 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom.get(self.rom_offset(self.rom_bank_0(), addr)).copied().unwrap_or(0xFF),
+            0x4000..=0x7FFF => self.rom.get(self.rom_offset(self.rom_bank_n(), addr)).copied().unwrap_or(0xFF),
+            0xA000..=0xBFFF if self.ram_enable && !self.ram.is_empty() => self.ram[self.ram_offset(addr)],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enable = data & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.bank1 = if data & 0x1F == 0 { 1 } else { data & 0x1F },
+            0x4000..=0x5FFF => self.bank2 = data & 0x03,
+            0x6000..=0x7FFF => self.mode = data & 0x01 != 0,
+            0xA000..=0xBFFF if self.ram_enable && !self.ram.is_empty() => {
+                let offset = self.ram_offset(addr);
+                self.ram[offset] = data;
+                self.dirty = true;
+            }
+            _ => { }
+        }
+    }
+}
+
+impl Cartridge for Mbc1 {
+    fn header(&self) -> &CartridgeHeader { &self.header }
+    fn box_clone(&self) -> Box<dyn Cartridge> { Box::new(self.clone()) }
+
+    fn rom(&self) -> &[u8] { &self.rom }
+    fn rom_mut(&mut self) -> &mut [u8] { &mut self.rom }
+
+    fn ram(&self) -> &[u8] { &self.ram }
+    fn ram_mut(&mut self) -> &mut [u8] { &mut self.ram }
+
+    fn take_dirty(&mut self) -> bool { std::mem::take(&mut self.dirty) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mbc1(rom_size: usize, ram_size: usize) -> Mbc1 {
+        // Tag each bank with its own number
+        let mut rom = vec![0; rom_size];
+        for (bank, data) in rom.chunks_mut(ROM_BANK_SIZE).enumerate() {
+            data[0] = bank as u8;
+        }
+        rom[0x0147] = 0x03;
+        rom[0x0149] = 0x03;
+
+        let mut header = CartridgeHeader::parse(&rom).unwrap();
+        header.rom_size = rom_size;
+        header.ram_size = ram_size;
+        Mbc1::new(header, rom)
+    }
+
+    #[test]
+    fn rom_bank_test() {
+        let mut mbc = mbc1(0x200000, 0);
+        assert_eq!(0x00, mbc.read(0x0000));
+        assert_eq!(0x01, mbc.read(0x4000));
+
+        // bank 0 is translated to 1
+        mbc.write(0x2000, 0x00);
+        assert_eq!(0x01, mbc.read(0x4000));
+
+        mbc.write(0x2000, 0x1F);
+        assert_eq!(0x1F, mbc.read(0x4000));
+
+        // upper bits
+        mbc.write(0x4000, 0x03);
+        assert_eq!(0x7F, mbc.read(0x4000));
+        assert_eq!(0x00, mbc.read(0x0000));
+
+        // bank 0 area follows upper bits on advanced mode
+        mbc.write(0x6000, 0x01);
+        assert_eq!(0x60, mbc.read(0x0000));
+
+        // bank 20h can't be mapped on $4000
+        mbc.write(0x4000, 0x01);
+        mbc.write(0x2000, 0x20);
+        assert_eq!(0x21, mbc.read(0x4000));
+    }
+
+    #[test]
+    fn rom_bank_mask_test() {
+        let mut mbc = mbc1(0x40000, 0);
+        mbc.write(0x2000, 0x12);
+        assert_eq!(0x02, mbc.read(0x4000));
+    }
+
+    #[test]
+    fn ram_bank_test() {
+        let mut mbc = mbc1(0x8000, 0x8000);
+
+        // disabled
+        mbc.write(0xA000, 0x55);
+        assert_eq!(0xFF, mbc.read(0xA000));
+
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0xA000, 0x55);
+        assert_eq!(0x55, mbc.read(0xA000));
+
+        // simple mode always maps the ram bank 0
+        mbc.write(0x4000, 0x02);
+        assert_eq!(0x55, mbc.read(0xA000));
+
+        mbc.write(0x6000, 0x01);
+        assert_eq!(0x00, mbc.read(0xA000));
+        mbc.write(0xA000, 0xAA);
+        assert_eq!(0xAA, mbc.ram()[2 * RAM_BANK_SIZE]);
+
+        mbc.write(0x0000, 0x00);
+        assert_eq!(0xFF, mbc.read(0xA000));
+    }
+
+    #[test]
+    fn multicart_test() {
+        let mut mbc = mbc1(0x100000, 0);
+        assert!(!mbc.is_multicart());
+
+        let mut rom = mbc.rom().to_vec();
+        let logo = &DMG_BIOS[LOGO_BEGIN..LOGO_END];
+        rom[0x40000 + LOGO_ADDR..0x40000 + LOGO_ADDR + logo.len()].copy_from_slice(logo);
+        mbc = Mbc1::new(mbc.header().clone(), rom);
+        assert!(mbc.is_multicart());
+
+        mbc.write(0x2000, 0x12);
+        assert_eq!(0x02, mbc.read(0x4000));
+
+        mbc.write(0x4000, 0x01);
+        assert_eq!(0x12, mbc.read(0x4000));
+        assert_eq!(0x00, mbc.read(0x0000));
+
+        mbc.write(0x6000, 0x01);
+        assert_eq!(0x10, mbc.read(0x0000));
+    }
+}
@@ -0,0 +1,133 @@
+use super::Cartridge;
+use super::header::CartridgeHeader;
+
+use crate::MemoryBus;
+
+const ROM_BANK_SIZE: usize = 0x4000;
+const RAM_SIZE: usize = 0x200;
+
+/// MBC2 (max 256KByte ROM and 512x4 bits RAM)
+///
+/// - 0000-3FFF - RAM Enable and ROM Bank Number (Write Only)
+///   The least significant bit of the upper address byte selects the register:
+///   Bit 8 = 0  RAM Enable (0Ah enables the RAM)
+///   Bit 8 = 1  ROM Bank Number (4 bits, 00h is translated to 01h)
+/// - A000-A1FF - 512x4bits RAM, built-in into the MBC2 chip (Read/Write)
+///   Only the lower 4 bits are used, the upper bits read as 1.
+/// - A200-BFFF - 15 "echoes" of A000-A1FF
+#[derive(Clone, Debug)]
+pub struct Mbc2 {
+    header: CartridgeHeader,
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+
+    ram_enable: bool,
+    dirty: bool,
+    rom_bank: u8,
+}
+
+impl Mbc2 {
+    pub fn new(header: CartridgeHeader, rom: Vec<u8>) -> Self {
+        Self {
+            header,
+            rom,
+            ram: vec![0; RAM_SIZE],
+            ram_enable: false,
+            dirty: false,
+            rom_bank: 1,
+        }
+    }
+
+    fn rom_offset(&self, bank: usize, addr: u16) -> usize {
+        let banks = (self.rom.len() / ROM_BANK_SIZE).max(1);
+        (bank % banks) * ROM_BANK_SIZE + (addr as usize % ROM_BANK_SIZE)
+    }
+}
+
+impl MemoryBus for Mbc2 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
+            0x4000..=0x7FFF => self.rom.get(self.rom_offset(self.rom_bank as usize, addr)).copied().unwrap_or(0xFF),
+            0xA000..=0xBFFF if self.ram_enable => 0xF0 | self.ram[addr as usize % RAM_SIZE],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x0000..=0x3FFF if addr & 0x0100 == 0 => self.ram_enable = data & 0x0F == 0x0A,
+            0x0000..=0x3FFF => self.rom_bank = if data & 0x0F == 0 { 1 } else { data & 0x0F },
+            0xA000..=0xBFFF if self.ram_enable => {
+                self.ram[addr as usize % RAM_SIZE] = data & 0x0F;
+                self.dirty = true;
+            }
+            _ => { }
+        }
+    }
+}
+
+impl Cartridge for Mbc2 {
+    fn header(&self) -> &CartridgeHeader { &self.header }
+    fn box_clone(&self) -> Box<dyn Cartridge> { Box::new(self.clone()) }
+
+    fn rom(&self) -> &[u8] { &self.rom }
+    fn rom_mut(&mut self) -> &mut [u8] { &mut self.rom }
+
+    fn ram(&self) -> &[u8] { &self.ram }
+    fn ram_mut(&mut self) -> &mut [u8] { &mut self.ram }
+
+    fn take_dirty(&mut self) -> bool { std::mem::take(&mut self.dirty) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mbc2() -> Mbc2 {
+        // Tag each bank with its own number
+        let mut rom = vec![0; 0x40000];
+        for (bank, data) in rom.chunks_mut(ROM_BANK_SIZE).enumerate() {
+            data[0] = bank as u8;
+        }
+        rom[0x0147] = 0x06;
+        rom[0x0148] = 0x03;
+        Mbc2::new(CartridgeHeader::parse(&rom).unwrap(), rom)
+    }
+
+    #[test]
+    fn register_decoding_test() {
+        let mut mbc = mbc2();
+
+        // bit 8 clear selects the ram enable register
+        mbc.write(0x2000, 0x05);
+        assert_eq!(0x01, mbc.read(0x4000));
+        assert_eq!(0xFF, mbc.read(0xA000));
+
+        // bit 8 set selects the rom bank register
+        mbc.write(0x2100, 0x05);
+        assert_eq!(0x05, mbc.read(0x4000));
+        mbc.write(0x3FFF, 0x00);
+        assert_eq!(0x01, mbc.read(0x4000));
+
+        mbc.write(0x0100, 0x0A);
+        assert_eq!(0xFF, mbc.read(0xA000));
+        mbc.write(0x00FF, 0x0A);
+        assert_eq!(0xF0, mbc.read(0xA000));
+    }
+
+    #[test]
+    fn ram_test() {
+        let mut mbc = mbc2();
+        mbc.write(0x0000, 0x0A);
+
+        mbc.write(0xA000, 0xAB);
+        assert_eq!(0xFB, mbc.read(0xA000));
+        assert_eq!(0x0B, mbc.ram()[0]);
+
+        // echoes
+        assert_eq!(0xFB, mbc.read(0xA200));
+        mbc.write(0xBFFF, 0x03);
+        assert_eq!(0xF3, mbc.read(0xA1FF));
+    }
+}
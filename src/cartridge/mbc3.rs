@@ -0,0 +1,226 @@
+use super::Cartridge;
+use super::header::CartridgeHeader;
+use super::rtc::{Rtc, RTC_S, RTC_DH};
+
+use crate::MemoryBus;
+
+const ROM_BANK_SIZE: usize = 0x4000;
+const RAM_BANK_SIZE: usize = 0x2000;
+
+/// MBC3 (max 2MByte ROM and/or 32KByte RAM and Timer)
+///
+/// - 0000-1FFF - RAM and Timer Enable (Write Only)
+///   00h  Disable RAM and RTC (default)
+///   0Ah  Enable RAM and RTC
+/// - 2000-3FFF - ROM Bank Number (Write Only)
+///   7 bits of the ROM Bank Number, 00h is translated to 01h
+/// - 4000-5FFF - RAM Bank Number - or - RTC Register Select (Write Only)
+///   00h-03h maps the RAM bank, 08h-0Ch maps the RTC register
+/// - 6000-7FFF - Latch Clock Data (Write Only)
+///   Writing 00h and then 01h latches the current time into the RTC registers
+#[derive(Clone, Debug)]
+pub struct Mbc3 {
+    header: CartridgeHeader,
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    rtc: Option<Rtc>,
+
+    ram_enable: bool,
+    dirty: bool,
+    rom_bank: u8,
+    ram_select: u8,
+    latch: u8,
+}
+
+impl Mbc3 {
+    pub fn new(header: CartridgeHeader, rom: Vec<u8>) -> Self {
+        let ram = vec![0; if header.cartridge_type.ram { header.ram_size } else { 0 }];
+        let rtc = if header.cartridge_type.timer { Some(Rtc::default()) } else { None };
+
+        Self {
+            header,
+            rom,
+            ram,
+            rtc,
+            ram_enable: false,
+            dirty: false,
+            rom_bank: 1,
+            ram_select: 0,
+            latch: 0xFF,
+        }
+    }
+
+    fn rom_offset(&self, bank: usize, addr: u16) -> usize {
+        let banks = (self.rom.len() / ROM_BANK_SIZE).max(1);
+        (bank % banks) * ROM_BANK_SIZE + (addr as usize % ROM_BANK_SIZE)
+    }
+
+    fn ram_offset(&self, addr: u16) -> usize {
+        (self.ram_select as usize * RAM_BANK_SIZE + (addr as usize - 0xA000)) % self.ram.len()
+    }
+}
+
+impl MemoryBus for Mbc3 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
+            0x4000..=0x7FFF => self.rom.get(self.rom_offset(self.rom_bank as usize, addr)).copied().unwrap_or(0xFF),
+            0xA000..=0xBFFF if self.ram_enable => match (self.ram_select, &self.rtc) {
+                (0x00..=0x03, _) if !self.ram.is_empty() => self.ram[self.ram_offset(addr)],
+                (RTC_S..=RTC_DH, Some(rtc)) => rtc.latched_register(self.ram_select),
+                _ => 0xFF,
+            },
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enable = data & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = if data & 0x7F == 0 { 1 } else { data & 0x7F },
+            0x4000..=0x5FFF => self.ram_select = data & 0x0F,
+            0x6000..=0x7FFF => {
+                if self.latch == 0x00 && data == 0x01 {
+                    if let Some(rtc) = &mut self.rtc {
+                        rtc.latch();
+                        self.dirty = true;
+                    }
+                }
+                self.latch = data;
+            }
+            0xA000..=0xBFFF if self.ram_enable => match self.ram_select {
+                0x00..=0x03 if !self.ram.is_empty() => {
+                    let offset = self.ram_offset(addr);
+                    self.ram[offset] = data;
+                    self.dirty = true;
+                }
+                RTC_S..=RTC_DH => {
+                    if let Some(rtc) = &mut self.rtc {
+                        rtc.set_register(self.ram_select, data);
+                        self.dirty = true;
+                    }
+                }
+                _ => { }
+            },
+            _ => { }
+        }
+    }
+}
+
+impl Cartridge for Mbc3 {
+    fn header(&self) -> &CartridgeHeader { &self.header }
+    fn box_clone(&self) -> Box<dyn Cartridge> { Box::new(self.clone()) }
+
+    fn rom(&self) -> &[u8] { &self.rom }
+    fn rom_mut(&mut self) -> &mut [u8] { &mut self.rom }
+
+    fn ram(&self) -> &[u8] { &self.ram }
+    fn ram_mut(&mut self) -> &mut [u8] { &mut self.ram }
+
+    fn rtc(&self) -> Option<&Rtc> { self.rtc.as_ref() }
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> { self.rtc.as_mut() }
+
+    fn take_dirty(&mut self) -> bool { std::mem::take(&mut self.dirty) }
+
+    fn step(&mut self, ticks: u64) {
+        if let Some(rtc) = &mut self.rtc {
+            rtc.step(ticks);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::rtc::RTC_M;
+    use crate::TICKS_PER_SECOND;
+
+    fn mbc3(cartridge_type: u8) -> Mbc3 {
+        // Tag each bank with its own number
+        let mut rom = vec![0; 0x200000];
+        for (bank, data) in rom.chunks_mut(ROM_BANK_SIZE).enumerate() {
+            data[0] = bank as u8;
+        }
+        rom[0x0147] = cartridge_type;
+        rom[0x0148] = 0x06;
+        rom[0x0149] = 0x03;
+        Mbc3::new(CartridgeHeader::parse(&rom).unwrap(), rom)
+    }
+
+    #[test]
+    fn rom_bank_test() {
+        let mut mbc = mbc3(0x13);
+        assert_eq!(0x01, mbc.read(0x4000));
+
+        mbc.write(0x2000, 0x00);
+        assert_eq!(0x01, mbc.read(0x4000));
+
+        mbc.write(0x2000, 0x7F);
+        assert_eq!(0x7F, mbc.read(0x4000));
+        assert_eq!(0x00, mbc.read(0x0000));
+    }
+
+    #[test]
+    fn ram_bank_test() {
+        let mut mbc = mbc3(0x13);
+        mbc.write(0xA000, 0x55);
+        assert_eq!(0xFF, mbc.read(0xA000));
+
+        mbc.write(0x0000, 0x0A);
+        for bank in 0..4u8 {
+            mbc.write(0x4000, bank);
+            mbc.write(0xA000, bank + 1);
+        }
+        for bank in 0..4u8 {
+            mbc.write(0x4000, bank);
+            assert_eq!(bank + 1, mbc.read(0xA000));
+            assert_eq!(bank + 1, mbc.ram()[bank as usize * RAM_BANK_SIZE]);
+        }
+
+        // no timer on this cartridge
+        mbc.write(0x4000, RTC_S);
+        assert_eq!(0xFF, mbc.read(0xA000));
+    }
+
+    #[test]
+    fn rtc_latch_test() {
+        let mut mbc = mbc3(0x10);
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0x4000, RTC_S);
+
+        mbc.step(TICKS_PER_SECOND * 5);
+        assert_eq!(0x00, mbc.read(0xA000));
+
+        mbc.write(0x6000, 0x00);
+        mbc.write(0x6000, 0x01);
+        assert_eq!(0x05, mbc.read(0xA000));
+
+        // only the 00h => 01h sequence latches
+        mbc.step(TICKS_PER_SECOND * 5);
+        mbc.write(0x6000, 0x01);
+        assert_eq!(0x05, mbc.read(0xA000));
+
+        mbc.write(0x6000, 0x00);
+        mbc.write(0x6000, 0x01);
+        assert_eq!(0x0A, mbc.read(0xA000));
+    }
+
+    #[test]
+    fn rtc_write_test() {
+        let mut mbc = mbc3(0x10);
+        mbc.write(0x0000, 0x0A);
+
+        mbc.write(0x4000, RTC_DH);
+        mbc.write(0xA000, 0x40);
+        mbc.write(0x4000, RTC_M);
+        mbc.write(0xA000, 0x2A);
+
+        mbc.step(TICKS_PER_SECOND * 120);
+        mbc.write(0x6000, 0x00);
+        mbc.write(0x6000, 0x01);
+        assert_eq!(0x2A, mbc.read(0xA000));
+
+        mbc.write(0x4000, RTC_DH);
+        assert_eq!(0x40, mbc.read(0xA000));
+    }
+}
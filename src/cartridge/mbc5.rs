@@ -0,0 +1,165 @@
+use super::Cartridge;
+use super::header::CartridgeHeader;
+
+use crate::MemoryBus;
+
+const ROM_BANK_SIZE: usize = 0x4000;
+const RAM_BANK_SIZE: usize = 0x2000;
+
+const RUMBLE_MOTOR: u8 = 0b0000_1000;
+
+/// MBC5 (max 8MByte ROM and/or 128KByte RAM)
+///
+/// - 0000-1FFF - RAM Enable (Write Only)
+///   0Ah enables the RAM, any other value disables it
+/// - 2000-2FFF - Low 8 bits of ROM Bank Number (Write Only)
+///   Bank 00h can be mapped at 4000-7FFF
+/// - 3000-3FFF - High bit of ROM Bank Number (Write Only)
+/// - 4000-5FFF - RAM Bank Number (Write Only)
+///   00h-0Fh, on rumble cartridges bit 3 drives the rumble motor instead
+#[derive(Clone, Debug)]
+pub struct Mbc5 {
+    header: CartridgeHeader,
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+
+    ram_enable: bool,
+    dirty: bool,
+    rom_bank: u16,
+    ram_bank: u8,
+    rumble: bool,
+}
+
+impl Mbc5 {
+    pub fn new(header: CartridgeHeader, rom: Vec<u8>) -> Self {
+        let ram = vec![0; if header.cartridge_type.ram { header.ram_size } else { 0 }];
+
+        Self {
+            header,
+            rom,
+            ram,
+            ram_enable: false,
+            dirty: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            rumble: false,
+        }
+    }
+
+    fn rom_offset(&self, bank: usize, addr: u16) -> usize {
+        let banks = (self.rom.len() / ROM_BANK_SIZE).max(1);
+        (bank % banks) * ROM_BANK_SIZE + (addr as usize % ROM_BANK_SIZE)
+    }
+
+    fn ram_offset(&self, addr: u16) -> usize {
+        (self.ram_bank as usize * RAM_BANK_SIZE + (addr as usize - 0xA000)) % self.ram.len()
+    }
+}
+
+impl MemoryBus for Mbc5 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
+            0x4000..=0x7FFF => self.rom.get(self.rom_offset(self.rom_bank as usize, addr)).copied().unwrap_or(0xFF),
+            0xA000..=0xBFFF if self.ram_enable && !self.ram.is_empty() => self.ram[self.ram_offset(addr)],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enable = data == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | data as u16,
+            0x3000..=0x3FFF => self.rom_bank = (self.rom_bank & 0x0FF) | ((data & 0x01) as u16) << 8,
+            0x4000..=0x5FFF => {
+                if self.header.cartridge_type.rumble {
+                    self.rumble = data & RUMBLE_MOTOR != 0;
+                    self.ram_bank = data & 0x07;
+                } else {
+                    self.ram_bank = data & 0x0F;
+                }
+            }
+            0xA000..=0xBFFF if self.ram_enable && !self.ram.is_empty() => {
+                let offset = self.ram_offset(addr);
+                self.ram[offset] = data;
+                self.dirty = true;
+            }
+            _ => { }
+        }
+    }
+}
+
+impl Cartridge for Mbc5 {
+    fn header(&self) -> &CartridgeHeader { &self.header }
+    fn box_clone(&self) -> Box<dyn Cartridge> { Box::new(self.clone()) }
+
+    fn rom(&self) -> &[u8] { &self.rom }
+    fn rom_mut(&mut self) -> &mut [u8] { &mut self.rom }
+
+    fn ram(&self) -> &[u8] { &self.ram }
+    fn ram_mut(&mut self) -> &mut [u8] { &mut self.ram }
+
+    fn take_dirty(&mut self) -> bool { std::mem::take(&mut self.dirty) }
+
+    fn rumble(&self) -> bool { self.rumble }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mbc5(cartridge_type: u8) -> Mbc5 {
+        // Tag each bank with its own number (lsb, msb)
+        let mut rom = vec![0; 0x800000];
+        for (bank, data) in rom.chunks_mut(ROM_BANK_SIZE).enumerate() {
+            data[0] = bank as u8;
+            data[1] = (bank >> 8) as u8;
+        }
+        rom[0x0147] = cartridge_type;
+        rom[0x0148] = 0x08;
+        rom[0x0149] = 0x04;
+        Mbc5::new(CartridgeHeader::parse(&rom).unwrap(), rom)
+    }
+
+    #[test]
+    fn rom_bank_test() {
+        let mut mbc = mbc5(0x1B);
+        assert_eq!(0x01, mbc.read(0x4000));
+
+        mbc.write(0x2000, 0x00);
+        assert_eq!(0x00, mbc.read(0x4000));
+
+        mbc.write(0x2000, 0xFF);
+        mbc.write(0x3000, 0x01);
+        assert_eq!(0xFF, mbc.read(0x4000));
+        assert_eq!(0x01, mbc.read(0x4001));
+    }
+
+    #[test]
+    fn ram_bank_test() {
+        let mut mbc = mbc5(0x1B);
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0x4000, 0x0F);
+        mbc.write(0xA000, 0x55);
+        assert_eq!(0x55, mbc.ram()[0x0F * RAM_BANK_SIZE]);
+        assert!(!mbc.rumble());
+
+        mbc.write(0x0000, 0x1A);
+        assert_eq!(0xFF, mbc.read(0xA000));
+    }
+
+    #[test]
+    fn rumble_test() {
+        let mut mbc = mbc5(0x1E);
+        mbc.write(0x0000, 0x0A);
+
+        mbc.write(0x4000, 0x0B);
+        assert!(mbc.rumble());
+        mbc.write(0xA000, 0x55);
+        assert_eq!(0x55, mbc.ram()[0x03 * RAM_BANK_SIZE]);
+
+        mbc.write(0x4000, 0x03);
+        assert!(!mbc.rumble());
+        assert_eq!(0x55, mbc.read(0xA000));
+    }
+}
@@ -0,0 +1,63 @@
+use super::Cartridge;
+use super::header::CartridgeHeader;
+
+use crate::MemoryBus;
+
+/// Cartridge without bank controller
+/// - $0000..=$7FFF (32KiB ROM)
+/// - $A000..=$BFFF (Optional 8KiB RAM)
+#[derive(Clone, Debug)]
+pub struct RomOnly {
+    header: CartridgeHeader,
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    dirty: bool,
+}
+
+impl RomOnly {
+    pub fn new(header: CartridgeHeader, rom: Vec<u8>) -> Self {
+        let ram = vec![0; header.ram_size];
+        Self { header, rom, ram, dirty: false }
+    }
+
+    /// Blank 32KiB ROM and 8KiB RAM, used while no cartridge is inserted
+    pub fn blank() -> Self {
+        let rom = vec![0; 0x8000];
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        Self { header, rom, ram: vec![0; 0x2000], dirty: false }
+    }
+}
+
+impl MemoryBus for RomOnly {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
+            0xA000..=0xBFFF if !self.ram.is_empty() => self.ram[(addr as usize - 0xA000) % self.ram.len()],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0xA000..=0xBFFF if !self.ram.is_empty() => {
+                let len = self.ram.len();
+                self.ram[(addr as usize - 0xA000) % len] = data;
+                self.dirty = true;
+            }
+            _ => { }
+        }
+    }
+}
+
+impl Cartridge for RomOnly {
+    fn header(&self) -> &CartridgeHeader { &self.header }
+    fn box_clone(&self) -> Box<dyn Cartridge> { Box::new(self.clone()) }
+
+    fn rom(&self) -> &[u8] { &self.rom }
+    fn rom_mut(&mut self) -> &mut [u8] { &mut self.rom }
+
+    fn ram(&self) -> &[u8] { &self.ram }
+    fn ram_mut(&mut self) -> &mut [u8] { &mut self.ram }
+
+    fn take_dirty(&mut self) -> bool { std::mem::take(&mut self.dirty) }
+}
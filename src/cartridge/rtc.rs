@@ -0,0 +1,276 @@
+use crate::TICKS_PER_SECOND;
+
+use std::convert::TryInto;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const RTC_S: u8 = 0x08;
+pub const RTC_M: u8 = 0x09;
+pub const RTC_H: u8 = 0x0A;
+pub const RTC_DL: u8 = 0x0B;
+pub const RTC_DH: u8 = 0x0C;
+
+/// Footer appended to the save file by most emulators (VBA-M, BGB, SameBoy, mGBA)
+/// - 5 x u32 live registers (S, M, H, DL, DH)
+/// - 5 x u32 latched registers (S, M, H, DL, DH)
+/// - u64 unix timestamp of when the save was written (some write only 32 bits)
+pub const RTC_FOOTER_SIZE: usize = 48;
+pub const RTC_FOOTER_SIZE_32: usize = 44;
+
+const DH_DAY_MSB: u8 = 0b0000_0001;
+const DH_HALT: u8 = 0b0100_0000;
+const DH_DAY_CARRY: u8 = 0b1000_0000;
+
+/// MBC3 Real Time Clock
+///
+/// - 08h  RTC S   Seconds   0-59 (0-3Bh)
+/// - 09h  RTC M   Minutes   0-59 (0-3Bh)
+/// - 0Ah  RTC H   Hours     0-23 (0-17h)
+/// - 0Bh  RTC DL  Lower 8 bits of Day Counter (0-FFh)
+/// - 0Ch  RTC DH  Upper 1 bit of Day Counter, Carry Bit, Halt Flag
+///   Bit 0  Most significant bit of Day Counter (Bit 8)
+///   Bit 6  Halt (0=Active, 1=Stop Timer)
+///   Bit 7  Day Counter Carry Bit (1=Counter Overflow)
+///
+/// The clock is driven by the emulated cycles, so it stays deterministic.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Rtc {
+    ticks: u64,
+
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    days: u16,
+    halt: bool,
+    carry: bool,
+
+    latched: [u8; 5],
+}
+
+impl Rtc {
+    /// Live register value
+    pub fn register(&self, reg: u8) -> u8 {
+        match reg {
+            RTC_S => self.seconds,
+            RTC_M => self.minutes,
+            RTC_H => self.hours,
+            RTC_DL => (self.days & 0xFF) as u8,
+            RTC_DH => {
+                let mut dh = (self.days >> 8) as u8 & DH_DAY_MSB;
+                if self.halt { dh |= DH_HALT; }
+                if self.carry { dh |= DH_DAY_CARRY; }
+                dh
+            }
+            _ => 0xFF,
+        }
+    }
+
+    pub fn set_register(&mut self, reg: u8, data: u8) {
+        match reg {
+            RTC_S => {
+                // Writing the seconds resets the sub-second divider
+                self.ticks = 0;
+                self.seconds = data & 0x3F;
+            }
+            RTC_M => self.minutes = data & 0x3F,
+            RTC_H => self.hours = data & 0x1F,
+            RTC_DL => self.days = (self.days & 0x100) | data as u16,
+            RTC_DH => {
+                self.days = (self.days & 0xFF) | ((data & DH_DAY_MSB) as u16) << 8;
+                self.halt = data & DH_HALT != 0;
+                self.carry = data & DH_DAY_CARRY != 0;
+            }
+            _ => { }
+        }
+    }
+
+    /// Latched register value, as seen by the CPU at $A000..=$BFFF
+    pub fn latched_register(&self, reg: u8) -> u8 {
+        match reg {
+            RTC_S..=RTC_DH => self.latched[(reg - RTC_S) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    /// Copy the live registers into the latched registers
+    pub fn latch(&mut self) {
+        for reg in RTC_S..=RTC_DH {
+            self.latched[(reg - RTC_S) as usize] = self.register(reg);
+        }
+    }
+
+    pub fn step(&mut self, ticks: u64) {
+        if self.halt {
+            return;
+        }
+
+        self.ticks += ticks;
+        if self.ticks >= TICKS_PER_SECOND {
+            let seconds = self.ticks / TICKS_PER_SECOND;
+            self.ticks %= TICKS_PER_SECOND;
+            self.advance(seconds);
+        }
+    }
+
+    /// Move the clock forward, unless it is halted
+    pub fn advance(&mut self, mut seconds: u64) {
+        if self.halt {
+            return;
+        }
+
+        // Out of range values (written by the game) count up to the register
+        // limit before wrapping, so tick them one by one until they settle
+        while seconds > 0 && (self.seconds >= 60 || self.minutes >= 60 || self.hours >= 24) {
+            self.tick();
+            seconds -= 1;
+        }
+
+        let total = self.seconds as u64
+            + self.minutes as u64 * 60
+            + self.hours as u64 * 3600
+            + self.days as u64 * 86400
+            + seconds;
+
+        let days = total / 86400;
+        self.seconds = (total % 60) as u8;
+        self.minutes = (total / 60 % 60) as u8;
+        self.hours = (total / 3600 % 24) as u8;
+        self.days = (days % 512) as u16;
+        self.carry |= days >= 512;
+    }
+
+    fn tick(&mut self) {
+        self.seconds = (self.seconds + 1) & 0x3F;
+        if self.seconds != 60 {
+            return;
+        }
+        self.seconds = 0;
+
+        self.minutes = (self.minutes + 1) & 0x3F;
+        if self.minutes != 60 {
+            return;
+        }
+        self.minutes = 0;
+
+        self.hours = (self.hours + 1) & 0x1F;
+        if self.hours != 24 {
+            return;
+        }
+        self.hours = 0;
+
+        self.days = (self.days + 1) & 0x1FF;
+        if self.days == 0 {
+            self.carry = true;
+        }
+    }
+
+    pub fn save_footer(&self, timestamp: u64) -> [u8; RTC_FOOTER_SIZE] {
+        let mut footer = [0; RTC_FOOTER_SIZE];
+        for reg in RTC_S..=RTC_DH {
+            let i = (reg - RTC_S) as usize;
+            footer[i * 4] = self.register(reg);
+            footer[20 + i * 4] = self.latched_register(reg);
+        }
+        footer[40..].copy_from_slice(&timestamp.to_le_bytes());
+        footer
+    }
+
+    /// Restore the registers from a save footer, returning the timestamp it was saved
+    pub fn load_footer(&mut self, footer: &[u8]) -> Option<u64> {
+        let timestamp = match footer.len() {
+            RTC_FOOTER_SIZE => u64::from_le_bytes(footer[40..48].try_into().unwrap()),
+            RTC_FOOTER_SIZE_32 => u32::from_le_bytes(footer[40..44].try_into().unwrap()) as u64,
+            _ => return None,
+        };
+
+        for reg in RTC_S..=RTC_DH {
+            let i = (reg - RTC_S) as usize;
+            self.set_register(reg, footer[i * 4]);
+            self.latched[i] = footer[20 + i * 4];
+        }
+        Some(timestamp)
+    }
+
+    /// Catch up with the host clock, given the unix timestamp of when the clock was saved
+    pub fn sync_to_host(&mut self, saved_timestamp: u64) {
+        let now = host_timestamp();
+        if now > saved_timestamp {
+            self.advance(now - saved_timestamp);
+        }
+    }
+}
+
+/// Seconds since the unix epoch
+pub fn host_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_test() {
+        let mut rtc = Rtc::default();
+        rtc.step(TICKS_PER_SECOND - 1);
+        assert_eq!(0, rtc.register(RTC_S));
+        rtc.step(1);
+        assert_eq!(1, rtc.register(RTC_S));
+
+        rtc.set_register(RTC_DH, DH_HALT);
+        rtc.step(TICKS_PER_SECOND * 10);
+        assert_eq!(1, rtc.register(RTC_S));
+    }
+
+    #[test]
+    fn advance_test() {
+        let mut rtc = Rtc::default();
+        rtc.advance(86400 + 3600 + 60 + 1);
+        assert_eq!(1, rtc.register(RTC_S));
+        assert_eq!(1, rtc.register(RTC_M));
+        assert_eq!(1, rtc.register(RTC_H));
+        assert_eq!(1, rtc.register(RTC_DL));
+        assert_eq!(0, rtc.register(RTC_DH));
+
+        rtc.set_register(RTC_DL, 0xFF);
+        rtc.set_register(RTC_DH, DH_DAY_MSB);
+        rtc.advance(86400);
+        assert_eq!(0, rtc.register(RTC_DL));
+        assert_eq!(DH_DAY_CARRY, rtc.register(RTC_DH));
+    }
+
+    #[test]
+    fn invalid_value_test() {
+        let mut rtc = Rtc::default();
+        rtc.set_register(RTC_S, 62);
+        rtc.advance(2);
+        assert_eq!(0, rtc.register(RTC_S));
+        assert_eq!(0, rtc.register(RTC_M));
+    }
+
+    #[test]
+    fn footer_test() {
+        let mut rtc = Rtc::default();
+        rtc.advance(86400 * 300 + 3600 * 5 + 60 * 4 + 3);
+        rtc.latch();
+        rtc.advance(1);
+
+        let footer = rtc.save_footer(0x1234_5678_9ABC);
+        let mut restored = Rtc::default();
+        assert_eq!(Some(0x1234_5678_9ABC), restored.load_footer(&footer));
+        assert_eq!(rtc.register(RTC_DH), restored.register(RTC_DH));
+        assert_eq!(rtc.latched, restored.latched);
+
+        assert_eq!(Some(0x5678_9ABC), restored.load_footer(&footer[..RTC_FOOTER_SIZE_32]));
+        assert_eq!(None, restored.load_footer(&footer[..40]));
+    }
+
+    #[test]
+    fn latch_test() {
+        let mut rtc = Rtc::default();
+        rtc.advance(30);
+        rtc.latch();
+        rtc.advance(10);
+        assert_eq!(30, rtc.latched_register(RTC_S));
+        assert_eq!(40, rtc.register(RTC_S));
+    }
+}
@@ -0,0 +1,119 @@
+use super::Cartridge;
+use super::rtc::{self, RTC_FOOTER_SIZE, RTC_FOOTER_SIZE_32};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Battery backed RAM file, stored next to the rom as `<rom>.sav`
+pub fn save_path(rom_path: &Path) -> PathBuf {
+    rom_path.with_extension("sav")
+}
+
+/// Save file contents: the external RAM followed by the RTC footer (MBC3 only)
+pub fn encode(cartridge: &dyn Cartridge) -> Vec<u8> {
+    let mut data = cartridge.ram().to_vec();
+    if let Some(rtc) = cartridge.rtc() {
+        data.extend_from_slice(&rtc.save_footer(rtc::host_timestamp()));
+    }
+    data
+}
+
+/// Restore the external RAM and RTC from a save file contents.
+///
+/// When `sync_rtc` is set, the clock also advances by the host time elapsed since the save.
+pub fn decode(cartridge: &mut dyn Cartridge, data: &[u8], sync_rtc: bool) {
+    let ram = cartridge.ram_mut();
+    let len = ram.len().min(data.len());
+    ram[..len].copy_from_slice(&data[..len]);
+
+    if data.len() < ram.len() {
+        warn!("save file smaller than cartridge ram ({} < {} bytes)", data.len(), ram.len());
+    }
+
+    let footer = &data[len..];
+    if let Some(rtc) = cartridge.rtc_mut() {
+        match footer.len() {
+            0 => { }
+            RTC_FOOTER_SIZE | RTC_FOOTER_SIZE_32 => {
+                let timestamp = rtc.load_footer(footer).unwrap();
+                if sync_rtc {
+                    rtc.sync_to_host(timestamp);
+                }
+            }
+            len => warn!("ignoring unknown save file footer ({} bytes)", len),
+        }
+    }
+}
+
+/// Load the save file, returns false when the file does not exist yet
+pub fn load(cartridge: &mut dyn Cartridge, path: &Path, sync_rtc: bool) -> io::Result<bool> {
+    match fs::read(path) {
+        Ok(data) => {
+            decode(cartridge, &data, sync_rtc);
+            Ok(true)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+pub fn store(cartridge: &dyn Cartridge, path: &Path) -> io::Result<()> {
+    fs::write(path, encode(cartridge))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::header::CartridgeHeader;
+    use super::super::mbc3::Mbc3;
+    use super::super::rtc::RTC_S;
+    use crate::MemoryBus;
+
+    fn mbc3(cartridge_type: u8) -> Mbc3 {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0147] = cartridge_type;
+        rom[0x0149] = 0x02;
+        Mbc3::new(CartridgeHeader::parse(&rom).unwrap(), rom)
+    }
+
+    #[test]
+    fn save_path_test() {
+        assert_eq!(PathBuf::from("roms/pokemon.sav"), save_path(Path::new("roms/pokemon.gbc")));
+        assert_eq!(PathBuf::from("tetris.sav"), save_path(Path::new("tetris")));
+    }
+
+    #[test]
+    fn ram_only_test() {
+        let mut cartridge = mbc3(0x13);
+        cartridge.ram_mut()[0] = 0x12;
+        cartridge.ram_mut()[0x1FFF] = 0x34;
+
+        let data = encode(&cartridge);
+        assert_eq!(0x2000, data.len());
+
+        let mut restored = mbc3(0x13);
+        decode(&mut restored, &data, false);
+        assert_eq!(cartridge.ram(), restored.ram());
+    }
+
+    #[test]
+    fn rtc_footer_test() {
+        let mut cartridge = mbc3(0x10);
+        cartridge.ram_mut()[0] = 0x12;
+        cartridge.rtc_mut().unwrap().advance(42);
+
+        let data = encode(&cartridge);
+        assert_eq!(0x2000 + RTC_FOOTER_SIZE, data.len());
+
+        let mut restored = mbc3(0x10);
+        decode(&mut restored, &data, false);
+        assert_eq!(0x12, restored.ram()[0]);
+        assert_eq!(42, restored.rtc().unwrap().register(RTC_S));
+
+        // latched registers are restored too
+        restored.write(0x0000, 0x0A);
+        restored.write(0x4000, RTC_S);
+        assert_eq!(0x00, restored.read(0xA000));
+    }
+}
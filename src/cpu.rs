@@ -8,14 +8,12 @@ pub mod interrupt_service;
 #[cfg(test)]
 mod tests;
 
-use std::ptr;
 use registers::Registers;
 use interrupt::Interrupt;
 use interrupt_service::InterruptService;
-use crate::mmu::Mmu;
 use crate::MemoryBus;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Cpu {
     // Registers
     pub (crate) regs: Registers,
@@ -24,21 +22,6 @@ pub struct Cpu {
     pub (crate) int_svc: InterruptService,
 
     next_pc: u16,
-
-    // Memory Management Unit
-    pub mmu: *mut Mmu,
-
-}
-
-impl Default for Cpu {
-    fn default() -> Self {
-        Self {
-            regs: Registers::default(),
-            int_svc: InterruptService::default(),
-            next_pc: 0,
-            mmu: ptr::null_mut(),
-        }
-    }
 }
 
 const IF_ADDR: u16 = 0xFF0F;
@@ -69,6 +52,22 @@ impl Cpu {
         self.int_svc.latch_interrupt_flags(int);
     }
 
+    /// Bus read, the interrupt registers (IF, IE) live in the CPU
+    fn load<B: MemoryBus>(&self, bus: &B, addr: u16) -> u8 {
+        match addr {
+            IF_ADDR | IE_ADDR => self.read(addr),
+            _ => bus.read(addr),
+        }
+    }
+
+    /// Bus write, the interrupt registers (IF, IE) live in the CPU
+    fn store<B: MemoryBus>(&mut self, bus: &mut B, addr: u16, data: u8) {
+        match addr {
+            IF_ADDR | IE_ADDR => self.write(addr, data),
+            _ => bus.write(addr, data),
+        }
+    }
+
     fn jump_absolute(&mut self, target: u16) {
         self.next_pc = target;
     }
@@ -95,14 +94,14 @@ impl Cpu {
         }
     }
 
-    unsafe fn subroutine_call(&mut self, target: u16) {
-        self.stack_push(self.next_pc);
+    fn subroutine_call<B: MemoryBus>(&mut self, bus: &mut B, target: u16) {
+        self.stack_push(bus, self.next_pc);
         self.next_pc = target;
     }
 
-    unsafe fn subroutine_call_if(&mut self, target: u16, cond: bool) -> u64 {
+    fn subroutine_call_if<B: MemoryBus>(&mut self, bus: &mut B, target: u16, cond: bool) -> u64 {
         if cond {
-            self.stack_push(self.next_pc);
+            self.stack_push(bus, self.next_pc);
             self.next_pc = target;
             12
         } else {
@@ -110,61 +109,62 @@ impl Cpu {
         }
     }
 
-    unsafe fn subroutine_return(&mut self) {
-        self.next_pc = self.stack_pop();
+    fn subroutine_return<B: MemoryBus>(&mut self, bus: &mut B) {
+        self.next_pc = self.stack_pop(bus);
     }
 
-    unsafe fn subroutine_return_if(&mut self, cond: bool) -> u64 {
+    fn subroutine_return_if<B: MemoryBus>(&mut self, bus: &mut B, cond: bool) -> u64 {
         if cond {
-            self.next_pc = self.stack_pop();
+            self.next_pc = self.stack_pop(bus);
             12
         } else {
             0
         }
     }
 
-    unsafe fn stack_push(&mut self, data: u16) {
+    fn stack_push<B: MemoryBus>(&mut self, bus: &mut B, data: u16) {
         let [lsb, msb] = data.to_le_bytes();
         let sp = self.regs.sp();
 
         let sp = sp.wrapping_sub(1);
-        (*self.mmu).write(sp, msb);
+        self.store(bus, sp, msb);
 
         let sp = sp.wrapping_sub(1);
-        (*self.mmu).write(sp, lsb);
+        self.store(bus, sp, lsb);
 
         self.regs.set_sp(sp);
     }
 
-    unsafe fn stack_pop(&mut self) -> u16 {
+    fn stack_pop<B: MemoryBus>(&mut self, bus: &mut B) -> u16 {
         let sp = self.regs.sp();
 
-        let lsb = (*self.mmu).read(sp);
+        let lsb = self.load(bus, sp);
         let sp = sp.wrapping_add(1);
 
-        let msb = (*self.mmu).read(sp);
+        let msb = self.load(bus, sp);
         let sp = sp.wrapping_add(1);
 
         self.regs.set_sp(sp);
         u16::from_le_bytes([lsb, msb])
     }
 
-    pub fn cycle(&mut self) -> u64 {
+    /// Run one instruction against the given bus, returning the ticks it took
+    pub fn cycle<B: MemoryBus>(&mut self, bus: &mut B) -> u64 {
         self.int_svc.interrupt_service_preamble();
 
-        let mut ticks: u64 = unsafe {
+        let mut ticks: u64 = {
             let pc = self.regs.pc();
-            let opcode = (*self.mmu).read(pc);
+            let opcode = self.load(bus, pc);
 
             let pc = pc.wrapping_add(1);
-            let imm8 = (*self.mmu).read(pc);
+            let imm8 = self.load(bus, pc);
 
             let pc = pc.wrapping_add(1);
-            let imm16 = u16::from_le_bytes([imm8, (*self.mmu).read(pc)]);
+            let imm16 = u16::from_le_bytes([imm8, self.load(bus, pc)]);
 
             // trace!(("${:04x} {:<15} {:02x?}", pc, asm::disassemble(opcode, imm8, imm16), self.regs);
 
-            self.fetch_decode_execute_store_cycle(opcode, imm8, imm16)
+            self.fetch_decode_execute_store_cycle(bus, opcode, imm8, imm16)
         };
 
         // HALT Handler
@@ -173,19 +173,16 @@ impl Cpu {
         }
 
         // Execute Interruptions
-        match self.int_svc.interrupt_service_routine() {
-            Some(addr) => unsafe {
-                self.subroutine_call(addr);
-                ticks += 12;
-            }
-            None => { }
+        if let Some(addr) = self.int_svc.interrupt_service_routine() {
+            self.subroutine_call(bus, addr);
+            ticks += 12;
         }
         self.regs.set_pc(self.next_pc);
 
         ticks
     }
 
-    unsafe fn fetch_decode_execute_store_cycle(&mut self, opcode: u8, imm8: u8, imm16: u16) -> u64 {
+    fn fetch_decode_execute_store_cycle<B: MemoryBus>(&mut self, bus: &mut B, opcode: u8, imm8: u8, imm16: u16) -> u64 {
         self.next_pc = self.regs.pc() + asm::instruction_size(opcode);
         let mut ticks = asm::instruction_ticks(opcode);
 
@@ -200,7 +197,7 @@ impl Cpu {
             }
             0x02 => {
                 // LD (BC), A
-                (*self.mmu).write(self.regs.bc(), self.regs.a());
+                self.store(bus, self.regs.bc(), self.regs.a());
             },
             0x03 => {
                 // INC BC
@@ -235,8 +232,8 @@ impl Cpu {
             0x08 => {
                 // LD ($0000),SP
                 let [lsb, msb] = self.regs.sp().to_le_bytes();
-                (*self.mmu).write(imm16, lsb);
-                (*self.mmu).write(imm16.wrapping_add(1), msb);
+                self.store(bus, imm16, lsb);
+                self.store(bus, imm16.wrapping_add(1), msb);
             }
             0x09 => {
                 // ADD HL, BC
@@ -246,7 +243,7 @@ impl Cpu {
             }
             0x0A => {
                 // LD A, (BC)
-                let data: u8 = (*self.mmu).read(self.regs.bc());
+                let data: u8 = self.load(bus, self.regs.bc());
                 self.regs.set_a(data);
             }
             0x0B => {
@@ -288,7 +285,7 @@ impl Cpu {
             }
             0x12 => {
                 // LD (DE), A
-                (*self.mmu).write(self.regs.de(), self.regs.a());
+                self.store(bus, self.regs.de(), self.regs.a());
             }
             0x13 => {
                 // INC DE
@@ -332,7 +329,7 @@ impl Cpu {
             }
             0x1A => {
                 // LD A, (DE)
-                let data: u8 = (*self.mmu).read(self.regs.de());
+                let data: u8 = self.load(bus, self.regs.de());
                 self.regs.set_a(data);
             }
             0x1B => {
@@ -376,7 +373,7 @@ impl Cpu {
             0x22 => {
                 // LDI (HL), A
                 let hl = self.regs.hl();
-                (*self.mmu).write(hl, self.regs.a());
+                self.store(bus, hl, self.regs.a());
                 let hl = hl.wrapping_add(1);
                 self.regs.set_hl(hl);
             }
@@ -420,7 +417,7 @@ impl Cpu {
             0x2A => {
                 // LDI A, (HL)
                 let addr = self.regs.hl();
-                let data = (*self.mmu).read(addr);
+                let data = self.load(bus, addr);
                 self.regs.set_a(data);
                 self.regs.set_hl(addr.wrapping_add(1));
             }
@@ -462,7 +459,7 @@ impl Cpu {
             0x32 => {
                 // LDD (HL), A
                 let hl = self.regs.hl();
-                (*self.mmu).write(hl, self.regs.a());
+                self.store(bus, hl, self.regs.a());
                 let hl = hl.wrapping_sub(1);
                 self.regs.set_hl(hl);
             }
@@ -474,24 +471,24 @@ impl Cpu {
             0x34 => {
                 // INC (HL)
                 let addr = self.regs.hl();
-                let data = (*self.mmu).read(addr);
+                let data = self.load(bus, addr);
 
                 let (flags, data) = alu::inc(self.regs.flags(), data);
                 self.regs.set_flags(flags);
-                (*self.mmu).write(addr, data);
+                self.store(bus, addr, data);
             }
             0x35 => {
                 // DEC (HL)
                 let addr = self.regs.hl();
-                let data = (*self.mmu).read(addr);
+                let data = self.load(bus, addr);
 
                 let (flags, data) = alu::dec(self.regs.flags(), data);
                 self.regs.set_flags(flags);
-                (*self.mmu).write(addr, data);
+                self.store(bus, addr, data);
             }
             0x36 => {
                 // LD (HL), $00
-                (*self.mmu).write(self.regs.hl(), imm8);
+                self.store(bus, self.regs.hl(), imm8);
             }
             0x37 => {
                 // SCF
@@ -515,7 +512,7 @@ impl Cpu {
             0x3A => {
                 // LDD A, (HL)
                 let addr = self.regs.hl();
-                let data = (*self.mmu).read(addr);
+                let data = self.load(bus, addr);
                 self.regs.set_a(data);
                 self.regs.set_hl(addr.wrapping_sub(1));
             }
@@ -573,7 +570,7 @@ impl Cpu {
             }
             0x46 => {
                 // LD B, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 self.regs.set_b(data);
             }
             0x47 => {
@@ -605,7 +602,7 @@ impl Cpu {
             }
             0x4E => {
                 // LD C, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 self.regs.set_c(data);
             }
             0x4F => {
@@ -637,7 +634,7 @@ impl Cpu {
             }
             0x56 => {
                 // LD D, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 self.regs.set_d(data);
             }
             0x57 => {
@@ -669,7 +666,7 @@ impl Cpu {
             }
             0x5E => {
                 // LD E, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 self.regs.set_e(data);
             }
             0x5F => {
@@ -701,7 +698,7 @@ impl Cpu {
             }
             0x66 => {
                 // LD H, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 self.regs.set_h(data);
             }
             0x67 => {
@@ -733,7 +730,7 @@ impl Cpu {
             }
             0x6E => {
                 // LD L, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 self.regs.set_l(data);
             }
             0x6F => {
@@ -742,27 +739,27 @@ impl Cpu {
             }
             0x70 => {
                 // LD (HL), B
-                (*self.mmu).write(self.regs.hl(), self.regs.b());
+                self.store(bus, self.regs.hl(), self.regs.b());
             }
             0x71 => {
                 // LD (HL), C
-                (*self.mmu).write(self.regs.hl(), self.regs.c());
+                self.store(bus, self.regs.hl(), self.regs.c());
             }
             0x72 => {
                 // LD (HL), D
-                (*self.mmu).write(self.regs.hl(), self.regs.d());
+                self.store(bus, self.regs.hl(), self.regs.d());
             }
             0x73 => {
                 // LD (HL), E
-                (*self.mmu).write(self.regs.hl(), self.regs.e());
+                self.store(bus, self.regs.hl(), self.regs.e());
             }
             0x74 => {
                 // LD (HL), H
-                (*self.mmu).write(self.regs.hl(), self.regs.h());
+                self.store(bus, self.regs.hl(), self.regs.h());
             }
             0x75 => {
                 // LD (HL), L
-                (*self.mmu).write(self.regs.hl(), self.regs.l());
+                self.store(bus, self.regs.hl(), self.regs.l());
             }
             0x76 => {
                 // HALT
@@ -770,7 +767,7 @@ impl Cpu {
             }
             0x77 => {
                 // LD (HL), A
-                (*self.mmu).write(self.regs.hl(), self.regs.a());
+                self.store(bus, self.regs.hl(), self.regs.a());
             }
             0x78 => {
                 // LD A, B
@@ -798,7 +795,7 @@ impl Cpu {
             }
             0x7E => {
                 // LD A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 self.regs.set_a(data);
             }
             0x7F => {
@@ -842,7 +839,7 @@ impl Cpu {
             }
             0x86 => {
                 // ADD A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 let (flags, a) = alu::add(self.regs.a(), data);
                 self.regs.set_flags(flags);
                 self.regs.set_a(a);
@@ -891,7 +888,7 @@ impl Cpu {
             }
             0x8E => {
                 // ADC A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 let (flags, a) = alu::adc(self.regs.flags(), self.regs.a(), data);
                 self.regs.set_flags(flags);
                 self.regs.set_a(a);
@@ -940,7 +937,7 @@ impl Cpu {
             }
             0x96 => {
                 // SUB A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 let (flags, a) = alu::sub(self.regs.a(), data);
                 self.regs.set_flags(flags);
                 self.regs.set_a(a);
@@ -989,7 +986,7 @@ impl Cpu {
             }
             0x9E => {
                 // SBC A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 let (flags, a) = alu::sbc(self.regs.flags(), self.regs.a(), data);
                 self.regs.set_flags(flags);
                 self.regs.set_a(a);
@@ -1038,7 +1035,7 @@ impl Cpu {
             }
             0xA6 => {
                 // AND A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 let (flags, a) = alu::and(self.regs.a(), data);
                 self.regs.set_flags(flags);
                 self.regs.set_a(a);
@@ -1087,7 +1084,7 @@ impl Cpu {
             }
             0xAE => {
                 // XOR A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 let (flags, a) = alu::xor(self.regs.a(), data);
                 self.regs.set_flags(flags);
                 self.regs.set_a(a);
@@ -1136,7 +1133,7 @@ impl Cpu {
             }
             0xB6 => {
                 // OR A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 let (flags, a) = alu::or(self.regs.a(), data);
                 self.regs.set_flags(flags);
                 self.regs.set_a(a);
@@ -1179,7 +1176,7 @@ impl Cpu {
             }
             0xBE => {
                 // CP A, (HL)
-                let data = (*self.mmu).read(self.regs.hl());
+                let data = self.load(bus, self.regs.hl());
                 let (flags, _) = alu::sub(self.regs.a(), data);
                 self.regs.set_flags(flags);
             }
@@ -1190,11 +1187,11 @@ impl Cpu {
             }
             0xC0 => {
                 // RET NZ
-                ticks += self.subroutine_return_if(!self.regs.flags().zero());
+                ticks += self.subroutine_return_if(bus, !self.regs.flags().zero());
             }
             0xC1 => {
                 // POP BC
-                let bc = self.stack_pop();
+                let bc = self.stack_pop(bus);
                 self.regs.set_bc(bc);
             }
             0xC2 => {
@@ -1207,11 +1204,11 @@ impl Cpu {
             }
             0xC4 => {
                 // CALL NZ $0000
-                ticks += self.subroutine_call_if(imm16, !self.regs.flags().zero());
+                ticks += self.subroutine_call_if(bus, imm16, !self.regs.flags().zero());
             }
             0xC5 => {
                 // PUSH BC
-                self.stack_push(self.regs.bc());
+                self.stack_push(bus, self.regs.bc());
             }
             0xC6 => {
                 // ADD A, $00
@@ -1221,15 +1218,15 @@ impl Cpu {
             }
             0xC7 => {
                 // RST $00
-                self.subroutine_call(0x00);
+                self.subroutine_call(bus, 0x00);
             }
             0xC8 => {
                 // RET Z
-                ticks += self.subroutine_return_if(self.regs.flags().zero());
+                ticks += self.subroutine_return_if(bus, self.regs.flags().zero());
             }
             0xC9 => {
                 // RET
-                self.subroutine_return();
+                self.subroutine_return(bus);
             }
             0xCA => {
                 // JP Z $0000
@@ -1245,7 +1242,7 @@ impl Cpu {
                     0x3 => self.regs.e(),
                     0x4 => self.regs.h(),
                     0x5 => self.regs.l(),
-                    0x6 => (*self.mmu).read(self.regs.hl()),
+                    0x6 => self.load(bus, self.regs.hl()),
                     0x7 => self.regs.a(),
                     _ => panic!()
                 };
@@ -1311,7 +1308,7 @@ impl Cpu {
                         0x3 => self.regs.set_e(ret),
                         0x4 => self.regs.set_h(ret),
                         0x5 => self.regs.set_l(ret),
-                        0x6 => (*self.mmu).write(self.regs.hl(), ret),
+                        0x6 => self.store(bus, self.regs.hl(), ret),
                         0x7 => self.regs.set_a(ret),
                         _ => panic!()
                     }
@@ -1319,11 +1316,11 @@ impl Cpu {
             }
             0xCC => {
                 // CALL Z $0000
-                ticks += self.subroutine_call_if(imm16, self.regs.flags().zero());
+                ticks += self.subroutine_call_if(bus, imm16, self.regs.flags().zero());
             }
             0xCD => {
                 // CALL $0000
-                self.subroutine_call(imm16);
+                self.subroutine_call(bus, imm16);
             }
             0xCE => {
                 // ADC A, $00
@@ -1333,15 +1330,15 @@ impl Cpu {
             }
             0xCF => {
                 // RST $08
-                self.subroutine_call(0x08);
+                self.subroutine_call(bus, 0x08);
             }
             0xD0 => {
                 // RET NC
-                ticks += self.subroutine_return_if(!self.regs.flags().carry());
+                ticks += self.subroutine_return_if(bus, !self.regs.flags().carry());
             }
             0xD1 => {
                 // POP DE
-                let de = self.stack_pop();
+                let de = self.stack_pop(bus);
                 self.regs.set_de(de);
             }
             0xD2 => {
@@ -1353,11 +1350,11 @@ impl Cpu {
             }
             0xD4 => {
                 // CALL NC $0000
-                ticks += self.subroutine_call_if(imm16, !self.regs.flags().carry());
+                ticks += self.subroutine_call_if(bus, imm16, !self.regs.flags().carry());
             }
             0xD5 => {
                 // PUSH DE
-                self.stack_push(self.regs.de());
+                self.stack_push(bus, self.regs.de());
             }
             0xD6 => {
                 // SUB A, $00
@@ -1367,16 +1364,16 @@ impl Cpu {
             }
             0xD7 => {
                 // RST $10
-                self.subroutine_call(0x10);
+                self.subroutine_call(bus, 0x10);
             }
             0xD8 => {
                 // RET C
-                ticks += self.subroutine_return_if(self.regs.flags().carry());
+                ticks += self.subroutine_return_if(bus, self.regs.flags().carry());
             }
             0xD9 => {
                 // RETI
                 self.int_svc.enable_interrupt();
-                self.subroutine_return();
+                self.subroutine_return(bus);
             }
             0xDA => {
                 // JP C $0000
@@ -1387,7 +1384,7 @@ impl Cpu {
             }
             0xDC => {
                 // CALL C $0000
-                ticks += self.subroutine_call_if(imm16, self.regs.flags().carry())
+                ticks += self.subroutine_call_if(bus, imm16, self.regs.flags().carry())
             }
             0xDD => {
                 // [DD] - INVALID
@@ -1400,24 +1397,24 @@ impl Cpu {
             }
             0xDF => {
                 // RST $18
-                self.subroutine_call(0x18);
+                self.subroutine_call(bus, 0x18);
             }
             0xE0 => {
                 // LDH ($00), A
                 let addr: u16 = 0xff00u16 | imm8 as u16;
                 let data = self.regs.a();
-                (*self.mmu).write(addr, data);
+                self.store(bus, addr, data);
             }
             0xE1 => {
                 // POP HL
-                let hl = self.stack_pop();
+                let hl = self.stack_pop(bus);
                 self.regs.set_hl(hl);
             }
             0xE2 => {
                 // LDH (C), A
                 let addr = 0xff00u16 | self.regs.c() as u16;
                 let data = self.regs.a();
-                (*self.mmu).write(addr, data);
+                self.store(bus, addr, data);
             }
             0xE3 => {
                 // [E3] - INVALID
@@ -1427,7 +1424,7 @@ impl Cpu {
             }
             0xE5 => {
                 // PUSH HL
-                self.stack_push(self.regs.hl());
+                self.stack_push(bus, self.regs.hl());
             }
             0xE6 => {
                 // AND $00
@@ -1437,7 +1434,7 @@ impl Cpu {
             }
             0xE7 => {
                 // RST $20
-                self.subroutine_call(0x20);
+                self.subroutine_call(bus, 0x20);
             }
             0xE8 => {
                 // ADD SP, $00
@@ -1451,7 +1448,7 @@ impl Cpu {
             }
             0xEA => {
                 // LD ($0000), A
-                (*self.mmu).write(imm16, self.regs.a());
+                self.store(bus, imm16, self.regs.a());
             }
             0xEB => {
                 // [EB] - INVALID
@@ -1470,23 +1467,23 @@ impl Cpu {
             }
             0xEF => {
                 // RST $28
-                self.subroutine_call(0x28);
+                self.subroutine_call(bus, 0x28);
             }
             0xF0 => {
                 // LDH A, ($00)
                 let addr: u16 = 0xff00u16 | imm8 as u16;
-                let data = (*self.mmu).read(addr);
+                let data = self.load(bus, addr);
                 self.regs.set_a(data);
             }
             0xF1 => {
                 // POP AF
-                let af = self.stack_pop();
+                let af = self.stack_pop(bus);
                 self.regs.set_af(af);
             }
             0xF2 => {
                 // LD A, ($FF00+C)
                 let addr = 0xff00u16 | self.regs.c() as u16;
-                let data = (*self.mmu).read(addr);
+                let data = self.load(bus, addr);
                 self.regs.set_a(data);
             }
             0xF3 => {
@@ -1498,7 +1495,7 @@ impl Cpu {
             }
             0xF5 => {
                 // PUSH AF
-                self.stack_push(self.regs.af());
+                self.stack_push(bus, self.regs.af());
             }
             0xF6 => {
                 // OR $00
@@ -1508,7 +1505,7 @@ impl Cpu {
             }
             0xF7 => {
                 // RST $30
-                self.subroutine_call(0x30);
+                self.subroutine_call(bus, 0x30);
             }
             0xF8 => {
                 // LD HL,SP+$00
@@ -1522,7 +1519,7 @@ impl Cpu {
             }
             0xFA => {
                 // LD A, ($0000)
-                let data = (*self.mmu).read(imm16);
+                let data = self.load(bus, imm16);
                 self.regs.set_a(data);
             }
             0xFB => {
@@ -1542,7 +1539,7 @@ impl Cpu {
             }
             0xFF => {
                 // RST $38
-                self.subroutine_call(0x38);
+                self.subroutine_call(bus, 0x38);
             }
         }
 
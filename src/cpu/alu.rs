@@ -192,7 +192,7 @@ pub fn dec(mut flags: Flags, mut acc: u8) -> (Flags, u8) {
 /// - H: Set
 /// - C: Reset
 pub fn and( mut acc: u8, arg: u8) -> (Flags, u8) {
-    acc = acc & arg;
+    acc &= arg;
 
     let mut flags = Flags::H;
     flags.set_zero_if(acc == 0);
@@ -208,7 +208,7 @@ pub fn and( mut acc: u8, arg: u8) -> (Flags, u8) {
 /// - H: Reset
 /// - C: Reset
 pub fn or(mut acc: u8, arg: u8) -> (Flags, u8) {
-    acc = acc | arg;
+    acc |= arg;
 
     let flags = if acc == 0 { Flags::Z } else { Flags::empty() };
 
@@ -223,7 +223,7 @@ pub fn or(mut acc: u8, arg: u8) -> (Flags, u8) {
 /// - H: Reset
 /// - C: Reset
 pub fn xor(mut acc: u8, arg: u8) -> (Flags, u8) {
-    acc = acc ^ arg;
+    acc ^= arg;
 
     let flags = if acc == 0 { Flags::Z } else { Flags::empty() };
 
@@ -468,7 +468,7 @@ mod tests {
                 assert_eq!(expected, acc);
 
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(false, flags.sub());
+                assert!(!flags.sub());
                 assert_eq!(half, flags.half());
                 assert_eq!(carry, flags.carry());
             }
@@ -492,7 +492,7 @@ mod tests {
                     assert_eq!(expected, acc);
 
                     assert_eq!(acc == 0, flags.zero());
-                    assert_eq!(false, flags.sub());
+                    assert!(!flags.sub());
                     assert_eq!(half, flags.half());
                     assert_eq!(carry, flags.carry());
                 }
@@ -513,7 +513,7 @@ mod tests {
                 assert_eq!(expected, acc);
 
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(true, flags.sub());
+                assert!(flags.sub());
                 assert_eq!(half, flags.half());
                 assert_eq!(carry, flags.carry());
             }
@@ -538,7 +538,7 @@ mod tests {
                     assert_eq!(expected, acc);
 
                     assert_eq!(acc == 0, flags.zero());
-                    assert_eq!(true, flags.sub());
+                    assert!(flags.sub());
                     assert_eq!(half, flags.half());
                     assert_eq!(carry, flags.carry());
                 }
@@ -559,7 +559,7 @@ mod tests {
 
                 assert_eq!(expected, acc);
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(false, flags.sub());
+                assert!(!flags.sub());
                 assert_eq!(half, flags.half());
                 assert_eq!(carry, flags.carry());
             }
@@ -579,7 +579,7 @@ mod tests {
 
                 assert_eq!(expected, acc);
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(true, flags.sub());
+                assert!(flags.sub());
                 assert_eq!(half, flags.half());
                 assert_eq!(carry, flags.carry());
             }
@@ -596,9 +596,9 @@ mod tests {
 
                 assert_eq!(expected, acc);
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(false, flags.sub());
-                assert_eq!(true, flags.half());
-                assert_eq!(false, flags.carry());
+                assert!(!flags.sub());
+                assert!(flags.half());
+                assert!(!flags.carry());
             }
         }
     }
@@ -612,9 +612,9 @@ mod tests {
 
                 assert_eq!(expected, acc);
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(false, flags.sub());
-                assert_eq!(false, flags.half());
-                assert_eq!(false, flags.carry());
+                assert!(!flags.sub());
+                assert!(!flags.half());
+                assert!(!flags.carry());
             }
         }
     }
@@ -628,9 +628,9 @@ mod tests {
 
                 assert_eq!(expected, acc);
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(false, flags.sub());
-                assert_eq!(false, flags.half());
-                assert_eq!(false, flags.carry());
+                assert!(!flags.sub());
+                assert!(!flags.half());
+                assert!(!flags.carry());
             }
         }
     }
@@ -645,8 +645,8 @@ mod tests {
 
             assert_eq!(expected, acc);
             assert_eq!(acc == 0, flags.zero());
-            assert_eq!(false, flags.sub());
-            assert_eq!(false, flags.half());
+            assert!(!flags.sub());
+            assert!(!flags.half());
             assert_eq!(carry, flags.carry());
         }
     }
@@ -661,8 +661,8 @@ mod tests {
 
             assert_eq!(expected, acc);
             assert_eq!(acc == 0, flags.zero());
-            assert_eq!(false, flags.sub());
-            assert_eq!(false, flags.half());
+            assert!(!flags.sub());
+            assert!(!flags.half());
             assert_eq!(carry, flags.carry());
         }
     }
@@ -678,8 +678,8 @@ mod tests {
 
                 assert_eq!(expected, acc);
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(false, flags.sub());
-                assert_eq!(false, flags.half());
+                assert!(!flags.sub());
+                assert!(!flags.half());
                 assert_eq!(carry, flags.carry());
             }
         }
@@ -697,8 +697,8 @@ mod tests {
 
                 assert_eq!(expected, acc);
                 assert_eq!(acc == 0, flags.zero());
-                assert_eq!(false, flags.sub());
-                assert_eq!(false, flags.half());
+                assert!(!flags.sub());
+                assert!(!flags.half());
                 assert_eq!(carry, flags.carry());
             }
         }
@@ -714,8 +714,8 @@ mod tests {
 
             assert_eq!(expected, acc);
             assert_eq!(acc == 0, flags.zero());
-            assert_eq!(false, flags.sub());
-            assert_eq!(false, flags.half());
+            assert!(!flags.sub());
+            assert!(!flags.half());
             assert_eq!(carry, flags.carry());
         }
     }
@@ -730,8 +730,8 @@ mod tests {
 
             assert_eq!(expected, acc);
             assert_eq!(acc == 0, flags.zero());
-            assert_eq!(false, flags.sub());
-            assert_eq!(false, flags.half());
+            assert!(!flags.sub());
+            assert!(!flags.half());
             assert_eq!(carry, flags.carry());
         }
     }
@@ -745,9 +745,9 @@ mod tests {
 
             assert_eq!(expected, acc);
             assert_eq!(acc == 0, flags.zero());
-            assert_eq!(false, flags.sub());
-            assert_eq!(false, flags.half());
-            assert_eq!(false, flags.carry());
+            assert!(!flags.sub());
+            assert!(!flags.half());
+            assert!(!flags.carry());
         }
     }
 
@@ -764,8 +764,8 @@ mod tests {
 
                 assert_eq!(expected, acc);
                 assert_eq!(zero, flags.zero());
-                assert_eq!(true, flags.sub());
-                assert_eq!(true, flags.half());
+                assert!(flags.sub());
+                assert!(flags.half());
                 assert_eq!(carry, flags.carry());
             }
         }
@@ -783,8 +783,8 @@ mod tests {
                     let flags = test_bit(flags, acc, bit);
 
                     assert_eq!(acc & (1 << bit) == 0, flags.zero());
-                    assert_eq!(false, flags.sub());
-                    assert_eq!(true, flags.half());
+                    assert!(!flags.sub());
+                    assert!(flags.half());
                     assert_eq!(carry, flags.carry());
                 }
             }
@@ -797,7 +797,7 @@ mod tests {
             for bit in 0u8..7u8
             {
                 let ret = set_bit(acc, bit);
-                assert_eq!(true, (ret & 1 << bit) != 0);
+                assert!((ret & 1 << bit) != 0);
                 assert_eq!(0, (ret & !(1 << bit) ^ (acc & !(1 << bit))));
             }
         }
@@ -809,7 +809,7 @@ mod tests {
             for bit in 0u8..7u8
             {
                 let ret = reset_bit(acc, bit);
-                assert_eq!(false, (ret & 1 << bit) != 0);
+                assert!(((ret & 1 << bit) == 0));
                 assert_eq!(0, (ret & !(1 << bit) ^ (acc & !(1 << bit))));
             }
         }
@@ -3,7 +3,7 @@ pub fn disassemble(opcode: u8, immediate8: u8, immediate16: u16) -> String {
     let opcode = opcode as usize;
     match opcode {
         0xCB => INST_ASM_CB[immediate8 as usize].to_string(),
-        _ => match INST_SIZE[opcode as usize] {
+        _ => match INST_SIZE[opcode] {
             1 => INST_ASM[opcode].to_string(),
             2 => INST_ASM[opcode].replace("$00", &format!("${:02X}", immediate8)),
             3 => INST_ASM[opcode].replace("$0000", &format!("${:04X}", immediate16)),
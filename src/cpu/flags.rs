@@ -14,9 +14,9 @@ impl From<u8> for Flags {
     }
 }
 
-impl Into<u8> for Flags {
-    fn into(self) -> u8 {
-        self.bits
+impl From<Flags> for u8 {
+    fn from(val: Flags) -> Self {
+        val.bits
     }
 }
 
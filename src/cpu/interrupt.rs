@@ -15,9 +15,9 @@ impl From<u8> for Interrupt {
     }
 }
 
-impl Into<u8> for Interrupt {
-    fn into(self) -> u8 {
-        self.bits
+impl From<Interrupt> for u8 {
+    fn from(val: Interrupt) -> Self {
+        val.bits
     }
 }
 
@@ -99,11 +99,11 @@ impl InterruptService {
             self.dirty = false;
 
             let irq = self.enabled_flags & self.latched_flags;
-            for (int, rst) in vec![
+            for (int, rst) in [
                     (Interrupt::VBLANK, 0x40u16),
                     (Interrupt::LCDC  , 0x48u16),
                     (Interrupt::TIMER , 0x50u16),
-                    (Interrupt::SERIAL, 0x58u16), 
+                    (Interrupt::SERIAL, 0x58u16),
                     (Interrupt::HL_PIN, 0x60u16)
                 ].iter() {
                 if irq.contains(*int) {
@@ -122,8 +122,7 @@ mod tests {
     use super::*;
 
     fn isr(int: Interrupt, ei: Interrupt) -> Option<u16> {
-        let mut int_svc = InterruptService::default();
-        int_svc.enabled = true;
+        let mut int_svc = InterruptService { enabled: true, ..Default::default() };
         int_svc.set_interrupt_enabled_flags(ei);
         int_svc.set_interrupt_latched_flags(int);
         int_svc.interrupt_service_routine()
@@ -154,8 +153,7 @@ mod tests {
     }
 
     fn int_svc_post_isr(int: Interrupt, ei: Interrupt) -> InterruptService {
-        let mut int_svc = InterruptService::default();
-        int_svc.enabled = true;
+        let mut int_svc = InterruptService { enabled: true, ..Default::default() };
         int_svc.set_interrupt_enabled_flags(ei);
         int_svc.set_interrupt_latched_flags(int);
         int_svc.interrupt_service_routine();
@@ -1,55 +1,37 @@
 
 use super::*;
 use flags::Flags;
+use crate::mmu::Mmu;
 
-unsafe fn build() -> (*mut Cpu, *mut Mmu) {
-    let cpu = Box::new(Cpu::default());
-    let cpu: *mut Cpu = Box::into_raw(cpu);
-
-    let mmu = Box::new(Mmu::default());
-    let mmu: *mut Mmu = Box::into_raw(mmu);
-
-    (*cpu).mmu = mmu;
-    (*mmu).cpu = cpu;
-
-    (cpu, mmu)
-}
-
-unsafe fn destroy(component: (*mut Cpu, *mut Mmu)) {
-    let (cpu, mmu): (*mut Cpu, *mut Mmu) = component;
-    drop(Box::from_raw(cpu));
-    drop(Box::from_raw(mmu));
+fn build() -> (Cpu, Mmu) {
+    (Cpu::default(), Mmu::default())
 }
 
 macro_rules! int_test {
     ($int:expr, $addr:literal) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).int_svc.set_enabled(true);
-            (*cpu).regs.set_sp(0xFFFE);
-            (*mmu).cartridge_rom[$addr] = 0xD9;
-
-            (*cpu).cycle();
-            let r1 = (*cpu).registers();
+        let (mut cpu, mut mmu) = build();
+        cpu.int_svc.set_enabled(true);
+        cpu.regs.set_sp(0xFFFE);
+        mmu.cartridge.rom_mut()[$addr] = 0xD9;
 
-            // Interrupt
-            (*cpu).int_svc.set_interrupt_latched_flags($int);
-            (*cpu).int_svc.set_interrupt_enabled_flags($int);
+        cpu.cycle(&mut mmu);
+        let r1 = cpu.registers();
 
-            (*cpu).cycle();
-            let r2 = (*cpu).registers();
+        // Interrupt
+        cpu.int_svc.set_interrupt_latched_flags($int);
+        cpu.int_svc.set_interrupt_enabled_flags($int);
 
-            (*cpu).cycle();
-            let r3 = (*cpu).registers();
+        cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
 
-            (*cpu).cycle();
+        cpu.cycle(&mut mmu);
+        let r3 = cpu.registers();
 
-            destroy((cpu, mmu));
+        cpu.cycle(&mut mmu);
 
-            assert_eq!(0x01, r1.pc());
-            assert_eq!($addr, r2.pc());
-            assert_eq!(0x02, r3.pc());
-        }
+        assert_eq!(0x01, r1.pc());
+        assert_eq!($addr, r2.pc());
+        assert_eq!(0x02, r3.pc());
     }
 }
 
@@ -80,185 +62,158 @@ fn joypad_int_test() {
 
 #[test]
 fn nop_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        destroy((cpu, mmu));
+    let (mut cpu, mut mmu) = build();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(4, tk);
-        assert_eq!(1, r2.pc());
+    assert_eq!(4, tk);
+    assert_eq!(1, r2.pc());
 
-        let mut rr = r2.clone();
-        rr.set_pc(r1.pc());
-        assert_eq!(rr, r1);
-    }
+    let mut rr = r2.clone();
+    rr.set_pc(r1.pc());
+    assert_eq!(rr, r1);
 }
 
 #[test]
 fn stop_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x01);
-        (*mmu).cartridge_rom[0] = 0x10;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x01);
+    mmu.cartridge.rom_mut()[0] = 0x10;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 
-        let mut rr = r2.clone();
-        rr.set_pc(r1.pc());
+    let mut rr = r2.clone();
+    rr.set_pc(r1.pc());
 
-        assert_eq!(r1, rr);
-    }
+    assert_eq!(r1, rr);
 }
 
 #[test]
 fn halt_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0x76;
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0x76;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(0, r2.pc());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(0, r2.pc());
 }
 
 #[test]
 fn di_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).int_svc.set_enabled(true);
-        (*mmu).cartridge_rom[0] = 0xF3;
+    let (mut cpu, mut mmu) = build();
+    cpu.int_svc.set_enabled(true);
+    mmu.cartridge.rom_mut()[0] = 0xF3;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let ie1 = (*cpu).int_svc.enabled();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let ie1 = cpu.int_svc.enabled();
+    let r2 = cpu.registers();
 
-        (*cpu).cycle();
-        let ie2 = (*cpu).int_svc.enabled();
+    cpu.cycle(&mut mmu);
+    let ie2 = cpu.int_svc.enabled();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(true, ie1);
-        assert_eq!(false, ie2);
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert!(ie1);
+    assert!(!ie2);
 }
 
 #[test]
 fn ei_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xFB;
-
-        let r1 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xFB;
 
-        let tk = (*cpu).cycle();
-        let ie1 = (*cpu).int_svc.enabled();
+    let r1 = cpu.registers();
 
-        let r2 = (*cpu).registers();
+    let tk = cpu.cycle(&mut mmu);
+    let ie1 = cpu.int_svc.enabled();
 
-        (*cpu).cycle();
-        let ie2 = (*cpu).int_svc.enabled();
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
+    cpu.cycle(&mut mmu);
+    let ie2 = cpu.int_svc.enabled();
 
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(false, ie1);
-        assert_eq!(true, ie2);
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert!(!ie1);
+    assert!(ie2);
 }
 
 #[test]
 fn ei2_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x04);
-        (*mmu).cartridge_rom[0] = 0xEA; // LD (0xFFFF), A
-        (*mmu).cartridge_rom[1] = 0xFF;
-        (*mmu).cartridge_rom[2] = 0xFF;
-        (*mmu).cartridge_rom[3] = 0xFB; // EI
-        (*mmu).cartridge_rom[4] = 0x04; // INC B
-        (*mmu).cartridge_rom[5] = 0xEA; // LD (0xFFF0), A
-        (*mmu).cartridge_rom[6] = 0x0F;
-        (*mmu).cartridge_rom[7] = 0xFF;
-        (*mmu).cartridge_rom[8] = 0x05; // DEC B
-        (*mmu).cartridge_rom[9] = 0xC2; // JP NZ
-
-        (*mmu).cartridge_rom[0x50] = 0x3C; // INC A
-        (*mmu).cartridge_rom[0x51] = 0xC0; // RET
-
-        let mut deque: std::collections::VecDeque<Registers> =
-            std::collections::VecDeque::new();
-
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // LD (0xFFFF), A
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // EI
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // INC B
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // LD (0xFF0F), A
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // DEC B + TIMER
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // INC A
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // RET NZ
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // JP NZ
-        (*cpu).cycle(); deque.push_back((*cpu).registers()); // JP NZ
-
-        destroy((cpu, mmu));
-
-        assert_eq!(
-            [0x0003, 0x0004, 0x0005, 0x0050, 0x0051, 0x0008, 0x0009, 0x000c, 0x000d],
-            [
-                deque.get(0).unwrap().pc(),
-                deque.get(1).unwrap().pc(),
-                deque.get(2).unwrap().pc(),
-                deque.get(3).unwrap().pc(),
-                deque.get(4).unwrap().pc(),
-                deque.get(5).unwrap().pc(),
-                deque.get(6).unwrap().pc(),
-                deque.get(7).unwrap().pc(),
-                deque.get(8).unwrap().pc()
-            ]);
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x04);
+    mmu.cartridge.rom_mut()[0] = 0xEA; // LD (0xFFFF), A
+    mmu.cartridge.rom_mut()[1] = 0xFF;
+    mmu.cartridge.rom_mut()[2] = 0xFF;
+    mmu.cartridge.rom_mut()[3] = 0xFB; // EI
+    mmu.cartridge.rom_mut()[4] = 0x04; // INC B
+    mmu.cartridge.rom_mut()[5] = 0xEA; // LD (0xFFF0), A
+    mmu.cartridge.rom_mut()[6] = 0x0F;
+    mmu.cartridge.rom_mut()[7] = 0xFF;
+    mmu.cartridge.rom_mut()[8] = 0x05; // DEC B
+    mmu.cartridge.rom_mut()[9] = 0xC2; // JP NZ
+
+    mmu.cartridge.rom_mut()[0x50] = 0x3C; // INC A
+    mmu.cartridge.rom_mut()[0x51] = 0xC0; // RET
+
+    let mut deque: std::collections::VecDeque<Registers> =
+        std::collections::VecDeque::new();
+
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // LD (0xFFFF), A
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // EI
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // INC B
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // LD (0xFF0F), A
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // DEC B + TIMER
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // INC A
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // RET NZ
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // JP NZ
+    cpu.cycle(&mut mmu); deque.push_back(cpu.registers()); // JP NZ
+
+    assert_eq!(
+        [0x0003, 0x0004, 0x0005, 0x0050, 0x0051, 0x0008, 0x0009, 0x000c, 0x000d],
+        [
+            deque.front().unwrap().pc(),
+            deque.get(1).unwrap().pc(),
+            deque.get(2).unwrap().pc(),
+            deque.get(3).unwrap().pc(),
+            deque.get(4).unwrap().pc(),
+            deque.get(5).unwrap().pc(),
+            deque.get(6).unwrap().pc(),
+            deque.get(7).unwrap().pc(),
+            deque.get(8).unwrap().pc()
+        ]);
 }
 
 macro_rules! ld_r16_d16_test {
     ($opcode:literal, $r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*mmu).cartridge_rom[0] = $opcode;
-            (*mmu).cartridge_rom[1] = 0xEF;
-            (*mmu).cartridge_rom[2] = 0xBE;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(12, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(3, r2.pc());
-            assert_eq!(0x0000, r1.$r());
-            assert_eq!(0xBEEF, r2.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        mmu.cartridge.rom_mut()[0] = $opcode;
+        mmu.cartridge.rom_mut()[1] = 0xEF;
+        mmu.cartridge.rom_mut()[2] = 0xBE;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(12, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(3, r2.pc());
+        assert_eq!(0x0000, r1.$r());
+        assert_eq!(0xBEEF, r2.$r());
     }
 }
 
@@ -284,93 +239,77 @@ fn ld_sp_d16_test() {
 
 #[test]
 fn ld_sp_hl_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xF9;
-        (*cpu).regs.set_hl(0xABCD);
-        (*cpu).regs.set_sp(0xFFFE);
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xF9;
+    cpu.regs.set_hl(0xABCD);
+    cpu.regs.set_sp(0xFFFE);
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0xFFFE, r1.sp());
-        assert_eq!(0xABCD, r2.sp());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0xFFFE, r1.sp());
+    assert_eq!(0xABCD, r2.sp());
 }
 
 #[test]
 fn ld_hl_sp_add_positive_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xF8;
-        (*mmu).cartridge_rom[1] = 0x01;
-        (*cpu).regs.set_sp(0x4000);
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xF8;
+    mmu.cartridge.rom_mut()[1] = 0x01;
+    cpu.regs.set_sp(0x4000);
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x4000, r2.sp());
-        assert_eq!(0x4001, r2.hl());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x4000, r2.sp());
+    assert_eq!(0x4001, r2.hl());
 }
 
 #[test]
 fn ld_hl_sp_add_negative_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xF8;
-        (*mmu).cartridge_rom[1] = 0xFF;
-        (*cpu).regs.set_sp(0x4000);
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xF8;
+    mmu.cartridge.rom_mut()[1] = 0xFF;
+    cpu.regs.set_sp(0x4000);
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x4000, r2.sp());
-        assert_eq!(0x3FFF, r2.hl());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x4000, r2.sp());
+    assert_eq!(0x3FFF, r2.hl());
 }
 
 macro_rules! ld_r16_addr_a_test {
     ($opcode:literal, $r_set:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*mmu).cartridge_rom[0] = $opcode;
-            (*cpu).regs.set_a(0x99);
-            (*cpu).regs.$r_set(0xA000);
-
-            let d1 = (*mmu).cartridge_ram[0];
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            let d2 = (*mmu).cartridge_ram[0];
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0x00, d1);
-            assert_eq!(0x99, d2);
-        }
+        let (mut cpu, mut mmu) = build();
+        mmu.cartridge.rom_mut()[0] = $opcode;
+        cpu.regs.set_a(0x99);
+        cpu.regs.$r_set(0xA000);
+
+        let d1 = mmu.cartridge.ram()[0];
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        let d2 = mmu.cartridge.ram()[0];
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0x00, d1);
+        assert_eq!(0x99, d2);
     };
 }
 
@@ -386,22 +325,18 @@ fn ld_de_addr_a_test() {
 
 macro_rules! inc_r16_test {
     ($opcode:literal, $r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0, r1.$r());
-            assert_eq!(1, r2.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0, r1.$r());
+        assert_eq!(1, r2.$r());
     };
 }
 
@@ -427,46 +362,38 @@ fn inc_sp_test() {
 
 #[test]
 fn inc_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x34;
-        (*mmu).cartridge_ram[0] = 0x7F;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x34;
+    mmu.cartridge.ram_mut()[0] = 0x7F;
 
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x7F, d1);
-        assert_eq!(0x80, d2);
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x7F, d1);
+    assert_eq!(0x80, d2);
 }
 
 macro_rules! inc_r8_test {
     ($opcode:literal, $r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0, r1.$r());
-            assert_eq!(1, r2.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0, r1.$r());
+        assert_eq!(1, r2.$r());
     };
 }
 
@@ -485,7 +412,6 @@ fn inc_h_test() {
     inc_r8_test!(0x24, h);
 }
 
-
 #[test]
 fn inc_c_test() {
     inc_r8_test!(0x0C, c);
@@ -508,22 +434,18 @@ fn inc_a_test() {
 
 macro_rules! dec_r8_test {
     ($opcode:literal, $r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0, r1.$r());
-            assert_eq!(255, r2.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0, r1.$r());
+        assert_eq!(255, r2.$r());
     };
 }
 
@@ -544,26 +466,22 @@ fn dec_h_test() {
 
 #[test]
 fn dec_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x35;
-        (*mmu).cartridge_ram[0] = 0x7F;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x35;
+    mmu.cartridge.ram_mut()[0] = 0x7F;
 
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x7F, d1);
-        assert_eq!(0x7E, d2);
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x7F, d1);
+    assert_eq!(0x7E, d2);
 }
 
 #[test]
@@ -588,22 +506,19 @@ fn dec_a_test() {
 
 macro_rules! ld_r8_d8_test {
     ($opcode:literal, $r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*mmu).cartridge_rom[0] = $opcode;
-            (*mmu).cartridge_rom[1] = 0xAB;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0, r1.$r());
-            assert_eq!(0xAB, r2.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        mmu.cartridge.rom_mut()[0] = $opcode;
+        mmu.cartridge.rom_mut()[1] = 0xAB;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0, r1.$r());
+        assert_eq!(0xAB, r2.$r());
     };
 }
 
@@ -644,311 +559,261 @@ fn ld_a_d8_test() {
 
 #[test]
 fn ld_hl_addr_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x36;
-        (*mmu).cartridge_rom[1] = 0xAB;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x36;
+    mmu.cartridge.rom_mut()[1] = 0xAB;
 
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0, d1);
-        assert_eq!(0xAB, d2);
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0, d1);
+    assert_eq!(0xAB, d2);
 }
 
 #[test]
 fn ld_a_de_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_de(0xA000);
-        (*mmu).cartridge_rom[0] = 0x1A;
-        (*mmu).cartridge_ram[0] = 0xFF;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_de(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x1A;
+    mmu.cartridge.ram_mut()[0] = 0xFF;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x00, r1.a());
-        assert_eq!(0xFF, r2.a());
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x00, r1.a());
+    assert_eq!(0xFF, r2.a());
 
-        let mut rr = r2.clone();
-        rr.set_a(r1.a());
-        rr.set_pc(r1.pc());
-        assert_eq!(rr, r1);
-    }
+    let mut rr = r2.clone();
+    rr.set_a(r1.a());
+    rr.set_pc(r1.pc());
+    assert_eq!(rr, r1);
 }
 
 #[test]
 fn ld_a_hli_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x2A;
-        (*mmu).cartridge_ram[0] = 0xFF;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x2A;
+    mmu.cartridge.ram_mut()[0] = 0xFF;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x00, r1.a());
+    assert_eq!(0xFF, r2.a());
+    assert_eq!(0xA000, r1.hl());
+    assert_eq!(0xA001, r2.hl());
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x00, r1.a());
-        assert_eq!(0xFF, r2.a());
-        assert_eq!(0xA000, r1.hl());
-        assert_eq!(0xA001, r2.hl());
-
-        let mut rr = r2.clone();
-        rr.set_a(r1.a());
-        rr.set_hl(r1.hl());
-        rr.set_pc(r1.pc());
-        assert_eq!(rr, r1);
-    }
+    let mut rr = r2.clone();
+    rr.set_a(r1.a());
+    rr.set_hl(r1.hl());
+    rr.set_pc(r1.pc());
+    assert_eq!(rr, r1);
 }
 
 #[test]
 fn ld_a_hld_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x3A;
-        (*mmu).cartridge_ram[0] = 0xFF;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x3A;
+    mmu.cartridge.ram_mut()[0] = 0xFF;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x00, r1.a());
+    assert_eq!(0xFF, r2.a());
+    assert_eq!(0xA000, r1.hl());
+    assert_eq!(0x9FFF, r2.hl());
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x00, r1.a());
-        assert_eq!(0xFF, r2.a());
-        assert_eq!(0xA000, r1.hl());
-        assert_eq!(0x9FFF, r2.hl());
-
-        let mut rr = r2.clone();
-        rr.set_a(r1.a());
-        rr.set_hl(r1.hl());
-        rr.set_pc(r1.pc());
-        assert_eq!(rr, r1);
-    }
+    let mut rr = r2.clone();
+    rr.set_a(r1.a());
+    rr.set_hl(r1.hl());
+    rr.set_pc(r1.pc());
+    assert_eq!(rr, r1);
 }
 
 #[test]
 fn ld_a16_sp_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_sp(0x1234);
-        (*mmu).cartridge_rom[0] = 0x08;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0xA0;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_sp(0x1234);
+    mmu.cartridge.rom_mut()[0] = 0x08;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0xA0;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        let lsb = (*mmu).cartridge_ram[0];
-        let msb = (*mmu).cartridge_ram[1];
+    let lsb = mmu.cartridge.ram()[0];
+    let msb = mmu.cartridge.ram()[1];
 
-        destroy((cpu, mmu));
+    assert_eq!(20, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(3, r2.pc());
+    assert_eq!(0x34, lsb);
+    assert_eq!(0x12, msb);
 
-        assert_eq!(20, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(3, r2.pc());
-        assert_eq!(0x34, lsb);
-        assert_eq!(0x12, msb);
-
-        let mut rr = r2.clone();
-        rr.set_pc(r1.pc());
-        assert_eq!(rr, r1);
-    }
+    let mut rr = r2.clone();
+    rr.set_pc(r1.pc());
+    assert_eq!(rr, r1);
 }
 
 #[test]
 fn ld_a8_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0xFF);
-        (*mmu).cartridge_rom[0] = 0xE0;
-        (*mmu).cartridge_rom[1] = 0x90;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0xFF);
+    mmu.cartridge.rom_mut()[0] = 0xE0;
+    mmu.cartridge.rom_mut()[1] = 0x90;
 
-        let d1 = (*mmu).ram[0x2010];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).ram[0x2010];
+    let d1 = mmu.ram[0x2010];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.ram[0x2010];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x00, d1);
-        assert_eq!(0xFF, d2);
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x00, d1);
+    assert_eq!(0xFF, d2);
 }
 
 #[test]
 fn ld_a_a8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xF0;
-        (*mmu).cartridge_rom[1] = 0x90;
-        (*mmu).ram[0x2010] = 0xFF;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xF0;
+    mmu.cartridge.rom_mut()[1] = 0x90;
+    mmu.ram[0x2010] = 0xFF;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x00, r1.a());
-        assert_eq!(0xFF, r2.a());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x00, r1.a());
+    assert_eq!(0xFF, r2.a());
 }
 
 #[test]
 fn ld_c_zp_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0xFF);
-        (*cpu).regs.set_c(0x90);
-        (*mmu).cartridge_rom[0] = 0xE2;
-        (*mmu).cartridge_rom[1] = 0x90;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0xFF);
+    cpu.regs.set_c(0x90);
+    mmu.cartridge.rom_mut()[0] = 0xE2;
+    mmu.cartridge.rom_mut()[1] = 0x90;
 
-        let d1 = (*mmu).ram[0x2010];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).ram[0x2010];
+    let d1 = mmu.ram[0x2010];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.ram[0x2010];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x00, d1);
-        assert_eq!(0xFF, d2);
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x00, d1);
+    assert_eq!(0xFF, d2);
 }
 
 #[test]
 fn ld_a_c_zp_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_c(0x90);
-        (*mmu).cartridge_rom[0] = 0xF2;
-        (*mmu).cartridge_rom[1] = 0x90;
-        (*mmu).ram[0x2010] = 0xFF;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_c(0x90);
+    mmu.cartridge.rom_mut()[0] = 0xF2;
+    mmu.cartridge.rom_mut()[1] = 0x90;
+    mmu.ram[0x2010] = 0xFF;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x00, r1.a());
-        assert_eq!(0xFF, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x00, r1.a());
+    assert_eq!(0xFF, r2.a());
 }
 
-
-
 #[test]
 fn ld_a16_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0xFF);
-        (*cpu).regs.set_c(0x90);
-        (*mmu).cartridge_rom[0] = 0xEA;
-        (*mmu).cartridge_rom[1] = 0x90;
-        (*mmu).cartridge_rom[2] = 0xFF;
-
-        let d1 = (*mmu).ram[0x2010];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).ram[0x2010];
-
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(3, r2.pc());
-        assert_eq!(0x00, d1);
-        assert_eq!(0xFF, d2);
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0xFF);
+    cpu.regs.set_c(0x90);
+    mmu.cartridge.rom_mut()[0] = 0xEA;
+    mmu.cartridge.rom_mut()[1] = 0x90;
+    mmu.cartridge.rom_mut()[2] = 0xFF;
+
+    let d1 = mmu.ram[0x2010];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.ram[0x2010];
+
+    assert_eq!(16, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(3, r2.pc());
+    assert_eq!(0x00, d1);
+    assert_eq!(0xFF, d2);
 }
 
 #[test]
 fn ld_a_a16_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_c(0x90);
-        (*mmu).cartridge_rom[0] = 0xFA;
-        (*mmu).cartridge_rom[1] = 0x90;
-        (*mmu).cartridge_rom[2] = 0xFF;
-        (*mmu).ram[0x2010] = 0xFF;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_c(0x90);
+    mmu.cartridge.rom_mut()[0] = 0xFA;
+    mmu.cartridge.rom_mut()[1] = 0x90;
+    mmu.cartridge.rom_mut()[2] = 0xFF;
+    mmu.ram[0x2010] = 0xFF;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(16, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(3, r2.pc());
-        assert_eq!(0x00, r1.a());
-        assert_eq!(0xFF, r2.a());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(3, r2.pc());
+    assert_eq!(0x00, r1.a());
+    assert_eq!(0xFF, r2.a());
 }
 
 macro_rules! add_hl_r16_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_hl(0x10F8);
-            (*cpu).regs.$set_r(0x1010);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0x10F8, r1.hl());
-            assert_eq!(0x2108, r2.hl());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_hl(0x10F8);
+        cpu.regs.$set_r(0x1010);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0x10F8, r1.hl());
+        assert_eq!(0x2108, r2.hl());
     };
 }
 
@@ -964,23 +829,19 @@ fn add_hl_de_test() {
 
 #[test]
 fn add_hl_hl_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0x10F8);
-        (*mmu).cartridge_rom[0] = 0x29;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0x10F8);
+    mmu.cartridge.rom_mut()[0] = 0x29;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x10F8, r1.hl());
-        assert_eq!(0x21F0, r2.hl());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x10F8, r1.hl());
+    assert_eq!(0x21F0, r2.hl());
 }
 
 #[test]
@@ -990,44 +851,36 @@ fn add_hl_sp_test() {
 
 #[test]
 fn add_sp_s8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_sp(0xFF00);
-        (*mmu).cartridge_rom[0] = 0xE8;
-        (*mmu).cartridge_rom[1] = 0x7F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_sp(0xFF00);
+    mmu.cartridge.rom_mut()[0] = 0xE8;
+    mmu.cartridge.rom_mut()[1] = 0x7F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(16, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0xFF00, r1.sp());
-        assert_eq!(0xFF7F, r2.sp());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0xFF00, r1.sp());
+    assert_eq!(0xFF7F, r2.sp());
 }
 
 macro_rules! dec_r16_test {
     ($opcode:literal, $r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0, r1.$r());
-            assert_eq!(0xFFFF, r2.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0, r1.$r());
+        assert_eq!(0xFFFF, r2.$r());
     }
 }
 
@@ -1053,23 +906,19 @@ fn dec_sp_test() {
 
 macro_rules! ld_r8_r8_test {
     ($opcode:literal, $dst:tt, $src:tt, $set_src:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_src(0xFF);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0xFF, r2.$dst());
-            assert_eq!(0xFF, r2.$src());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_src(0xFF);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0xFF, r2.$dst());
+        assert_eq!(0xFF, r2.$src());
     }
 }
 
@@ -1320,23 +1169,19 @@ fn ld_a_a_test() {
 
 macro_rules! ld_r8_r16_addr_test {
     ($opcode:literal, $dst:tt, $src:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$src(0xA000);
-            (*mmu).cartridge_rom[0] = $opcode;
-            (*mmu).cartridge_ram[0] = 0xFF;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0xFF, r2.$dst());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$src(0xA000);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+        mmu.cartridge.ram_mut()[0] = 0xFF;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0xFF, r2.$dst());
     }
 }
 
@@ -1382,26 +1227,22 @@ fn ld_a_bc_addr_test() {
 
 macro_rules! ld_hl_addr_r8_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_hl(0xA000);
-            (*cpu).regs.$set_r(0xFF);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let d1 = (*mmu).cartridge_ram[0];
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            let d2 = (*mmu).cartridge_ram[0];
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0x00, d1);
-            assert_eq!(0xFF, d2);
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_hl(0xA000);
+        cpu.regs.$set_r(0xFF);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let d1 = mmu.cartridge.ram()[0];
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        let d2 = mmu.cartridge.ram()[0];
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0x00, d1);
+        assert_eq!(0xFF, d2);
     }
 }
 
@@ -1432,120 +1273,100 @@ fn ld_hl_addr_a_test() {
 
 #[test]
 fn ld_hl_addr_h_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA080);
-        (*mmu).cartridge_rom[0] = 0x74;
-
-        let d1 = (*mmu).cartridge_ram[0x80];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0x80];
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA080);
+    mmu.cartridge.rom_mut()[0] = 0x74;
 
-        destroy((cpu, mmu));
+    let d1 = mmu.cartridge.ram()[0x80];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0x80];
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x00, d1);
-        assert_eq!(0xA0, d2);
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x00, d1);
+    assert_eq!(0xA0, d2);
 }
 
 #[test]
 fn ld_hl_addr_l_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA080);
-        (*mmu).cartridge_rom[0] = 0x75;
-
-        let d1 = (*mmu).cartridge_ram[0x80];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0x80];
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA080);
+    mmu.cartridge.rom_mut()[0] = 0x75;
 
-        destroy((cpu, mmu));
+    let d1 = mmu.cartridge.ram()[0x80];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0x80];
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x00, d1);
-        assert_eq!(0x80, d2);
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x00, d1);
+    assert_eq!(0x80, d2);
 }
 
 #[test]
 fn ld_hli_addr_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0xFF);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x22;
-
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0xFF);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x22;
 
-        destroy((cpu, mmu));
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0, d1);
-        assert_eq!(0xFF, d2);
-        assert_eq!(0xA001, r2.hl());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0, d1);
+    assert_eq!(0xFF, d2);
+    assert_eq!(0xA001, r2.hl());
 }
 
 #[test]
 fn ld_hld_addr_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0xFF);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x32;
-
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0xFF);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x32;
 
-        destroy((cpu, mmu));
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0, d1);
-        assert_eq!(0xFF, d2);
-        assert_eq!(0x9FFF, r2.hl());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0, d1);
+    assert_eq!(0xFF, d2);
+    assert_eq!(0x9FFF, r2.hl());
 }
 
 macro_rules! add_a_r8_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_a(0x91);
-            (*cpu).regs.$set_r(0x2F);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0x91, r1.a());
-            assert_eq!(0xC0, r2.a());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_a(0x91);
+        cpu.regs.$set_r(0x2F);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0x91, r1.a());
+        assert_eq!(0xC0, r2.a());
     }
 }
 
@@ -1581,92 +1402,76 @@ fn add_a_l_test() {
 
 #[test]
 fn add_a_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x86;
-        (*mmu).cartridge_ram[0] = 0x2F;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x86;
+    mmu.cartridge.ram_mut()[0] = 0x2F;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0xC0, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0xC0, r2.a());
 }
 
 #[test]
 fn add_a_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x87;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x87;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0x22, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0x22, r2.a());
 }
 
 #[test]
 fn add_a_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*mmu).cartridge_rom[0] = 0xC6;
-        (*mmu).cartridge_rom[1] = 0x2F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    mmu.cartridge.rom_mut()[0] = 0xC6;
+    mmu.cartridge.rom_mut()[1] = 0x2F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0xC0, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0xC0, r2.a());
 }
 
 macro_rules! adc_a_r8_test {
     ($opcode:literal, $set_src:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_a(0x91);
-            (*cpu).regs.$set_src(0x2F);
-            (*cpu).regs.set_flags(Flags::C);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0x91, r1.a());
-            assert_eq!(0xC1, r2.a());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_a(0x91);
+        cpu.regs.$set_src(0x2F);
+        cpu.regs.set_flags(Flags::C);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0x91, r1.a());
+        assert_eq!(0xC1, r2.a());
     }
 }
 
@@ -1702,94 +1507,78 @@ fn adc_a_l_test() {
 
 #[test]
 fn adc_a_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x8E;
-        (*mmu).cartridge_ram[0] = 0x2F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x8E;
+    mmu.cartridge.ram_mut()[0] = 0x2F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0xC1, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0xC1, r2.a());
 }
 
 #[test]
 fn adc_a_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x8F;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x8F;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0x23, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0x23, r2.a());
 }
 
 #[test]
 fn adc_a_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0xCE;
-        (*mmu).cartridge_rom[1] = 0x2F;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0xCE;
+    mmu.cartridge.rom_mut()[1] = 0x2F;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0xC1, r2.a());
-    }
-}
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0xC1, r2.a());
+}
 
 macro_rules! sub_a_r8_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_a(0x91);
-            (*cpu).regs.$set_r(0x2F);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0x91, r1.a());
-            assert_eq!(0x62, r2.a());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_a(0x91);
+        cpu.regs.$set_r(0x2F);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0x91, r1.a());
+        assert_eq!(0x62, r2.a());
     }
 }
 
@@ -1825,91 +1614,75 @@ fn sub_a_l_test() {
 
 #[test]
 fn sub_a_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x96;
-        (*mmu).cartridge_ram[0] = 0x2F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x96;
+    mmu.cartridge.ram_mut()[0] = 0x2F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0x62, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0x62, r2.a());
 }
 
 #[test]
 fn sub_a_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*mmu).cartridge_rom[0] = 0x97;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    mmu.cartridge.rom_mut()[0] = 0x97;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0x00, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0x00, r2.a());
 }
 
 #[test]
 fn sub_a_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*mmu).cartridge_rom[0] = 0xD6;
-        (*mmu).cartridge_rom[1] = 0x2F;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    mmu.cartridge.rom_mut()[0] = 0xD6;
+    mmu.cartridge.rom_mut()[1] = 0x2F;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0x62, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0x62, r2.a());
 }
 
 macro_rules! sbc_a_r8_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_a(0x91);
-            (*cpu).regs.$set_r(0x2F);
-            (*cpu).regs.set_flags(Flags::C);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0x91, r1.a());
-            assert_eq!(0x61, r2.a());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_a(0x91);
+        cpu.regs.$set_r(0x2F);
+        cpu.regs.set_flags(Flags::C);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0x91, r1.a());
+        assert_eq!(0x61, r2.a());
     }
 }
 
@@ -1945,94 +1718,78 @@ fn sbc_a_l_test() {
 
 #[test]
 fn sbc_a_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x9E;
-        (*mmu).cartridge_ram[0] = 0x2F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x9E;
+    mmu.cartridge.ram_mut()[0] = 0x2F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0x61, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0x61, r2.a());
 }
 
 #[test]
 fn sbc_a_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x9F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x9F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0xFF, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0xFF, r2.a());
 }
 
 #[test]
 fn sbc_a_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x91);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0xDE;
-        (*mmu).cartridge_rom[1] = 0x2F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x91);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0xDE;
+    mmu.cartridge.rom_mut()[1] = 0x2F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x91, r1.a());
-        assert_eq!(0x61, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x91, r1.a());
+    assert_eq!(0x61, r2.a());
 }
 
 macro_rules! and_a_r8_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_a(0b00111100);
-            (*cpu).regs.$set_r(0b00001111);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0b00111100, r1.a());
-            assert_eq!(0b00001100, r2.a());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_a(0b00111100);
+        cpu.regs.$set_r(0b00001111);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0b00111100, r1.a());
+        assert_eq!(0b00001100, r2.a());
     }
 }
 
@@ -2068,90 +1825,74 @@ fn and_a_l_test() {
 
 #[test]
 fn and_a_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xA6;
-        (*mmu).cartridge_ram[0] = 0b00001111;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xA6;
+    mmu.cartridge.ram_mut()[0] = 0b00001111;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00001100, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00001100, r2.a());
 }
 
 #[test]
 fn and_a_a_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*mmu).cartridge_rom[0] = 0xA7;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    mmu.cartridge.rom_mut()[0] = 0xA7;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00111100, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00111100, r2.a());
 }
 
 #[test]
 fn and_a_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*mmu).cartridge_rom[0] = 0xE6;
-        (*mmu).cartridge_rom[1] = 0b00001111;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    mmu.cartridge.rom_mut()[0] = 0xE6;
+    mmu.cartridge.rom_mut()[1] = 0b00001111;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00001100, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00001100, r2.a());
 }
 
 macro_rules! xor_a_r8_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_a(0b00111100);
-            (*cpu).regs.$set_r(0b00001111);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0b00111100, r1.a());
-            assert_eq!(0b00110011, r2.a());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_a(0b00111100);
+        cpu.regs.$set_r(0b00001111);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0b00111100, r1.a());
+        assert_eq!(0b00110011, r2.a());
     }
 }
 
@@ -2187,90 +1928,74 @@ fn xor_a_l_test() {
 
 #[test]
 fn xor_a_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xAE;
-        (*mmu).cartridge_ram[0] = 0b00001111;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xAE;
+    mmu.cartridge.ram_mut()[0] = 0b00001111;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00110011, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00110011, r2.a());
 }
 
 #[test]
 fn xor_a_a_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*mmu).cartridge_rom[0] = 0xAF;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    mmu.cartridge.rom_mut()[0] = 0xAF;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00000000, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00000000, r2.a());
 }
 
 #[test]
 fn xor_a_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*mmu).cartridge_rom[0] = 0xEE;
-        (*mmu).cartridge_rom[1] = 0b00001111;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    mmu.cartridge.rom_mut()[0] = 0xEE;
+    mmu.cartridge.rom_mut()[1] = 0b00001111;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00110011, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00110011, r2.a());
 }
 
 macro_rules! or_a_r8_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_a(0b00111100);
-            (*cpu).regs.$set_r(0b00001111);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0b00111100, r1.a());
-            assert_eq!(0b00111111, r2.a());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_a(0b00111100);
+        cpu.regs.$set_r(0b00001111);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0b00111100, r1.a());
+        assert_eq!(0b00111111, r2.a());
     }
 }
 
@@ -2306,92 +2031,76 @@ fn or_a_l_test() {
 
 #[test]
 fn or_a_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xB6;
-        (*mmu).cartridge_ram[0] = 0b00001111;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xB6;
+    mmu.cartridge.ram_mut()[0] = 0b00001111;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00111111, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00111111, r2.a());
 }
 
 #[test]
 fn or_a_a_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*mmu).cartridge_rom[0] = 0xB7;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    mmu.cartridge.rom_mut()[0] = 0xB7;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00111100, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00111100, r2.a());
 }
 
 #[test]
 fn or_a_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0b00111100);
-        (*mmu).cartridge_rom[0] = 0xF6;
-        (*mmu).cartridge_rom[1] = 0b00001111;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0b00111100);
+    mmu.cartridge.rom_mut()[0] = 0xF6;
+    mmu.cartridge.rom_mut()[1] = 0b00001111;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0b00111100, r1.a());
-        assert_eq!(0b00111111, r2.a());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0b00111100, r1.a());
+    assert_eq!(0b00111111, r2.a());
 }
 
 macro_rules! cp_r8_test {
     ($opcode:literal, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.set_a(0x0F);
-            (*cpu).regs.$set_r(0x0F);
-            (*mmu).cartridge_rom[0] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(4, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(1, r2.pc());
-            assert_eq!(0x0F, r1.a());
-            assert_eq!(0x0F, r2.a());
-            assert_eq!(Flags::Z | Flags::N, r2.flags());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.set_a(0x0F);
+        cpu.regs.$set_r(0x0F);
+        mmu.cartridge.rom_mut()[0] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+
+        assert_eq!(4, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(1, r2.pc());
+        assert_eq!(0x0F, r1.a());
+        assert_eq!(0x0F, r2.a());
+        assert_eq!(Flags::Z | Flags::N, r2.flags());
     }
 }
 
@@ -2427,26 +2136,22 @@ fn cp_l_test() {
 
 #[test]
 fn cp_a_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x0F);
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xBE;
-        (*mmu).cartridge_ram[0] = 0x0F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x0F);
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xBE;
+    mmu.cartridge.ram_mut()[0] = 0x0F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x0F, r1.a());
-        assert_eq!(0x0F, r2.a());
-        assert_eq!(Flags::Z | Flags::N, r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x0F, r1.a());
+    assert_eq!(0x0F, r2.a());
+    assert_eq!(Flags::Z | Flags::N, r2.flags());
 }
 
 #[test]
@@ -2456,176 +2161,148 @@ fn cp_a_test() {
 
 #[test]
 fn cp_a_d8_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x0F);
-        (*mmu).cartridge_rom[0] = 0xFE;
-        (*mmu).cartridge_rom[1] = 0x0F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x0F);
+    mmu.cartridge.rom_mut()[0] = 0xFE;
+    mmu.cartridge.rom_mut()[1] = 0x0F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x0F, r1.a());
-        assert_eq!(0x0F, r2.a());
-        assert_eq!(Flags::Z | Flags::N, r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x0F, r1.a());
+    assert_eq!(0x0F, r2.a());
+    assert_eq!(Flags::Z | Flags::N, r2.flags());
 }
 
 #[test]
 fn daa_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x0A);
-        (*mmu).cartridge_rom[0] = 0x27;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x0A);
+    mmu.cartridge.rom_mut()[0] = 0x27;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x0A, r1.a());
-        assert_eq!(0x10, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x0A, r1.a());
+    assert_eq!(0x10, r2.a());
 }
 
 #[test]
 fn rlca_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x80);
-        (*mmu).cartridge_rom[0] = 0x07;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x80);
+    mmu.cartridge.rom_mut()[0] = 0x07;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x80, r1.a());
+    assert_eq!(0x01, r2.a());
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C,       r2.flags());
 
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x80, r1.a());
-        assert_eq!(0x01, r2.a());
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C,       r2.flags());
-
-        let mut rr = r2.clone();
-        rr.set_flags(r1.flags());
-        rr.set_a(r1.a());
-        rr.set_pc(r1.pc());
-        assert_eq!(rr, r1);
-    }
+    let mut rr = r2.clone();
+    rr.set_flags(r1.flags());
+    rr.set_a(r1.a());
+    rr.set_pc(r1.pc());
+    assert_eq!(rr, r1);
 }
 
 #[test]
 fn rrca_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x01);
-        (*mmu).cartridge_rom[0] = 0x0F;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x01);
+    mmu.cartridge.rom_mut()[0] = 0x0F;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x01, r1.a());
+    assert_eq!(0x80, r2.a());
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C,       r2.flags());
 
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x01, r1.a());
-        assert_eq!(0x80, r2.a());
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C,       r2.flags());
-
-        let mut rr = r2.clone();
-        rr.set_flags(r1.flags());
-        rr.set_a(r1.a());
-        rr.set_pc(r1.pc());
-        assert_eq!(rr, r1);
-    }
+    let mut rr = r2.clone();
+    rr.set_flags(r1.flags());
+    rr.set_a(r1.a());
+    rr.set_pc(r1.pc());
+    assert_eq!(rr, r1);
 }
 
 #[test]
 fn rla_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x81);
-        (*mmu).cartridge_rom[0] = 0x17;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x81);
+    mmu.cartridge.rom_mut()[0] = 0x17;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x81, r1.a());
-        assert_eq!(0x02, r2.a());
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C,       r2.flags());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x81, r1.a());
+    assert_eq!(0x02, r2.a());
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C,       r2.flags());
 }
 
 #[test]
 fn rra_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x81);
-        (*mmu).cartridge_rom[0] = 0x1F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x81);
+    mmu.cartridge.rom_mut()[0] = 0x1F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(0x81, r1.a());
-        assert_eq!(0x40, r2.a());
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C,       r2.flags());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(0x81, r1.a());
+    assert_eq!(0x40, r2.a());
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C,       r2.flags());
 }
 
 macro_rules! rlc_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0x80);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0x80, r1.$r());
-            assert_eq!(0x01, r2.$r());
-            assert_eq!(Flags::empty(), r1.flags());
-            assert_eq!(Flags::C,       r2.flags());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0x80);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0x80, r1.$r());
+        assert_eq!(0x01, r2.$r());
+        assert_eq!(Flags::empty(), r1.flags());
+        assert_eq!(Flags::C,       r2.flags());
     }
 }
 
@@ -2661,29 +2338,25 @@ fn rlc_l_test() {
 
 #[test]
 fn rlc_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xCB;
-        (*mmu).cartridge_rom[1] = 0x06;
-        (*mmu).cartridge_ram[0] = 0x80;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xCB;
+    mmu.cartridge.rom_mut()[1] = 0x06;
+    mmu.cartridge.ram_mut()[0] = 0x80;
 
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x80, d1);
-        assert_eq!(0x01, d2);
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C,       r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x80, d1);
+    assert_eq!(0x01, d2);
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C,       r2.flags());
 }
 
 #[test]
@@ -2693,27 +2366,23 @@ fn rlc_a_test() {
 
 macro_rules! rrc_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0x01);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0x01, r1.$r());
-            assert_eq!(0x80, r2.$r());
-            assert_eq!(Flags::empty(), r1.flags());
-            assert_eq!(Flags::C,       r2.flags());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0x01);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0x01, r1.$r());
+        assert_eq!(0x80, r2.$r());
+        assert_eq!(Flags::empty(), r1.flags());
+        assert_eq!(Flags::C,       r2.flags());
     }
 }
 
@@ -2722,7 +2391,6 @@ fn rrc_b_test() {
     rrc_r8_test!(0x08, b, set_b);
 }
 
-
 #[test]
 fn rrc_c_test() {
     rrc_r8_test!(0x09, c, set_c);
@@ -2750,29 +2418,25 @@ fn rrc_l_test() {
 
 #[test]
 fn rrc_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xCB;
-        (*mmu).cartridge_rom[1] = 0x0E;
-        (*mmu).cartridge_ram[0] = 0x01;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xCB;
+    mmu.cartridge.rom_mut()[1] = 0x0E;
+    mmu.cartridge.ram_mut()[0] = 0x01;
 
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x01, d1);
-        assert_eq!(0x80, d2);
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C,       r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x01, d1);
+    assert_eq!(0x80, d2);
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C,       r2.flags());
 }
 
 #[test]
@@ -2782,28 +2446,24 @@ fn rrc_a_test() {
 
 macro_rules! rl_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0x80);
-            (*cpu).regs.set_flags(Flags::C);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0x80, r1.$r());
-            assert_eq!(0x01, r2.$r());
-            assert_eq!(Flags::C, r1.flags());
-            assert_eq!(Flags::C, r2.flags());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0x80);
+        cpu.regs.set_flags(Flags::C);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0x80, r1.$r());
+        assert_eq!(0x01, r2.$r());
+        assert_eq!(Flags::C, r1.flags());
+        assert_eq!(Flags::C, r2.flags());
     }
 }
 
@@ -2839,29 +2499,25 @@ fn rl_l_test() {
 
 #[test]
 fn rl_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xCB;
-        (*mmu).cartridge_rom[1] = 0x16;
-        (*mmu).cartridge_ram[0] = 0x80;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xCB;
+    mmu.cartridge.rom_mut()[1] = 0x16;
+    mmu.cartridge.ram_mut()[0] = 0x80;
 
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x80, d1);
-        assert_eq!(0x00, d2);
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C | Flags::Z,       r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x80, d1);
+    assert_eq!(0x00, d2);
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C | Flags::Z,       r2.flags());
 }
 
 #[test]
@@ -2871,28 +2527,24 @@ fn rl_a_test() {
 
 macro_rules! rr_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0x01);
-            (*cpu).regs.set_flags(Flags::C);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0x01, r1.$r());
-            assert_eq!(0x80, r2.$r());
-            assert_eq!(Flags::C, r1.flags());
-            assert_eq!(Flags::C, r2.flags());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0x01);
+        cpu.regs.set_flags(Flags::C);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0x01, r1.$r());
+        assert_eq!(0x80, r2.$r());
+        assert_eq!(Flags::C, r1.flags());
+        assert_eq!(Flags::C, r2.flags());
     }
 }
 
@@ -2901,7 +2553,6 @@ fn rr_b_test() {
     rr_r8_test!(0x18, b, set_b);
 }
 
-
 #[test]
 fn rr_c_test() {
     rr_r8_test!(0x19, c, set_c);
@@ -2929,29 +2580,25 @@ fn rr_l_test() {
 
 #[test]
 fn rr_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xCB;
-        (*mmu).cartridge_rom[1] = 0x1E;
-        (*mmu).cartridge_ram[0] = 0x01;
-
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xCB;
+    mmu.cartridge.rom_mut()[1] = 0x1E;
+    mmu.cartridge.ram_mut()[0] = 0x01;
 
-        destroy((cpu, mmu));
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x01, d1);
-        assert_eq!(0x00, d2);
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C | Flags::Z, r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x01, d1);
+    assert_eq!(0x00, d2);
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C | Flags::Z, r2.flags());
 }
 
 #[test]
@@ -2961,27 +2608,23 @@ fn rr_a_test() {
 
 macro_rules! sla_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0x80);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0x80, r1.$r());
-            assert_eq!(0x00, r2.$r());
-            assert_eq!(Flags::empty(), r1.flags());
-            assert_eq!(Flags::C | Flags::Z, r2.flags());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0x80);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0x80, r1.$r());
+        assert_eq!(0x00, r2.$r());
+        assert_eq!(Flags::empty(), r1.flags());
+        assert_eq!(Flags::C | Flags::Z, r2.flags());
     }
 }
 
@@ -3017,29 +2660,25 @@ fn sla_l_test() {
 
 #[test]
 fn sla_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xCB;
-        (*mmu).cartridge_rom[1] = 0x26;
-        (*mmu).cartridge_ram[0] = 0x80;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xCB;
+    mmu.cartridge.rom_mut()[1] = 0x26;
+    mmu.cartridge.ram_mut()[0] = 0x80;
 
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x80, d1);
-        assert_eq!(0x00, d2);
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C | Flags::Z, r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x80, d1);
+    assert_eq!(0x00, d2);
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C | Flags::Z, r2.flags());
 }
 
 #[test]
@@ -3049,27 +2688,23 @@ fn sla_a_test() {
 
 macro_rules! sra_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0x81);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0x81, r1.$r());
-            assert_eq!(0xC0, r2.$r());
-            assert_eq!(Flags::empty(), r1.flags());
-            assert_eq!(Flags::C, r2.flags());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0x81);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0x81, r1.$r());
+        assert_eq!(0xC0, r2.$r());
+        assert_eq!(Flags::empty(), r1.flags());
+        assert_eq!(Flags::C, r2.flags());
     }
 }
 
@@ -3105,29 +2740,25 @@ fn sra_l_test() {
 
 #[test]
 fn sra_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xCB;
-        (*mmu).cartridge_rom[1] = 0x2E;
-        (*mmu).cartridge_ram[0] = 0x81;
-
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xCB;
+    mmu.cartridge.rom_mut()[1] = 0x2E;
+    mmu.cartridge.ram_mut()[0] = 0x81;
 
-        destroy((cpu, mmu));
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x81, d1);
-        assert_eq!(0xC0, d2);
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C, r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x81, d1);
+    assert_eq!(0xC0, d2);
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C, r2.flags());
 }
 
 #[test]
@@ -3137,29 +2768,24 @@ fn sra_a_test() {
 
 macro_rules! swap_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0x81);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0x81, r1.$r());
-            assert_eq!(0x18, r2.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0x81);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0x81, r1.$r());
+        assert_eq!(0x18, r2.$r());
     }
 }
 
-
 #[test]
 fn swap_b_test() {
     swap_r8_test!(0x30, b, set_b);
@@ -3192,28 +2818,24 @@ fn swap_l_test() {
 
 #[test]
 fn swap_hl_addr() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xCB;
-        (*mmu).cartridge_rom[1] = 0x36;
-        (*mmu).cartridge_ram[0] = 0x81;
-
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xCB;
+    mmu.cartridge.rom_mut()[1] = 0x36;
+    mmu.cartridge.ram_mut()[0] = 0x81;
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x81, d1);
-        assert_eq!(0x18, d2);
-    }
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x81, d1);
+    assert_eq!(0x18, d2);
 }
 
 #[test]
@@ -3223,27 +2845,23 @@ fn swap_a_test() {
 
 macro_rules! srl_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0x81);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-
-            let r1 = (*cpu).registers();
-            let tk = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, tk);
-            assert_eq!(0, r1.pc());
-            assert_eq!(2, r2.pc());
-            assert_eq!(0x81, r1.$r());
-            assert_eq!(0x40, r2.$r());
-            assert_eq!(Flags::empty(), r1.flags());
-            assert_eq!(Flags::C, r2.flags());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0x81);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+
+        let r1 = cpu.registers();
+        let tk = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, tk);
+        assert_eq!(0, r1.pc());
+        assert_eq!(2, r2.pc());
+        assert_eq!(0x81, r1.$r());
+        assert_eq!(0x40, r2.$r());
+        assert_eq!(Flags::empty(), r1.flags());
+        assert_eq!(Flags::C, r2.flags());
     }
 }
 
@@ -3279,29 +2897,25 @@ fn srl_l_test() {
 
 #[test]
 fn srl_hl_addr_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0xCB;
-        (*mmu).cartridge_rom[1] = 0x3E;
-        (*mmu).cartridge_ram[0] = 0x81;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0xCB;
+    mmu.cartridge.rom_mut()[1] = 0x3E;
+    mmu.cartridge.ram_mut()[0] = 0x81;
 
-        let d1 = (*mmu).cartridge_ram[0];
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let d2 = (*mmu).cartridge_ram[0];
+    let d1 = mmu.cartridge.ram()[0];
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let d2 = mmu.cartridge.ram()[0];
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-        assert_eq!(0x81, d1);
-        assert_eq!(0x40, d2);
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C, r2.flags());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
+    assert_eq!(0x81, d1);
+    assert_eq!(0x40, d2);
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C, r2.flags());
 }
 
 #[test]
@@ -3311,63 +2925,59 @@ fn srl_a_test() {
 
 macro_rules! bit_set_r8_test {
     ($opcode:literal, $r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-            (*mmu).cartridge_rom[2] = 0xCB;
-            (*mmu).cartridge_rom[3] = $opcode + 8;
-            (*mmu).cartridge_rom[4] = 0xCB;
-            (*mmu).cartridge_rom[5] = $opcode + 16;
-            (*mmu).cartridge_rom[6] = 0xCB;
-            (*mmu).cartridge_rom[7] = $opcode + 24;
-            (*mmu).cartridge_rom[8] = 0xCB;
-            (*mmu).cartridge_rom[9] = $opcode + 32;
-            (*mmu).cartridge_rom[10] = 0xCB;
-            (*mmu).cartridge_rom[11] = $opcode + 40;
-            (*mmu).cartridge_rom[12] = 0xCB;
-            (*mmu).cartridge_rom[13] = $opcode + 48;
-            (*mmu).cartridge_rom[14] = 0xCB;
-            (*mmu).cartridge_rom[15] = $opcode + 56;
-
-            let t1 = (*cpu).cycle();
-            let r1 = (*cpu).registers();
-            let t2 = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            let t3 = (*cpu).cycle();
-            let r3 = (*cpu).registers();
-            let t4 = (*cpu).cycle();
-            let r4 = (*cpu).registers();
-            let t5 = (*cpu).cycle();
-            let r5 = (*cpu).registers();
-            let t6 = (*cpu).cycle();
-            let r6 = (*cpu).registers();
-            let t7 = (*cpu).cycle();
-            let r7 = (*cpu).registers();
-            let t8 = (*cpu).cycle();
-            let r8 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, t1);
-            assert_eq!(8, t2);
-            assert_eq!(8, t3);
-            assert_eq!(8, t4);
-            assert_eq!(8, t5);
-            assert_eq!(8, t6);
-            assert_eq!(8, t7);
-            assert_eq!(8, t8);
-
-            assert_eq!(0x01, r1.$r());
-            assert_eq!(0x03, r2.$r());
-            assert_eq!(0x07, r3.$r());
-            assert_eq!(0x0F, r4.$r());
-            assert_eq!(0x1F, r5.$r());
-            assert_eq!(0x3F, r6.$r());
-            assert_eq!(0x7F, r7.$r());
-            assert_eq!(0xFF, r8.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+        mmu.cartridge.rom_mut()[2] = 0xCB;
+        mmu.cartridge.rom_mut()[3] = $opcode + 8;
+        mmu.cartridge.rom_mut()[4] = 0xCB;
+        mmu.cartridge.rom_mut()[5] = $opcode + 16;
+        mmu.cartridge.rom_mut()[6] = 0xCB;
+        mmu.cartridge.rom_mut()[7] = $opcode + 24;
+        mmu.cartridge.rom_mut()[8] = 0xCB;
+        mmu.cartridge.rom_mut()[9] = $opcode + 32;
+        mmu.cartridge.rom_mut()[10] = 0xCB;
+        mmu.cartridge.rom_mut()[11] = $opcode + 40;
+        mmu.cartridge.rom_mut()[12] = 0xCB;
+        mmu.cartridge.rom_mut()[13] = $opcode + 48;
+        mmu.cartridge.rom_mut()[14] = 0xCB;
+        mmu.cartridge.rom_mut()[15] = $opcode + 56;
+
+        let t1 = cpu.cycle(&mut mmu);
+        let r1 = cpu.registers();
+        let t2 = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        let t3 = cpu.cycle(&mut mmu);
+        let r3 = cpu.registers();
+        let t4 = cpu.cycle(&mut mmu);
+        let r4 = cpu.registers();
+        let t5 = cpu.cycle(&mut mmu);
+        let r5 = cpu.registers();
+        let t6 = cpu.cycle(&mut mmu);
+        let r6 = cpu.registers();
+        let t7 = cpu.cycle(&mut mmu);
+        let r7 = cpu.registers();
+        let t8 = cpu.cycle(&mut mmu);
+        let r8 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, t1);
+        assert_eq!(8, t2);
+        assert_eq!(8, t3);
+        assert_eq!(8, t4);
+        assert_eq!(8, t5);
+        assert_eq!(8, t6);
+        assert_eq!(8, t7);
+        assert_eq!(8, t8);
+
+        assert_eq!(0x01, r1.$r());
+        assert_eq!(0x03, r2.$r());
+        assert_eq!(0x07, r3.$r());
+        assert_eq!(0x0F, r4.$r());
+        assert_eq!(0x1F, r5.$r());
+        assert_eq!(0x3F, r6.$r());
+        assert_eq!(0x7F, r7.$r());
+        assert_eq!(0xFF, r8.$r());
     }
 }
 
@@ -3384,64 +2994,60 @@ fn bit_set_b_test() {
 
 macro_rules! bit_reset_r8_test {
     ($opcode:literal, $r:tt, $set_r:tt) => {
-        unsafe {
-            let (cpu, mmu) = build();
-            (*cpu).regs.$set_r(0xFF);
-            (*mmu).cartridge_rom[0] = 0xCB;
-            (*mmu).cartridge_rom[1] = $opcode;
-            (*mmu).cartridge_rom[2] = 0xCB;
-            (*mmu).cartridge_rom[3] = $opcode + 8;
-            (*mmu).cartridge_rom[4] = 0xCB;
-            (*mmu).cartridge_rom[5] = $opcode + 16;
-            (*mmu).cartridge_rom[6] = 0xCB;
-            (*mmu).cartridge_rom[7] = $opcode + 24;
-            (*mmu).cartridge_rom[8] = 0xCB;
-            (*mmu).cartridge_rom[9] = $opcode + 32;
-            (*mmu).cartridge_rom[10] = 0xCB;
-            (*mmu).cartridge_rom[11] = $opcode + 40;
-            (*mmu).cartridge_rom[12] = 0xCB;
-            (*mmu).cartridge_rom[13] = $opcode + 48;
-            (*mmu).cartridge_rom[14] = 0xCB;
-            (*mmu).cartridge_rom[15] = $opcode + 56;
-
-            let t1 = (*cpu).cycle();
-            let r1 = (*cpu).registers();
-            let t2 = (*cpu).cycle();
-            let r2 = (*cpu).registers();
-            let t3 = (*cpu).cycle();
-            let r3 = (*cpu).registers();
-            let t4 = (*cpu).cycle();
-            let r4 = (*cpu).registers();
-            let t5 = (*cpu).cycle();
-            let r5 = (*cpu).registers();
-            let t6 = (*cpu).cycle();
-            let r6 = (*cpu).registers();
-            let t7 = (*cpu).cycle();
-            let r7 = (*cpu).registers();
-            let t8 = (*cpu).cycle();
-            let r8 = (*cpu).registers();
-            (*cpu).cycle();
-
-            destroy((cpu, mmu));
-
-            assert_eq!(8, t1);
-            assert_eq!(8, t2);
-            assert_eq!(8, t3);
-            assert_eq!(8, t4);
-            assert_eq!(8, t5);
-            assert_eq!(8, t6);
-            assert_eq!(8, t7);
-            assert_eq!(8, t8);
-
-            assert_eq!(0xfe, r1.$r());
-            assert_eq!(0xfc, r2.$r());
-            assert_eq!(0xf8, r3.$r());
-            assert_eq!(0xf0, r4.$r());
-            assert_eq!(0xe0, r5.$r());
-            assert_eq!(0xc0, r6.$r());
-            assert_eq!(0x80, r7.$r());
-            assert_eq!(0x00, r8.$r());
-        }
+        let (mut cpu, mut mmu) = build();
+        cpu.regs.$set_r(0xFF);
+        mmu.cartridge.rom_mut()[0] = 0xCB;
+        mmu.cartridge.rom_mut()[1] = $opcode;
+        mmu.cartridge.rom_mut()[2] = 0xCB;
+        mmu.cartridge.rom_mut()[3] = $opcode + 8;
+        mmu.cartridge.rom_mut()[4] = 0xCB;
+        mmu.cartridge.rom_mut()[5] = $opcode + 16;
+        mmu.cartridge.rom_mut()[6] = 0xCB;
+        mmu.cartridge.rom_mut()[7] = $opcode + 24;
+        mmu.cartridge.rom_mut()[8] = 0xCB;
+        mmu.cartridge.rom_mut()[9] = $opcode + 32;
+        mmu.cartridge.rom_mut()[10] = 0xCB;
+        mmu.cartridge.rom_mut()[11] = $opcode + 40;
+        mmu.cartridge.rom_mut()[12] = 0xCB;
+        mmu.cartridge.rom_mut()[13] = $opcode + 48;
+        mmu.cartridge.rom_mut()[14] = 0xCB;
+        mmu.cartridge.rom_mut()[15] = $opcode + 56;
+
+        let t1 = cpu.cycle(&mut mmu);
+        let r1 = cpu.registers();
+        let t2 = cpu.cycle(&mut mmu);
+        let r2 = cpu.registers();
+        let t3 = cpu.cycle(&mut mmu);
+        let r3 = cpu.registers();
+        let t4 = cpu.cycle(&mut mmu);
+        let r4 = cpu.registers();
+        let t5 = cpu.cycle(&mut mmu);
+        let r5 = cpu.registers();
+        let t6 = cpu.cycle(&mut mmu);
+        let r6 = cpu.registers();
+        let t7 = cpu.cycle(&mut mmu);
+        let r7 = cpu.registers();
+        let t8 = cpu.cycle(&mut mmu);
+        let r8 = cpu.registers();
+        cpu.cycle(&mut mmu);
+
+        assert_eq!(8, t1);
+        assert_eq!(8, t2);
+        assert_eq!(8, t3);
+        assert_eq!(8, t4);
+        assert_eq!(8, t5);
+        assert_eq!(8, t6);
+        assert_eq!(8, t7);
+        assert_eq!(8, t8);
+
+        assert_eq!(0xfe, r1.$r());
+        assert_eq!(0xfc, r2.$r());
+        assert_eq!(0xf8, r3.$r());
+        assert_eq!(0xf0, r4.$r());
+        assert_eq!(0xe0, r5.$r());
+        assert_eq!(0xc0, r6.$r());
+        assert_eq!(0x80, r7.$r());
+        assert_eq!(0x00, r8.$r());
     }
 }
 
@@ -3458,1122 +3064,921 @@ fn bit_reset_b_test() {
 
 #[test]
 fn cpl_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_a(0x28);
-        (*mmu).cartridge_rom[0] = 0x2F;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_a(0x28);
+    mmu.cartridge.rom_mut()[0] = 0x2F;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(4, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x1, r2.pc());
-        assert_eq!(0x28, r1.a());
-        assert_eq!(0xD7, r2.a());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x1, r2.pc());
+    assert_eq!(0x28, r1.a());
+    assert_eq!(0xD7, r2.a());
 }
 
 #[test]
 fn scf_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0x37;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0x37;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(Flags::empty(), r1.flags());
-        assert_eq!(Flags::C, r2.flags());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(Flags::empty(), r1.flags());
+    assert_eq!(Flags::C, r2.flags());
 }
 
 #[test]
 fn ccf_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x3F;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x3F;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(4, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(1, r2.pc());
-        assert_eq!(Flags::C, r1.flags());
-        assert_eq!(Flags::empty(), r2.flags());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(1, r2.pc());
+    assert_eq!(Flags::C, r1.flags());
+    assert_eq!(Flags::empty(), r2.flags());
 }
 
 #[test]
 fn jr_forward_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x18;
-        (*mmu).cartridge_rom[1] = 0x10;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x18;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x12, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x12, r2.pc());
 }
 
 #[test]
 fn jr_backward_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x18;
-        (*mmu).cartridge_rom[1] = 0xFE;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x18;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x00, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x00, r2.pc());
 }
 
 #[test]
 fn jr_zero_forward_zero_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::Z);
-        (*mmu).cartridge_rom[0] = 0x28;
-        (*mmu).cartridge_rom[1] = 0x10;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::Z);
+    mmu.cartridge.rom_mut()[0] = 0x28;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(12, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x12, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x12, r2.pc());
 }
 
 #[test]
 fn jr_zero_backward_zero_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::Z);
-        (*mmu).cartridge_rom[0] = 0x28;
-        (*mmu).cartridge_rom[1] = 0xFE;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::Z);
+    mmu.cartridge.rom_mut()[0] = 0x28;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x00, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x00, r2.pc());
 }
 
 #[test]
 fn jr_zero_forward_zero_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x28;
-        (*mmu).cartridge_rom[1] = 0x10;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x28;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 }
 
 #[test]
 fn jr_zero_backward_zero_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x28;
-        (*mmu).cartridge_rom[1] = 0xFE;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x28;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 }
 
 #[test]
 fn jr_not_zero_forward_zero_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::Z);
-        (*mmu).cartridge_rom[0] = 0x20;
-        (*mmu).cartridge_rom[1] = 0x10;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::Z);
+    mmu.cartridge.rom_mut()[0] = 0x20;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 }
 
 #[test]
 fn jr_not_zero_backward_zero_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::Z);
-        (*mmu).cartridge_rom[0] = 0x20;
-        (*mmu).cartridge_rom[1] = 0xFE;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::Z);
+    mmu.cartridge.rom_mut()[0] = 0x20;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 }
 
 #[test]
 fn jr_not_zero_forward_zero_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x20;
-        (*mmu).cartridge_rom[1] = 0x10;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x20;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x12, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x12, r2.pc());
 }
 
 #[test]
 fn jr_not_zero_backward_zero_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x20;
-        (*mmu).cartridge_rom[1] = 0xFE;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x20;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(0, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(0, r2.pc());
 }
 
 #[test]
 fn jr_not_carry_forward_carry_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x30;
-        (*mmu).cartridge_rom[1] = 0x10;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x30;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 }
 
 #[test]
 fn jr_not_carry_backward_carry_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x30;
-        (*mmu).cartridge_rom[1] = 0xFE;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x30;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 }
 
 #[test]
 fn jr_not_carry_forward_carry_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x30;
-        (*mmu).cartridge_rom[1] = 0x10;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x30;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x12, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x12, r2.pc());
 }
 
 #[test]
 fn jr_not_carry_backward_carry_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x30;
-        (*mmu).cartridge_rom[1] = 0xFE;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x30;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(12, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(0, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(0, r2.pc());
 }
 
-
 #[test]
 fn jr_carry_forward_carry_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x38;
-        (*mmu).cartridge_rom[1] = 0x10;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x38;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x12, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x12, r2.pc());
 }
 
 #[test]
 fn jr_carry_backward_carry_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0x38;
-        (*mmu).cartridge_rom[1] = 0xFE;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0x38;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(12, tk);
-        assert_eq!(0x00, r1.pc());
-        assert_eq!(0x00, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x00, r1.pc());
+    assert_eq!(0x00, r2.pc());
 }
 
 #[test]
 fn jr_carry_forward_carry_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x38;
-        (*mmu).cartridge_rom[1] = 0x10;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x38;
+    mmu.cartridge.rom_mut()[1] = 0x10;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 }
 
 #[test]
 fn jr_carry_backward_carry_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0xA000);
-        (*mmu).cartridge_rom[0] = 0x38;
-        (*mmu).cartridge_rom[1] = 0xFE;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0xA000);
+    mmu.cartridge.rom_mut()[0] = 0x38;
+    mmu.cartridge.rom_mut()[1] = 0xFE;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(8, tk);
-        assert_eq!(0, r1.pc());
-        assert_eq!(2, r2.pc());
-    }
+    assert_eq!(8, tk);
+    assert_eq!(0, r1.pc());
+    assert_eq!(2, r2.pc());
 }
 
 #[test]
 fn jp_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xC3;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xC3;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
 }
 
 #[test]
 fn jp_hl_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_hl(0x4000);
-        (*mmu).cartridge_rom[0] = 0xE9;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_hl(0x4000);
+    mmu.cartridge.rom_mut()[0] = 0xE9;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(4, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-    }
+    assert_eq!(4, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
 }
 
 #[test]
 fn jp_zero_with_zero_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::Z);
-        (*mmu).cartridge_rom[0] = 0xCA;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::Z);
+    mmu.cartridge.rom_mut()[0] = 0xCA;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
 }
 
 #[test]
 fn jp_zero_with_zero_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xCA;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xCA;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(12, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0003, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0003, r2.pc());
 }
 
 #[test]
 fn jp_carry_with_carry_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0xDA;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0xDA;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
 }
 
 #[test]
 fn jp_carry_with_carry_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xDA;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xDA;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0003, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0003, r2.pc());
 }
 
 #[test]
 fn jp_not_zero_with_zero_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::Z);
-        (*mmu).cartridge_rom[0] = 0xC2;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::Z);
+    mmu.cartridge.rom_mut()[0] = 0xC2;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0003, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0003, r2.pc());
 }
 
 #[test]
 fn jp_not_zero_with_zero_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xC2;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xC2;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
 }
 
 #[test]
 fn jp_not_carry_with_carry_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0xD2;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0xD2;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(12, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0003, r2.pc());
-    }
+    assert_eq!(12, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0003, r2.pc());
 }
 
 #[test]
 fn jp_not_carry_with_carry_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xD2;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xD2;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
 }
 
 #[test]
 fn call_ret_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xCD;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-        (*mmu).cartridge_rom[0x4000] = 0xC9;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let t2 = (*cpu).cycle();
-        let r3 = (*cpu).registers();
-        (*cpu).cycle();
-        let ie = (*cpu).int_svc.enabled();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(24, t1);
-        assert_eq!(16, t2);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-        assert_eq!(0x0003, r3.pc());
-        assert_eq!(false, ie);
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xCD;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+    mmu.cartridge.rom_mut()[0x4000] = 0xC9;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let t2 = cpu.cycle(&mut mmu);
+    let r3 = cpu.registers();
+    cpu.cycle(&mut mmu);
+    let ie = cpu.int_svc.enabled();
+
+    assert_eq!(24, t1);
+    assert_eq!(16, t2);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
+    assert_eq!(0x0003, r3.pc());
+    assert!(!ie);
 }
 
 #[test]
 fn call_reti_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xCD;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-        (*mmu).cartridge_rom[0x4000] = 0xD9;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let t2 = (*cpu).cycle();
-        let r3 = (*cpu).registers();
-        (*cpu).cycle();
-        let ie = (*cpu).int_svc.enabled();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(24, t1);
-        assert_eq!(16, t2);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-        assert_eq!(0x0003, r3.pc());
-        assert_eq!(true, ie);
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xCD;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+    mmu.cartridge.rom_mut()[0x4000] = 0xD9;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let t2 = cpu.cycle(&mut mmu);
+    let r3 = cpu.registers();
+    cpu.cycle(&mut mmu);
+    let ie = cpu.int_svc.enabled();
+
+    assert_eq!(24, t1);
+    assert_eq!(16, t2);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
+    assert_eq!(0x0003, r3.pc());
+    assert!(ie);
 }
 
 #[test]
 fn call_carry_ret_carry_with_carry_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0xDC;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-        (*mmu).cartridge_rom[0x4000] = 0xD8;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let t2 = (*cpu).cycle();
-        let r3 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(24, t1);
-        assert_eq!(20, t2);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-        assert_eq!(0x0003, r3.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0xDC;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+    mmu.cartridge.rom_mut()[0x4000] = 0xD8;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let t2 = cpu.cycle(&mut mmu);
+    let r3 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(24, t1);
+    assert_eq!(20, t2);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
+    assert_eq!(0x0003, r3.pc());
 }
 
 #[test]
 fn call_carry_with_carry_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xDC;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(12, t1);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0003, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xDC;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(12, t1);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0003, r2.pc());
 }
 
 #[test]
 fn call_zero_ret_zero_with_zero_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::Z);
-        (*mmu).cartridge_rom[0] = 0xCC;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-        (*mmu).cartridge_rom[0x4000] = 0xC8;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let t2 = (*cpu).cycle();
-        let r3 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(24, t1);
-        assert_eq!(20, t2);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-        assert_eq!(0x0003, r3.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::Z);
+    mmu.cartridge.rom_mut()[0] = 0xCC;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+    mmu.cartridge.rom_mut()[0x4000] = 0xC8;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let t2 = cpu.cycle(&mut mmu);
+    let r3 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(24, t1);
+    assert_eq!(20, t2);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
+    assert_eq!(0x0003, r3.pc());
 }
 
 #[test]
 fn call_zero_with_zero_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xCC;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(12, t1);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0003, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xCC;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(12, t1);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0003, r2.pc());
 }
 
 #[test]
 fn call_not_carry_ret_not_carry_with_carry_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xD4;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-        (*mmu).cartridge_rom[0x4000] = 0xD0;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let t2 = (*cpu).cycle();
-        let r3 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(24, t1);
-        assert_eq!(20, t2);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-        assert_eq!(0x0003, r3.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xD4;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+    mmu.cartridge.rom_mut()[0x4000] = 0xD0;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let t2 = cpu.cycle(&mut mmu);
+    let r3 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(24, t1);
+    assert_eq!(20, t2);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
+    assert_eq!(0x0003, r3.pc());
 }
 
 #[test]
 fn call_not_carry_with_carry_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::C);
-        (*mmu).cartridge_rom[0] = 0xD4;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(12, t1);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0003, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::C);
+    mmu.cartridge.rom_mut()[0] = 0xD4;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(12, t1);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0003, r2.pc());
 }
 
 #[test]
 fn call_not_zero_ret_not_zero_with_zero_not_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xC4;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-        (*mmu).cartridge_rom[0x4000] = 0xC0;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let t2 = (*cpu).cycle();
-        let r3 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(24, t1);
-        assert_eq!(20, t2);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x4000, r2.pc());
-        assert_eq!(0x0003, r3.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xC4;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+    mmu.cartridge.rom_mut()[0x4000] = 0xC0;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let t2 = cpu.cycle(&mut mmu);
+    let r3 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(24, t1);
+    assert_eq!(20, t2);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x4000, r2.pc());
+    assert_eq!(0x0003, r3.pc());
 }
 
 #[test]
 fn call_not_zero_with_zero_set_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_flags(Flags::Z);
-        (*mmu).cartridge_rom[0] = 0xC4;
-        (*mmu).cartridge_rom[1] = 0x00;
-        (*mmu).cartridge_rom[2] = 0x40;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(12, t1);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0003, r2.pc());
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_flags(Flags::Z);
+    mmu.cartridge.rom_mut()[0] = 0xC4;
+    mmu.cartridge.rom_mut()[1] = 0x00;
+    mmu.cartridge.rom_mut()[2] = 0x40;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(12, t1);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0003, r2.pc());
 }
 
 #[test]
 fn rst00_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xC7;
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xC7;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0000, r2.pc());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0000, r2.pc());
 }
 
 #[test]
 fn rst08_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xCF;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xCF;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0008, r2.pc());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0008, r2.pc());
 }
 
 #[test]
 fn rst10_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xD7;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xD7;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0010, r2.pc());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0010, r2.pc());
 }
 
 #[test]
 fn rst18_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xDF;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xDF;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0018, r2.pc());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0018, r2.pc());
 }
 
 #[test]
 fn rst20_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xE7;
-
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xE7;
 
-        destroy((cpu, mmu));
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0020, r2.pc());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0020, r2.pc());
 }
 
 #[test]
 fn rst28_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xEF;
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xEF;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0028, r2.pc());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0028, r2.pc());
 }
 
 #[test]
 fn rst30_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xF7;
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xF7;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0030, r2.pc());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0030, r2.pc());
 }
 
 #[test]
 fn rst38_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*mmu).cartridge_rom[0] = 0xFF;
+    let (mut cpu, mut mmu) = build();
+    mmu.cartridge.rom_mut()[0] = 0xFF;
 
-        let r1 = (*cpu).registers();
-        let tk = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        (*cpu).cycle();
+    let r1 = cpu.registers();
+    let tk = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    cpu.cycle(&mut mmu);
 
-        destroy((cpu, mmu));
-
-        assert_eq!(16, tk);
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0038, r2.pc());
-    }
+    assert_eq!(16, tk);
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0038, r2.pc());
 }
 
 #[test]
 fn push_pop_test() {
-    unsafe {
-        let (cpu, mmu) = build();
-        (*cpu).regs.set_af(0xAAF0);
-        (*cpu).regs.set_bc(0xBBCC);
-        (*cpu).regs.set_de(0xDDEE);
-        (*cpu).regs.set_hl(0x8811);
-        (*cpu).regs.set_sp(0xFFFE);
-        (*mmu).cartridge_rom[0] = 0xF5;
-        (*mmu).cartridge_rom[1] = 0xC5;
-        (*mmu).cartridge_rom[2] = 0xD5;
-        (*mmu).cartridge_rom[3] = 0xE5;
-        (*mmu).cartridge_rom[4] = 0xF1;
-        (*mmu).cartridge_rom[5] = 0xC1;
-        (*mmu).cartridge_rom[6] = 0xD1;
-        (*mmu).cartridge_rom[7] = 0xE1;
-
-        let r1 = (*cpu).registers();
-        let t1 = (*cpu).cycle();
-        let t2 = (*cpu).cycle();
-        let t3 = (*cpu).cycle();
-        let t4 = (*cpu).cycle();
-        let r2 = (*cpu).registers();
-        let t5 = (*cpu).cycle();
-        let t6 = (*cpu).cycle();
-        let t7 = (*cpu).cycle();
-        let t8 = (*cpu).cycle();
-        let r3 = (*cpu).registers();
-        (*cpu).cycle();
-
-        destroy((cpu, mmu));
-
-        assert_eq!(16, t1);
-        assert_eq!(16, t2);
-        assert_eq!(16, t3);
-        assert_eq!(16, t4);
-        assert_eq!(12, t5);
-        assert_eq!(12, t6);
-        assert_eq!(12, t7);
-        assert_eq!(12, t8);
-
-        assert_eq!(0x0000, r1.pc());
-        assert_eq!(0x0004, r2.pc());
-        assert_eq!(0x0008, r3.pc());
-
-        assert_eq!(0xfffe, r1.sp());
-        assert_eq!(0xfff6, r2.sp());
-        assert_eq!(0xfffe, r3.sp());
-
-        assert_eq!(0x8810, r3.af());
-        assert_eq!(0xDDEE, r3.bc());
-        assert_eq!(0xBBCC, r3.de());
-        assert_eq!(0xAAF0, r3.hl());
-    }
+    let (mut cpu, mut mmu) = build();
+    cpu.regs.set_af(0xAAF0);
+    cpu.regs.set_bc(0xBBCC);
+    cpu.regs.set_de(0xDDEE);
+    cpu.regs.set_hl(0x8811);
+    cpu.regs.set_sp(0xFFFE);
+    mmu.cartridge.rom_mut()[0] = 0xF5;
+    mmu.cartridge.rom_mut()[1] = 0xC5;
+    mmu.cartridge.rom_mut()[2] = 0xD5;
+    mmu.cartridge.rom_mut()[3] = 0xE5;
+    mmu.cartridge.rom_mut()[4] = 0xF1;
+    mmu.cartridge.rom_mut()[5] = 0xC1;
+    mmu.cartridge.rom_mut()[6] = 0xD1;
+    mmu.cartridge.rom_mut()[7] = 0xE1;
+
+    let r1 = cpu.registers();
+    let t1 = cpu.cycle(&mut mmu);
+    let t2 = cpu.cycle(&mut mmu);
+    let t3 = cpu.cycle(&mut mmu);
+    let t4 = cpu.cycle(&mut mmu);
+    let r2 = cpu.registers();
+    let t5 = cpu.cycle(&mut mmu);
+    let t6 = cpu.cycle(&mut mmu);
+    let t7 = cpu.cycle(&mut mmu);
+    let t8 = cpu.cycle(&mut mmu);
+    let r3 = cpu.registers();
+    cpu.cycle(&mut mmu);
+
+    assert_eq!(16, t1);
+    assert_eq!(16, t2);
+    assert_eq!(16, t3);
+    assert_eq!(16, t4);
+    assert_eq!(12, t5);
+    assert_eq!(12, t6);
+    assert_eq!(12, t7);
+    assert_eq!(12, t8);
+
+    assert_eq!(0x0000, r1.pc());
+    assert_eq!(0x0004, r2.pc());
+    assert_eq!(0x0008, r3.pc());
+
+    assert_eq!(0xfffe, r1.sp());
+    assert_eq!(0xfff6, r2.sp());
+    assert_eq!(0xfffe, r3.sp());
+
+    assert_eq!(0x8810, r3.af());
+    assert_eq!(0xDDEE, r3.bc());
+    assert_eq!(0xBBCC, r3.de());
+    assert_eq!(0xAAF0, r3.hl());
 }
\ No newline at end of file
@@ -0,0 +1,62 @@
+pub mod extractor;
+pub mod recorder;
+pub mod scope;
+#[cfg(feature = "sdl")]
+pub mod sdl;
+
+use crate::joypad::Keys;
+use crate::ppu::SCREEN_BUFFER_SIZE;
+use crate::spu::note::NoteEvent;
+
+/// Screen output, called by the core once every frame
+pub trait VideoSink {
+    /// Complete frame, in ARGB
+    fn present_frame(&mut self, frame: &[u8; SCREEN_BUFFER_SIZE]);
+}
+
+/// Audio output, called by the core once every frame with interleaved stereo samples (left, right)
+pub trait AudioSink {
+    /// Host sample rate, the SPU output is resampled to it
+    fn sample_rate(&self) -> u32;
+
+    /// Samples of the frame just run
+    fn queue(&mut self, samples: &[i16]);
+
+    /// Whether the SPU should render each channel apart as well, from the next frame
+    fn wants_stems(&self) -> bool { false }
+
+    /// Samples of the frame for each channel (square 1, square 2, wave, noise), panned and scaled
+    /// like the mix, called after `queue`
+    fn queue_stems(&mut self, _stems: [&[i16]; 4]) { }
+
+    /// Whether the SPU should log the notes of each channel, from the next frame
+    fn wants_notes(&self) -> bool { false }
+
+    /// Note changes of the frame, which lasted `ticks` ticks, called after `queue`
+    fn queue_notes(&mut self, _notes: &[NoteEvent], _ticks: u32) { }
+}
+
+/// Joypad input, polled by the core at the start of every frame
+pub trait InputSource {
+    /// Keys pressed and released since the last poll
+    fn poll_keys(&mut self) -> (Keys, Keys);
+}
+
+/// Frontend without screen, speakers or buttons
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Null;
+
+impl VideoSink for Null {
+    fn present_frame(&mut self, _frame: &[u8; SCREEN_BUFFER_SIZE]) { }
+}
+
+impl AudioSink for Null {
+    fn sample_rate(&self) -> u32 { crate::spu::DEFAULT_SAMPLE_RATE }
+    fn queue(&mut self, _samples: &[i16]) { }
+}
+
+impl InputSource for Null {
+    fn poll_keys(&mut self) -> (Keys, Keys) {
+        (Keys::empty(), Keys::empty())
+    }
+}
@@ -0,0 +1,271 @@
+use super::AudioSink;
+
+use crate::midi::{self, MidiFile, MidiTrack};
+use crate::spu::note::NoteEvent;
+use crate::spu::util::calculate_volume;
+use crate::TICKS_PER_SECOND;
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Track names, in channel order
+pub const TRACK_NAMES: [&str; 4] = ["Square 1", "Square 2", "Wave", "Noise"];
+
+/// MIDI channel of each track, the noise on the General MIDI percussion channel
+const MIDI_CHANNELS: [u8; 4] = [0, 1, 2, 9];
+
+/// Keys the noise is spread over, the General MIDI percussion sounds
+const PERCUSSION_KEYS: (u8, u8) = (35, 81);
+
+/// Audio sink turning the notes of each channel into a MIDI file, then passing the audio on
+///
+/// A channel plays a note from its trigger until it falls silent or moves to another key, at the
+/// velocity of its volume then. Volume changes on the same key are not kept.
+pub struct Extractor<S: AudioSink> {
+    inner: S,
+    extraction: Option<Extraction>,
+}
+
+struct Extraction {
+    path: PathBuf,
+    tracks: Vec<MidiTrack>,
+
+    // Note each channel is holding, and ticks since the start
+    held: [Option<HeldNote>; 4],
+    ticks: u64,
+}
+
+#[derive(Clone, Copy)]
+struct HeldNote {
+    key: u8,
+    velocity: u8,
+    start: u32,
+}
+
+impl Extraction {
+    fn time(&self, ticks: u64) -> u32 {
+        ((self.ticks + ticks) * midi::TICKS_PER_SECOND / TICKS_PER_SECOND) as u32
+    }
+
+    /// Write the note held by a channel, unless it lasted no time as when a frequency is written
+    /// a register at a time
+    fn release(&mut self, channel: usize, time: u32) {
+        if let Some(note) = self.held[channel].take() {
+            if time > note.start {
+                let track = &mut self.tracks[channel];
+                track.note_on(note.start, MIDI_CHANNELS[channel], note.key, note.velocity);
+                track.note_off(time, MIDI_CHANNELS[channel], note.key);
+            }
+        }
+    }
+}
+
+impl<S: AudioSink> Extractor<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner, extraction: None }
+    }
+
+    pub fn inner(&self) -> &S { &self.inner }
+    pub fn inner_mut(&mut self) -> &mut S { &mut self.inner }
+
+    pub fn is_extracting(&self) -> bool {
+        self.extraction.is_some()
+    }
+
+    /// Path of the MIDI file being extracted
+    pub fn path(&self) -> Option<&Path> {
+        self.extraction.as_ref().map(|extraction| extraction.path.as_path())
+    }
+
+    /// Extract to `path` from the next frame, the file is written on `stop`
+    pub fn start(&mut self, path: &Path) -> io::Result<()> {
+        self.stop()?;
+
+        let mut tracks: Vec<MidiTrack> = TRACK_NAMES.iter().map(|name| MidiTrack::new(name)).collect();
+        tracks[0].set_tempo(0, midi::TEMPO);
+        self.extraction = Some(Extraction { path: path.to_path_buf(), tracks, held: [None; 4], ticks: 0 });
+        Ok(())
+    }
+
+    /// Release the notes still held and write the file
+    pub fn stop(&mut self) -> io::Result<()> {
+        if let Some(mut extraction) = self.extraction.take() {
+            let time = extraction.time(0);
+            for channel in 0..4 {
+                extraction.release(channel, time);
+            }
+            MidiFile { tracks: extraction.tracks }.save(&extraction.path)?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(mut self) -> io::Result<S> {
+        self.stop()?;
+        Ok(self.inner)
+    }
+}
+
+impl<S: AudioSink> AudioSink for Extractor<S> {
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn queue(&mut self, samples: &[i16]) {
+        self.inner.queue(samples);
+    }
+
+    fn wants_stems(&self) -> bool {
+        self.inner.wants_stems()
+    }
+
+    fn queue_stems(&mut self, stems: [&[i16]; 4]) {
+        self.inner.queue_stems(stems);
+    }
+
+    fn wants_notes(&self) -> bool {
+        self.extraction.is_some() || self.inner.wants_notes()
+    }
+
+    fn queue_notes(&mut self, notes: &[NoteEvent], ticks: u32) {
+        if let Some(extraction) = &mut self.extraction {
+            for event in notes {
+                let time = extraction.time(event.time as u64);
+                let key = event.note.map(|note| {
+                    if event.channel == 3 {
+                        midi_key(note.frequency).clamp(PERCUSSION_KEYS.0, PERCUSSION_KEYS.1)
+                    } else {
+                        midi_key(note.frequency)
+                    }
+                });
+
+                if key == extraction.held[event.channel].map(|note| note.key) && !event.trigger {
+                    continue;
+                }
+                extraction.release(event.channel, time);
+                if let (Some(key), Some(note)) = (key, event.note) {
+                    let velocity = (calculate_volume(note.volume) as u8).max(1);
+                    extraction.held[event.channel] = Some(HeldNote { key, velocity, start: time });
+                }
+            }
+            extraction.ticks += ticks as u64;
+        }
+        self.inner.queue_notes(notes, ticks);
+    }
+}
+
+/// Nearest key of the frequency, A4 (69) at 440Hz
+pub fn midi_key(frequency: u32) -> u8 {
+    if frequency == 0 {
+        return 0;
+    }
+    let key = 69.0 + 12.0 * (frequency as f64 / 440.0).log2();
+    key.round().clamp(0.0, 127.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::Null;
+    use crate::gb::GameBoy;
+    use crate::model::Model;
+    use crate::test_rom::{ldh_writes, test_rom};
+
+    /// Past the boot chime and a few frames, channel 1 plays A4 then A5, channel 2 a fading C5
+    fn melody_rom() -> Vec<u8> {
+        let wait = [0x01, 0x00, 0x40, 0x0B, 0x78, 0xB1, 0x20, 0xFB]; // LD BC, $4000; DEC BC; LD A, B; OR C; JR NZ, -5
+        let mut code = ldh_writes(&[(0x26, 0x00), (0x26, 0x80)]);
+        code.extend_from_slice(&wait);
+        code.extend(ldh_writes(&[(0x12, 0xF0), (0x13, 0xD6), (0x14, 0x86), (0x17, 0xF1), (0x18, 0x06), (0x19, 0x87)]));
+        code.extend_from_slice(&wait);
+        code.extend(ldh_writes(&[(0x13, 0x6B), (0x14, 0x87)]));
+        code.extend_from_slice(&[0x18, 0xFE]); // JR -2
+        test_rom(&code)
+    }
+
+    /// Note events of a track: (time, status, key, velocity), past the track name and tempo
+    fn track_notes(data: &[u8]) -> Vec<(u32, u8, u8, u8)> {
+        let mut notes = Vec::new();
+        let (mut i, mut time) = (0, 0);
+        while i < data.len() {
+            let mut delta = 0;
+            loop {
+                delta = (delta << 7) | (data[i] & 0x7F) as u32;
+                i += 1;
+                if data[i - 1] & 0x80 == 0 {
+                    break;
+                }
+            }
+            time += delta;
+            if data[i] == 0xFF {
+                i += 3 + data[i + 2] as usize;
+            } else {
+                notes.push((time, data[i], data[i + 1], data[i + 2]));
+                i += 3;
+            }
+        }
+        notes
+    }
+
+    fn tracks(data: &[u8]) -> Vec<Vec<(u32, u8, u8, u8)>> {
+        let mut tracks = Vec::new();
+        let mut i = 14;
+        while i < data.len() {
+            assert_eq!(b"MTrk", &data[i..i + 4]);
+            let len = u32::from_be_bytes([data[i + 4], data[i + 5], data[i + 6], data[i + 7]]) as usize;
+            tracks.push(track_notes(&data[i + 8..i + 8 + len]));
+            i += 8 + len;
+        }
+        tracks
+    }
+
+    #[test]
+    fn midi_key_test() {
+        assert_eq!(69, midi_key(440));
+        assert_eq!(81, midi_key(880));
+        assert_eq!(60, midi_key(262));
+        assert_eq!(0, midi_key(0));
+        assert_eq!(127, midi_key(524_288));
+    }
+
+    #[test]
+    fn extract_test() {
+        let path = std::env::temp_dir().join(format!("kiwi-gb-extractor-{}.mid", std::process::id()));
+
+        let mut gameboy = GameBoy::new(Model::Dmg, None);
+        gameboy.load_rom(melody_rom()).unwrap();
+
+        let mut extractor = Extractor::new(Null);
+        extractor.start(&path).unwrap();
+        assert!(extractor.wants_notes());
+        for _ in 0..60 {
+            gameboy.run_next_frame(&mut Null, &mut Null, &mut extractor);
+        }
+        extractor.stop().unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(b"MThd", &data[0..4]);
+        let tracks = tracks(&data);
+        assert_eq!(4, tracks.len());
+
+        // A4 (fparam 1750), then A5 (1899) replacing it
+        let square1 = &tracks[0];
+        assert_eq!(4, square1.len(), "{:?}", square1);
+        assert_eq!((0x90, 69, 127), (square1[0].1, square1[0].2, square1[0].3));
+        assert_eq!((0x80, 69), (square1[1].1, square1[1].2));
+        assert_eq!((0x90, 81), (square1[2].1, square1[2].2));
+        assert_eq!(square1[1].0, square1[2].0);
+        assert!(square1[0].0 > 0 && square1[1].0 > square1[0].0);
+        assert_eq!((0x80, 81), (square1[3].1, square1[3].2));
+
+        // C5 (fparam 1798) fading out, released once silent
+        let square2 = &tracks[1];
+        assert_eq!(2, square2.len(), "{:?}", square2);
+        assert_eq!((0x91, 72, 127), (square2[0].1, square2[0].2, square2[0].3));
+        assert_eq!((0x81, 72), (square2[1].1, square2[1].2));
+        assert!(square2[1].0 < square1[3].0);
+
+        assert!(tracks[2].is_empty());
+        assert!(tracks[3].is_empty());
+    }
+}
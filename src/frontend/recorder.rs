@@ -0,0 +1,215 @@
+use super::AudioSink;
+
+use crate::spu::note::NoteEvent;
+use crate::wav::WavWriter;
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Suffixes of the stem files, in channel order
+pub const STEM_NAMES: [&str; 4] = ["square1", "square2", "wave", "noise"];
+
+type Writer = WavWriter<BufWriter<File>>;
+
+/// Audio sink writing the mix, and each channel apart if asked, to WAV files, then passing it on
+///
+/// Works the same in front of a speaker or of `Null`, as it only sees what the SPU hands out.
+pub struct Recorder<S: AudioSink> {
+    inner: S,
+    recording: Option<Recording>,
+}
+
+struct Recording {
+    path: PathBuf,
+    mix: Writer,
+    stems: Option<Vec<Writer>>,
+
+    // With stems, the mix waits for the first frame they are rendered, so all files line up
+    started: bool,
+}
+
+impl<S: AudioSink> Recorder<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner, recording: None }
+    }
+
+    pub fn inner(&self) -> &S { &self.inner }
+    pub fn inner_mut(&mut self) -> &mut S { &mut self.inner }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Path of the mix being written
+    pub fn path(&self) -> Option<&Path> {
+        self.recording.as_ref().map(|recording| recording.path.as_path())
+    }
+
+    /// Write to `path` from the next frame, and to `stem_path(path, name)` for each channel with stems
+    pub fn start(&mut self, path: &Path, stems: bool) -> io::Result<()> {
+        self.stop()?;
+
+        let sample_rate = self.inner.sample_rate();
+        let mix = WavWriter::create(path, sample_rate, 2)?;
+        let stems = if stems {
+            let writers = STEM_NAMES.iter()
+                .map(|name| WavWriter::create(&stem_path(path, name), sample_rate, 2))
+                .collect::<io::Result<Vec<_>>>()?;
+            Some(writers)
+        } else {
+            None
+        };
+
+        self.recording = Some(Recording { path: path.to_path_buf(), mix, stems, started: false });
+        Ok(())
+    }
+
+    /// Complete the files being written
+    pub fn stop(&mut self) -> io::Result<()> {
+        if let Some(recording) = self.recording.take() {
+            recording.mix.finish()?;
+            for stem in recording.stems.into_iter().flatten() {
+                stem.finish()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(mut self) -> io::Result<S> {
+        self.stop()?;
+        Ok(self.inner)
+    }
+
+    fn write(&mut self, write: impl FnOnce(&mut Recording) -> io::Result<()>) {
+        if let Some(recording) = &mut self.recording {
+            if let Err(err) = write(recording) {
+                error!("Failed to record to {}: {}", recording.path.display(), err);
+                self.recording = None;
+            }
+        }
+    }
+}
+
+impl<S: AudioSink> AudioSink for Recorder<S> {
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn queue(&mut self, samples: &[i16]) {
+        self.write(|recording| {
+            if recording.stems.is_none() {
+                recording.started = true;
+            }
+            if recording.started {
+                recording.mix.write_samples(samples)?;
+            }
+            Ok(())
+        });
+        self.inner.queue(samples);
+    }
+
+    fn wants_stems(&self) -> bool {
+        self.recording.as_ref().is_some_and(|recording| recording.stems.is_some()) || self.inner.wants_stems()
+    }
+
+    fn queue_stems(&mut self, stems: [&[i16]; 4]) {
+        self.write(|recording| {
+            if let Some(writers) = &mut recording.stems {
+                // The mix of this frame went by already, it starts with the next one
+                if recording.started {
+                    for (writer, samples) in writers.iter_mut().zip(stems.iter()) {
+                        writer.write_samples(samples)?;
+                    }
+                }
+                recording.started = true;
+            }
+            Ok(())
+        });
+        self.inner.queue_stems(stems);
+    }
+
+    fn wants_notes(&self) -> bool {
+        self.inner.wants_notes()
+    }
+
+    fn queue_notes(&mut self, notes: &[NoteEvent], ticks: u32) {
+        self.inner.queue_notes(notes, ticks);
+    }
+}
+
+/// `dir/name.wav` to `dir/name-suffix.wav`
+pub fn stem_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, suffix, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::Null;
+    use crate::gb::GameBoy;
+    use crate::model::Model;
+    use crate::test_rom::{ldh_writes, test_rom};
+
+    /// Square on channel 1 and noise on channel 4, both panned center
+    fn sound_rom() -> Vec<u8> {
+        let mut code = ldh_writes(&[(0x24, 0x77), (0x25, 0x99), (0x12, 0xF0), (0x13, 0x00), (0x14, 0x87), (0x21, 0xF0), (0x22, 0x50), (0x23, 0x80)]);
+        code.extend_from_slice(&[0x18, 0xFE]); // JR -2
+        test_rom(&code)
+    }
+
+    fn read_samples(path: &Path) -> Vec<i16> {
+        let data = std::fs::read(path).unwrap();
+        assert_eq!(b"RIFF", &data[0..4]);
+        data[44..].chunks(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect()
+    }
+
+    #[test]
+    fn stem_path_test() {
+        assert_eq!(PathBuf::from("out/run-noise.wav"), stem_path(Path::new("out/run.wav"), "noise"));
+        assert_eq!(PathBuf::from("run-wave"), stem_path(Path::new("run"), "wave"));
+    }
+
+    #[test]
+    fn record_test() {
+        let dir = std::env::temp_dir().join(format!("kiwi-gb-recorder-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("run.wav");
+
+        let mut gameboy = GameBoy::new(Model::Dmg, None);
+        gameboy.load_rom(sound_rom()).unwrap();
+        // The capacitor starts charged on the mix only, the stems would take a while to add up
+        gameboy.mmu_mut().spu.set_charge_factor(1.0);
+
+        let mut recorder = Recorder::new(Null);
+        recorder.start(&path, true).unwrap();
+        assert!(recorder.wants_stems());
+        for _ in 0..10 {
+            gameboy.run_next_frame(&mut Null, &mut Null, &mut recorder);
+        }
+        recorder.stop().unwrap();
+        assert!(!recorder.is_recording());
+
+        // All files line up, and the stems add up to the mix
+        let mix = read_samples(&path);
+        let stems: Vec<Vec<i16>> = STEM_NAMES.iter().map(|name| read_samples(&stem_path(&path, name))).collect();
+        assert!(!mix.is_empty());
+        for stem in &stems {
+            assert_eq!(mix.len(), stem.len());
+        }
+        assert!(stems[0].iter().any(|&s| s != stems[0][0]));
+        assert!(stems[3].iter().any(|&s| s != stems[3][0]));
+        assert!(stems[1].iter().chain(stems[2].iter()).all(|&s| s == 0));
+        for (i, &sample) in mix.iter().enumerate().skip(32) {
+            let sum: i32 = stems.iter().map(|stem| stem[i] as i32).sum();
+            assert!((sample as i32 - sum).abs() <= 4, "{}: {} != {}", i, sample, sum);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+use super::AudioSink;
+
+use crate::spu::note::NoteEvent;
+
+use std::collections::VecDeque;
+
+/// Traces kept, the four channels then the mix
+pub const TRACES: usize = 5;
+pub const MIX_TRACE: usize = 4;
+
+/// Audio sink keeping the latest output of each channel and of the mix, then passing it on
+///
+/// Traces are mono, the average of both sides, and only filled while enabled as the channels
+/// are rendered apart for them.
+pub struct Scope<S: AudioSink> {
+    inner: S,
+    enabled: bool,
+    length: usize,
+    traces: [VecDeque<i16>; TRACES],
+}
+
+impl<S: AudioSink> Scope<S> {
+    /// Keep the last `length` samples of each trace
+    pub fn new(inner: S, length: usize) -> Self {
+        Self {
+            inner,
+            enabled: false,
+            length,
+            traces: Default::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &S { &self.inner }
+    pub fn inner_mut(&mut self) -> &mut S { &mut self.inner }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Start or stop following the output, starting over from silence
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        for trace in self.traces.iter_mut() {
+            trace.clear();
+        }
+    }
+
+    /// Latest samples of a trace, oldest first, up to the scope length
+    pub fn trace(&self, trace: usize) -> &VecDeque<i16> {
+        &self.traces[trace]
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn push(&mut self, trace: usize, samples: &[i16]) {
+        let trace = &mut self.traces[trace];
+        trace.extend(samples.chunks(2).map(|pair| ((pair[0] as i32 + pair[1] as i32) / 2) as i16));
+        let excess = trace.len().saturating_sub(self.length);
+        trace.drain(..excess);
+    }
+}
+
+impl<S: AudioSink> AudioSink for Scope<S> {
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn queue(&mut self, samples: &[i16]) {
+        if self.enabled {
+            self.push(MIX_TRACE, samples);
+        }
+        self.inner.queue(samples);
+    }
+
+    fn wants_stems(&self) -> bool {
+        self.enabled || self.inner.wants_stems()
+    }
+
+    fn queue_stems(&mut self, stems: [&[i16]; 4]) {
+        if self.enabled {
+            for (trace, samples) in stems.iter().enumerate() {
+                self.push(trace, samples);
+            }
+        }
+        self.inner.queue_stems(stems);
+    }
+
+    fn wants_notes(&self) -> bool {
+        self.inner.wants_notes()
+    }
+
+    fn queue_notes(&mut self, notes: &[NoteEvent], ticks: u32) {
+        self.inner.queue_notes(notes, ticks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::Null;
+
+    #[test]
+    fn trace_test() {
+        let mut scope = Scope::new(Null, 3);
+        scope.queue(&[100, 100]);
+        assert!(!scope.wants_stems());
+        assert!(scope.trace(MIX_TRACE).is_empty());
+
+        // Downmixed, and only the latest samples
+        scope.set_enabled(true);
+        assert!(scope.wants_stems());
+        scope.queue(&[100, 200, -100, -100, 0, 0, 10, 0]);
+        scope.queue_stems([&[2, 0], &[], &[], &[8, 8, 6, 6]]);
+        assert_eq!(&[-100, 0, 5], scope.trace(MIX_TRACE).iter().cloned().collect::<Vec<_>>().as_slice());
+        assert_eq!(&[1], scope.trace(0).iter().cloned().collect::<Vec<_>>().as_slice());
+        assert!(scope.trace(1).is_empty());
+        assert_eq!(&[8, 6], scope.trace(3).iter().cloned().collect::<Vec<_>>().as_slice());
+
+        scope.set_enabled(false);
+        assert!(scope.trace(MIX_TRACE).is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+use super::{AudioSink, InputSource, VideoSink};
+use super::scope::{Scope, MIX_TRACE, TRACES};
+
+use crate::joypad::Keys;
+use crate::ppu::*;
+
+use sdl2::Sdl;
+use sdl2::audio::{AudioSpecDesired, AudioQueue};
+use sdl2::event::*;
+use sdl2::keyboard::*;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Point;
+use sdl2::render::*;
+use sdl2::video::*;
+
+pub const BUTTON_A:      Keycode = Keycode::Space;
+pub const BUTTON_B:      Keycode = Keycode::LShift;
+pub const BUTTON_UP:     Keycode = Keycode::Up;
+pub const BUTTON_DOWN:   Keycode = Keycode::Down;
+pub const BUTTON_LEFT:   Keycode = Keycode::Left;
+pub const BUTTON_RIGHT:  Keycode = Keycode::Right;
+pub const BUTTON_START:  Keycode = Keycode::Return;
+pub const BUTTON_SELECT: Keycode = Keycode::Backspace;
+
+/// SDL window the frames are drawn on
+pub struct SdlVideo {
+    window_canvas: Canvas<Window>,
+    //window_texture_creator: TextureCreator<WindowContext>,
+    window_texture: Texture,
+}
+
+impl SdlVideo {
+    pub fn new(sdl: &Sdl) -> Self {
+        let window = {
+            let scale = 4;
+            let width = (SCREEN_PIXEL_WIDTH * scale) as u32;
+            let height = (SCREEN_PIXEL_HEIGHT * scale) as u32;
+
+            let video_subsystem = sdl.video().unwrap();
+
+            video_subsystem
+                .window("KiwiGB", width, height)
+                .position_centered()
+                .build()
+                .unwrap()
+        };
+
+        let window_canvas = window.into_canvas().build().unwrap();
+
+        let window_texture: Texture = {
+            window_canvas.texture_creator().create_texture(
+                Some(PixelFormatEnum::ARGB32),
+                TextureAccess::Static,
+                SCREEN_PIXEL_WIDTH as u32,
+                SCREEN_PIXEL_HEIGHT as u32,
+            ).unwrap()
+        };
+
+        Self { window_canvas, window_texture }
+    }
+
+    pub fn window_id(&self) -> u32 {
+        self.window_canvas.window().id()
+    }
+}
+
+impl VideoSink for SdlVideo {
+    fn present_frame(&mut self, frame: &[u8; SCREEN_BUFFER_SIZE]) {
+        self.window_texture.update(None, frame, SCREEN_BUFFER_WIDTH).unwrap();
+        self.window_canvas.clear();
+        self.window_canvas.copy(&self.window_texture, None, None).unwrap();
+        self.window_canvas.present();
+    }
+}
+
+/// Colors of the scope traces, the four channels then the mix
+const SCOPE_COLORS: [Color; TRACES] = [
+    Color { r: 0xE0, g: 0x60, b: 0x60, a: 0xFF },
+    Color { r: 0xE0, g: 0xC0, b: 0x40, a: 0xFF },
+    Color { r: 0x60, g: 0xC0, b: 0xE0, a: 0xFF },
+    Color { r: 0x80, g: 0xE0, b: 0x80, a: 0xFF },
+    Color { r: 0xF0, g: 0xF0, b: 0xF0, a: 0xFF },
+];
+const SCOPE_MUTED_COLOR: Color = Color { r: 0x50, g: 0x50, b: 0x50, a: 0xFF };
+const SCOPE_AXIS_COLOR: Color = Color { r: 0x28, g: 0x28, b: 0x28, a: 0xFF };
+
+/// Second SDL window, drawing the latest output of each channel and of the mix in lanes
+pub struct SdlScope {
+    window_canvas: Canvas<Window>,
+}
+
+impl SdlScope {
+    pub fn new(sdl: &Sdl) -> Self {
+        let video_subsystem = sdl.video().unwrap();
+        let window = video_subsystem
+            .window("KiwiGB Scope", 640, 100 * TRACES as u32)
+            .build()
+            .unwrap();
+
+        Self { window_canvas: window.into_canvas().build().unwrap() }
+    }
+
+    pub fn window_id(&self) -> u32 {
+        self.window_canvas.window().id()
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.window_canvas.window_mut().set_title(title).unwrap();
+    }
+
+    /// Draw each trace, the channels left out of the mix greyed out
+    pub fn draw<S: AudioSink>(&mut self, scope: &Scope<S>, audible: [bool; 4]) {
+        let (width, height) = self.window_canvas.output_size().unwrap();
+        let lane_height = (height / TRACES as u32) as i32;
+
+        self.window_canvas.set_draw_color(Color::BLACK);
+        self.window_canvas.clear();
+
+        for trace in 0..TRACES {
+            let center = lane_height * trace as i32 + lane_height / 2;
+            self.window_canvas.set_draw_color(SCOPE_AXIS_COLOR);
+            self.window_canvas.draw_line((0, center), (width as i32, center)).unwrap();
+
+            // A channel stays within a quarter of the mix range
+            let range = if trace == MIX_TRACE { i16::MAX as i32 } else { i16::MAX as i32 / 4 };
+            let samples = scope.trace(trace);
+            if samples.is_empty() {
+                continue;
+            }
+            let points: Vec<Point> = (0..width as usize).map(|x| {
+                let sample = samples[x * samples.len() / width as usize] as i32;
+                let y = center - (sample * lane_height / 2 / range).clamp(-lane_height / 2, lane_height / 2);
+                Point::new(x as i32, y)
+            }).collect();
+
+            let color = if trace == MIX_TRACE || audible[trace] { SCOPE_COLORS[trace] } else { SCOPE_MUTED_COLOR };
+            self.window_canvas.set_draw_color(color);
+            self.window_canvas.draw_lines(points.as_slice()).unwrap();
+        }
+
+        self.window_canvas.present();
+    }
+}
+
+/// SDL audio device, fed a frame of stereo samples at a time
+pub struct SdlAudio {
+    audio_queue: AudioQueue<i16>,
+}
+
+impl SdlAudio {
+    pub fn new(sdl: &Sdl, sample_rate: u32) -> Self {
+        let audio_subsystem = sdl.audio().unwrap();
+
+        let spec = AudioSpecDesired { freq: Some(sample_rate as i32), channels: Some(2), samples: Some(1024) };
+        let audio_queue: AudioQueue<i16> = audio_subsystem.open_queue(None, &spec).unwrap();
+        audio_queue.resume();
+
+        Self { audio_queue }
+    }
+
+    /// Queued bytes over which frames are dropped, a tenth of a second
+    fn max_queued(&self) -> u32 {
+        self.audio_queue.spec().freq as u32 * 4 / 10
+    }
+}
+
+impl AudioSink for SdlAudio {
+    fn sample_rate(&self) -> u32 {
+        self.audio_queue.spec().freq as u32
+    }
+
+    fn queue(&mut self, samples: &[i16]) {
+        // Frames are paced by the host clock, drop one when the device falls behind
+        if self.audio_queue.size() > self.max_queued() {
+            return;
+        }
+        self.audio_queue.queue(samples);
+    }
+}
+
+/// Keyboard of the SDL window, mapped to the joypad
+pub struct SdlInput {
+    window_id: u32,
+
+    pressed_keys: Keys,
+    released_keys: Keys,
+}
+
+impl SdlInput {
+    pub fn new(window_id: u32) -> Self {
+        Self {
+            window_id,
+            pressed_keys: Keys::empty(),
+            released_keys: Keys::empty(),
+        }
+    }
+
+    pub fn handle_event(&mut self, evt: &Event) {
+        match evt {
+            Event::KeyDown { keycode: Some(keycode), repeat: false, window_id, ..} if *window_id == self.window_id => {
+                let keys = keys(*keycode);
+                self.pressed_keys.insert(keys);
+                self.released_keys.remove(keys);
+            }
+            Event::KeyUp { keycode: Some(keycode), repeat: false, window_id, ..} if *window_id == self.window_id => {
+                let keys = keys(*keycode);
+                self.released_keys.insert(keys);
+                self.pressed_keys.remove(keys);
+            }
+            _ => { }
+        }
+    }
+}
+
+impl InputSource for SdlInput {
+    fn poll_keys(&mut self) -> (Keys, Keys) {
+        let keys = (self.pressed_keys, self.released_keys);
+        self.pressed_keys = Keys::empty();
+        self.released_keys = Keys::empty();
+        keys
+    }
+}
+
+fn keys(keycode: Keycode) -> Keys {
+    match keycode {
+        BUTTON_A      => Keys::A,
+        BUTTON_B      => Keys::B,
+        BUTTON_UP     => Keys::UP,
+        BUTTON_DOWN   => Keys::DOWN,
+        BUTTON_LEFT   => Keys::LEFT,
+        BUTTON_RIGHT  => Keys::RIGHT,
+        BUTTON_START  => Keys::START,
+        BUTTON_SELECT => Keys::SELECT,
+        _ => Keys::empty(),
+    }
+}
@@ -1,317 +1,286 @@
+pub mod dual;
 
+use crate::cartridge;
+use crate::cartridge::CartridgeError;
+use crate::cartridge::save;
 use crate::cpu::Cpu;
-use crate::mmu::Mmu;
-use crate::ppu::Ppu;
-use crate::spu::Spu;
-use crate::timer::Timer;
-use crate::joypad::Joypad;
+use crate::mmu::{Mmu, BOOT_ROM_SIZE};
+use crate::model::Model;
+use crate::ppu::SCREEN_BUFFER_SIZE;
+use crate::frontend::{AudioSink, InputSource, VideoSink};
 
 use crate::cpu::interrupt::Interrupt;
-use crate::cpu::flags::Flags;
-use crate::ppu::*;
+use crate::cpu::registers::Registers;
 use crate::joypad::Keys;
 use crate::MemoryBus;
+use crate::TICKS_PER_FRAME;
 
-use sdl2::Sdl;
-use sdl2::audio::{AudioSpecDesired, AudioQueue};
-use sdl2::event::*;
-use sdl2::keyboard::*;
-use sdl2::pixels::PixelFormatEnum;
-use sdl2::render::*;
-use sdl2::video::*;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
 
+/// Notified whenever the cartridge rumble motor turns on or off
+pub type RumbleCallback = Arc<dyn Fn(bool) + Send + Sync>;
+
+/// The whole console, cloning it takes a save state
+#[derive(Clone)]
 pub struct GameBoy {
     ticks: u64,
 
+    model: Model,
+    boot_rom: Option<Box<[u8; BOOT_ROM_SIZE]>>,
+
+    // #region rumble
+    rumble: bool,
+    rumble_callback: Option<RumbleCallback>,
+    // #endregion
+
     // #region input
     joypad_pressed_keys: Keys,
     joypad_released_keys: Keys,
     // #endregion
 
     // #region hardware
-    cpu: *mut Cpu,
-    mmu: *mut Mmu,
-    ppu: *mut Ppu,
-    spu: *mut Spu,
-    timer: *mut Timer,
-    joypad: *mut Joypad,
-    // #endregion
-
-    // #region audio-output
-    audio_channel_1: AudioQueue<i8>,
-    audio_channel_2: AudioQueue<i8>,
-    audio_channel_3: AudioQueue<i8>,
-    audio_channel_4: AudioQueue<i8>,
-    // #endregion
-
-    // #region video-output
-    window_canvas: Canvas<Window>,
-    //window_texture_creator: TextureCreator<WindowContext>,
-    window_texture: Texture,
+    cpu: Cpu,
+    mmu: Mmu,
     // #endregion
 }
 
-const TICKS_PER_SECOND: u64 = 4_194_304;
-const TICKS_PER_FRAME:  u64 = TICKS_PER_SECOND / 60;
-
-const BUTTON_A:      Keycode = Keycode::Space;
-const BUTTON_B:      Keycode = Keycode::LShift;
-const BUTTON_UP:     Keycode = Keycode::Up;
-const BUTTON_DOWN:   Keycode = Keycode::Down;
-const BUTTON_LEFT:   Keycode = Keycode::Left;
-const BUTTON_RIGHT:  Keycode = Keycode::Right;
-const BUTTON_START:  Keycode = Keycode::Return;
-const BUTTON_SELECT: Keycode = Keycode::Backspace;
-
 impl GameBoy {
-    pub fn new(sdl: &Sdl) -> Self {
-        // #region sdl
-        let (audio_channel_1, audio_channel_2, audio_channel_3, audio_channel_4) = {
-            let audio_subsystem = sdl.audio().unwrap();
-
-            let spec = AudioSpecDesired { freq: Some(44_100), channels: Some(2), samples: Some(2048) };
-            let ch1: AudioQueue<i8> = audio_subsystem.open_queue(None, &spec).unwrap();
-            let ch2: AudioQueue<i8> = audio_subsystem.open_queue(None, &spec).unwrap();
-            let ch3: AudioQueue<i8> = audio_subsystem.open_queue(None, &spec).unwrap();
-            let ch4: AudioQueue<i8> = audio_subsystem.open_queue(None, &spec).unwrap();
-
-            ch1.resume();
-            ch2.resume();
-            ch3.resume();
-            ch4.resume();
-
-            (ch1, ch2, ch3, ch4)
-        };
-
-        let window = {
-            let scale = 4;
-            let width = (SCREEN_PIXEL_WIDTH * scale) as u32;
-            let height = (SCREEN_PIXEL_HEIGHT * scale) as u32;
+    /// Power on the gameboy, running the given boot rom or starting from the post-boot state
+    pub fn new(model: Model, boot_rom: Option<Box<[u8; BOOT_ROM_SIZE]>>) -> Self {
+        let mut mmu = Mmu::default();
+        mmu.ppu.set_shades(model.shades());
+        mmu.spu.set_charge_factor(model.charge_factor());
+
+        let mut gameboy = Self {
+            ticks: 0,
+            model,
+            boot_rom,
+
+            cpu: Cpu::default(),
+            mmu,
 
-            let video_subsystem = sdl.video().unwrap();
+            rumble: false,
+            rumble_callback: None,
 
-            video_subsystem
-                .window("KiwiGB", width, height)
-                .position_centered()
-                .build()
-                .unwrap()
+            joypad_pressed_keys: Keys::empty(),
+            joypad_released_keys: Keys::empty(),
         };
+        gameboy.power_on();
+        gameboy
+    }
 
-        let window_canvas = window.into_canvas().build().unwrap();
+    pub fn model(&self) -> Model {
+        self.model
+    }
 
-        let window_texture: Texture = {
-            window_canvas.texture_creator().create_texture(
-                Some(PixelFormatEnum::ARGB32),
-                TextureAccess::Static,
-                SCREEN_PIXEL_WIDTH as u32,
-                SCREEN_PIXEL_HEIGHT as u32,
-            ).unwrap()
-        };
-        // #endregion
+    /// Either hand over to the boot rom, or set up the state it leaves for the model
+    fn power_on(&mut self) {
+        if let Some(boot_rom) = &self.boot_rom {
+            // #region boot-rom
+            self.cpu.regs = Registers::default();
+            self.mmu.boot_rom = Some(boot_rom.clone());
+            self.mmu.write(0xff40, 0x00);
+            // #endregion
+        } else {
+            // #region bios-skip
+            self.cpu.regs = self.model.post_boot_registers(self.mmu.cartridge.as_ref());
+            for (addr, data) in self.model.post_boot_io() {
+                self.write(addr, data);
+            }
+            self.mmu.timer.set_divider(self.model.post_boot_divider());
+            // #endregion
+        }
+    }
 
-        // #region raw-ptr
-        let cpu = Box::new(Cpu::default());
-        let cpu: *mut Cpu = Box::into_raw(cpu);
+    pub fn load_rom(&mut self, rom: Vec<u8>) -> Result<(), CartridgeError> {
+        let cartridge = cartridge::load(rom)?;
+        info!("Cartridge {:?}", cartridge.header());
 
-        let mmu = Box::new(Mmu::default());
-        let mmu: *mut Mmu = Box::into_raw(mmu);
+        self.mmu.cartridge = cartridge;
 
-        let ppu = Box::new(Ppu::default());
-        let ppu: *mut Ppu = Box::into_raw(ppu);
+        // The post-boot registers depend on the cartridge header
+        self.power_on();
+        Ok(())
+    }
 
-        let spu = Box::new(Spu::default());
-        let spu: *mut Spu = Box::into_raw(spu);
+    pub fn cpu(&self) -> &Cpu { &self.cpu }
+    pub fn mmu(&self) -> &Mmu { &self.mmu }
+    pub fn mmu_mut(&mut self) -> &mut Mmu { &mut self.mmu }
 
-        let timer = Box::new(Timer::default());
-        let timer: *mut Timer = Box::into_raw(timer);
+    /// Last complete frame, in ARGB
+    pub fn frame_buffer(&self) -> &[u8; SCREEN_BUFFER_SIZE] {
+        self.mmu.ppu.frame_buffer()
+    }
 
-        let joypad = Box::new(Joypad::default());
-        let joypad: *mut Joypad = Box::into_raw(joypad);
-        // #endregion
+    /// Cartridge RAM (and RTC) survives power off
+    pub fn has_battery(&self) -> bool {
+        self.mmu.cartridge.header().cartridge_type.battery
+    }
 
-        unsafe {
-            // #region mmap
-            (*cpu).mmu = mmu;
-            (*mmu).cpu = cpu;
-            (*mmu).ppu = ppu;
-            (*mmu).spu = spu;
-            (*mmu).timer = timer;
-            (*mmu).joypad = joypad;
-            // #endregion
+    /// Cartridge RAM or clock written since the last save
+    pub fn is_save_dirty(&self) -> bool {
+        self.mmu.cartridge_ram_dirty
+    }
 
-            // #region bios-skip
-            (*cpu).regs.set_flags(Flags::Z | Flags::H | Flags::C);
-            (*cpu).regs.set_a(0x01);
-            (*cpu).regs.set_f(0xb0);
-            (*cpu).regs.set_bc(0x0013);
-            (*cpu).regs.set_de(0x00d8);
-            (*cpu).regs.set_hl(0x014d);
-            (*cpu).regs.set_sp(0xfffe);
-            (*cpu).regs.set_pc(0x0100);
-
-            (*mmu).write(0xff05, 0x00);
-            (*mmu).write(0xff06, 0x00);
-            (*mmu).write(0xff07, 0x00);
-            /*
-            (*mmu).write(0xff10, 0x80);
-            (*mmu).write(0xff11, 0xbf);
-            (*mmu).write(0xff12, 0xf3);
-            (*mmu).write(0xff14, 0xbf);
-            (*mmu).write(0xff16, 0x3f);
-            (*mmu).write(0xff17, 0x00);
-            (*mmu).write(0xff19, 0xbf);
-            (*mmu).write(0xff1a, 0x7f);
-            (*mmu).write(0xff1b, 0xff);
-            (*mmu).write(0xff1c, 0x9f);
-            (*mmu).write(0xff1e, 0xbf);
-            (*mmu).write(0xff20, 0xff);
-            (*mmu).write(0xff21, 0x00);
-            (*mmu).write(0xff22, 0x00);
-            (*mmu).write(0xff23, 0xbf);
-            (*mmu).write(0xff24, 0x77);
-            (*mmu).write(0xff25, 0xf3);
-            (*mmu).write(0xff26, 0xf1);
-            */
-            (*mmu).write(0xff40, 0x91);
-            (*mmu).write(0xff42, 0x00);
-            (*mmu).write(0xff43, 0x00);
-            (*mmu).write(0xff45, 0x00);
-            (*mmu).write(0xff47, 0xfc);
-            (*mmu).write(0xff48, 0xff);
-            (*mmu).write(0xff49, 0xff);
-            (*mmu).write(0xff4a, 0x00);
-            (*mmu).write(0xff4b, 0x00);
-            (*mmu).write(0xffff, 0x00);
-            // #endregion
-        }
+    pub fn load_save(&mut self, path: &Path, sync_rtc: bool) -> io::Result<bool> {
+        self.mmu.cartridge_ram_dirty = false;
+        save::load(self.mmu.cartridge.as_mut(), path, sync_rtc)
+    }
 
-        let ticks = 0u64;
+    pub fn write_save(&mut self, path: &Path) -> io::Result<()> {
+        save::store(self.mmu.cartridge.as_ref(), path)?;
+        self.mmu.cartridge_ram_dirty = false;
+        Ok(())
+    }
 
-        Self {
-            ticks,
-            cpu,
-            mmu,
-            ppu,
-            spu,
-            timer,
-            joypad,
+    /// Register a callback notified whenever the cartridge rumble motor turns on or off
+    pub fn set_rumble_callback<F: Fn(bool) + Send + Sync + 'static>(&mut self, callback: F) {
+        self.rumble_callback = Some(Arc::new(callback));
+    }
 
-            audio_channel_1,
-            audio_channel_2,
-            audio_channel_3,
-            audio_channel_4,
+    /// Press the keys, starting from the next frame
+    pub fn press(&mut self, keys: Keys) {
+        self.joypad_pressed_keys.insert(keys);
+        self.joypad_released_keys.remove(keys);
+    }
 
-            window_canvas,
-            window_texture,
+    /// Release the keys, starting from the next frame
+    pub fn release(&mut self, keys: Keys) {
+        self.joypad_released_keys.insert(keys);
+        self.joypad_pressed_keys.remove(keys);
+    }
 
-            joypad_pressed_keys: Keys::empty(),
-            joypad_released_keys: Keys::empty(),
+    /// Emulate a frame, reading the joypad at the start and handing the frame and audio out at the end
+    pub fn run_next_frame(&mut self, input: &mut dyn InputSource, video: &mut dyn VideoSink, audio: &mut dyn AudioSink) {
+        self.begin_frame(input);
+        while self.frame_ticks() < TICKS_PER_FRAME {
+            self.step();
         }
+        self.end_frame(video, audio);
     }
 
-    pub fn load_rom(&mut self, rom: &Vec<u8>) {
-        unsafe {
-            for i in 0..rom.len() {
-                (*self.mmu).cartridge_rom[i] = rom[i];
-            }
+    pub(crate) fn begin_frame(&mut self, input: &mut dyn InputSource) {
+        let (pressed, released) = input.poll_keys();
+        self.release(released);
+        self.press(pressed);
+
+        if !self.joypad_released_keys.is_empty() {
+            self.mmu.joypad.release(self.joypad_released_keys);
+            self.joypad_released_keys = Keys::empty();
+        }
+        if !self.joypad_pressed_keys.is_empty() {
+            self.mmu.joypad.press(self.joypad_pressed_keys);
+            self.cpu.request_interrupt(Interrupt::HL_PIN);
+            self.joypad_pressed_keys = Keys::empty();
         }
     }
 
-    pub fn handle_event(&mut self, evt: &Event) {
-        let window_canvas_id = self.window_canvas.window().id();
-        match evt {
-            Event::KeyDown { keycode: Some(keycode), repeat: false, window_id, ..} => {
-                if *window_id == window_canvas_id {
-                    let keys = match *keycode {
-                        BUTTON_A      => Keys::A,
-                        BUTTON_B      => Keys::B,
-                        BUTTON_UP     => Keys::UP,
-                        BUTTON_DOWN   => Keys::DOWN,
-                        BUTTON_LEFT   => Keys::LEFT,
-                        BUTTON_RIGHT  => Keys::RIGHT,
-                        BUTTON_START  => Keys::START,
-                        BUTTON_SELECT => Keys::SELECT,
-                        _ => Keys::empty(),
-                    };
-                    self.joypad_pressed_keys.insert(keys);
-                    self.joypad_released_keys.remove(keys);
-                }
-            }
-            Event::KeyUp { keycode: Some(keycode), repeat: false, window_id, ..} => {
-                if *window_id == window_canvas_id {
-                    let keys = match *keycode {
-                        BUTTON_A      => Keys::A,
-                        BUTTON_B      => Keys::B,
-                        BUTTON_UP     => Keys::UP,
-                        BUTTON_DOWN   => Keys::DOWN,
-                        BUTTON_LEFT   => Keys::LEFT,
-                        BUTTON_RIGHT  => Keys::RIGHT,
-                        BUTTON_START  => Keys::START,
-                        BUTTON_SELECT => Keys::SELECT,
-                        _ => Keys::empty(),
-                    };
-                    self.joypad_released_keys.insert(keys);
-                    self.joypad_pressed_keys.remove(keys);
-                }
+    /// Ticks run since the start of the frame
+    pub(crate) fn frame_ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Run a single instruction along with the hardware
+    pub(crate) fn step(&mut self) {
+        let ticks = self.cpu.cycle(&mut self.mmu);
+        self.ticks += ticks;
+
+        self.mmu.timer.step(ticks);
+        self.mmu.ppu.step(ticks);
+        self.mmu.spu.step(ticks);
+        self.mmu.serial.step(ticks);
+        self.mmu.cartridge.step(ticks);
+
+        let rumble = self.mmu.cartridge.rumble();
+        if rumble != self.rumble {
+            self.rumble = rumble;
+            if let Some(callback) = &self.rumble_callback {
+                callback(rumble);
             }
-            _ => { }
+        }
+
+        if self.mmu.timer.frame_sequencer_clocked() {
+            self.mmu.spu.step_frame_sequencer();
+        }
+
+        if self.mmu.timer.overflow_interrupt_requested() {
+            self.cpu.request_interrupt(Interrupt::TIMER);
+        }
+        if self.mmu.serial.interrupt_requested() {
+            self.cpu.request_interrupt(Interrupt::SERIAL);
+        }
+        if self.mmu.ppu.lcdc_status_interrupt_requested() {
+            self.cpu.request_interrupt(Interrupt::LCDC);
+        }
+        if self.mmu.ppu.vertical_blank_interrupt_requested() {
+            self.cpu.request_interrupt(Interrupt::VBLANK);
         }
     }
 
-    pub fn run_next_frame(&mut self) {
-        unsafe {
-            if !self.joypad_released_keys.is_empty() {
-                (*self.joypad).release(self.joypad_released_keys);
-                self.joypad_released_keys = Keys::empty();
-            }
-            if !self.joypad_pressed_keys.is_empty() {
-                (*self.joypad).press(self.joypad_pressed_keys);
-                (*self.cpu).request_interrupt(Interrupt::HL_PIN);
-                self.joypad_pressed_keys = Keys::empty();
-            }
+    pub(crate) fn end_frame(&mut self, video: &mut dyn VideoSink, audio: &mut dyn AudioSink) {
+        self.ticks -= TICKS_PER_FRAME;
 
-            while self.ticks < TICKS_PER_FRAME {
-                let ticks = (*self.cpu).cycle();
-                self.ticks += ticks;
-
-                (*self.timer).step(ticks);
-                (*self.ppu).step(ticks);
-
-                if (*self.timer).overflow_interrupt_requested() {
-                    (*self.cpu).request_interrupt(Interrupt::TIMER);
-                }
-                if (*self.ppu).lcdc_status_interrupt_requested() {
-                    (*self.cpu).request_interrupt(Interrupt::LCDC);
-                }
-                if (*self.ppu).vertical_blank_interrupt_requested() {
-                    (*self.cpu).request_interrupt(Interrupt::VBLANK);
-                }
-            }
-            self.ticks -= TICKS_PER_FRAME;
-
-            (*self.spu).enqueue_audio_samples(
-                &mut self.audio_channel_1,
-                &mut self.audio_channel_2,
-                &mut self.audio_channel_3,
-                &mut self.audio_channel_4);
-
-            self.window_texture.update(None, (*self.ppu).frame_buffer(), SCREEN_BUFFER_WIDTH).unwrap();
-            self.window_canvas.clear();
-            self.window_canvas.copy(&mut self.window_texture, None, None).unwrap();
-            self.window_canvas.present();
+        self.mmu.spu.end_frame(audio);
+        video.present_frame(self.mmu.ppu.frame_buffer());
+    }
+}
+
+/// Memory as seen by the CPU, including the interrupt registers
+impl MemoryBus for GameBoy {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF0F | 0xFFFF => self.cpu.read(addr),
+            _ => self.mmu.read(addr),
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0xFF0F | 0xFFFF => self.cpu.write(addr, data),
+            _ => self.mmu.write(addr, data),
         }
     }
 }
 
-impl Drop for GameBoy {
-    fn drop(&mut self) {
-        unsafe {
-            drop(Box::from_raw(self.mmu));
-            drop(Box::from_raw(self.cpu));
-            drop(Box::from_raw(self.ppu));
-            drop(Box::from_raw(self.spu));
-            drop(Box::from_raw(self.timer));
-            drop(Box::from_raw(self.joypad));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bios::DMG_BIOS;
+    use crate::frontend::Null;
+
+    fn is_send<T: Send>() { }
+
+    #[test]
+    fn send_test() {
+        is_send::<GameBoy>();
+    }
+
+    #[test]
+    fn save_state_test() {
+        let mut gameboy = GameBoy::new(Model::Dmg, None);
+        gameboy.write(0xC000, 0x12);
+
+        let state = gameboy.clone();
+        gameboy.write(0xC000, 0x34);
+        gameboy.run_next_frame(&mut Null, &mut Null, &mut Null);
+
+        assert_eq!(0x12, state.read(0xC000));
+        assert_eq!(0x34, gameboy.read(0xC000));
+        assert_eq!(0x0100, state.cpu().registers().pc());
+    }
+
+    #[test]
+    fn boot_rom_test() {
+        // Blank cartridge, the boot rom locks up on the logo check
+        let mut gameboy = GameBoy::new(Model::Dmg, Some(Box::new(DMG_BIOS)));
+        assert_eq!(0x0000, gameboy.cpu().registers().pc());
+        assert_eq!(DMG_BIOS[0], gameboy.read(0x0000));
+
+        for _ in 0..10 {
+            gameboy.run_next_frame(&mut Null, &mut Null, &mut Null);
         }
+        assert!(gameboy.mmu().boot_rom.is_some());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,181 @@
+use super::GameBoy;
+
+use crate::TICKS_PER_FRAME;
+use crate::frontend::Null;
+use crate::ppu::SCREEN_BUFFER_SIZE;
+use crate::serial::TextCapture;
+use crate::serial::link::LinkCable;
+
+/// Two gameboys linked by a cable, run in lockstep in the same process
+///
+/// The one behind always runs the next instruction, so neither gets ahead of the other by more
+/// than an instruction and runs are deterministic. Joypad input goes through `press` and `release`
+/// on each side, audio is dropped.
+pub struct DualGameBoy {
+    left: GameBoy,
+    right: GameBoy,
+}
+
+impl DualGameBoy {
+    /// Plug the cable in, replacing whatever was connected to the serial ports
+    pub fn new(mut left: GameBoy, mut right: GameBoy) -> Self {
+        let (left_cable, right_cable) = LinkCable::pair();
+        left.mmu.serial.connect(Box::new(left_cable));
+        right.mmu.serial.connect(Box::new(right_cable));
+        Self { left, right }
+    }
+
+    pub fn left(&self) -> &GameBoy { &self.left }
+    pub fn left_mut(&mut self) -> &mut GameBoy { &mut self.left }
+    pub fn right(&self) -> &GameBoy { &self.right }
+    pub fn right_mut(&mut self) -> &mut GameBoy { &mut self.right }
+
+    /// Last complete frames, in ARGB
+    pub fn frame_buffers(&self) -> (&[u8; SCREEN_BUFFER_SIZE], &[u8; SCREEN_BUFFER_SIZE]) {
+        (self.left.frame_buffer(), self.right.frame_buffer())
+    }
+
+    pub fn run_next_frame(&mut self) {
+        self.left.begin_frame(&mut Null);
+        self.right.begin_frame(&mut Null);
+
+        while self.left.frame_ticks() < TICKS_PER_FRAME || self.right.frame_ticks() < TICKS_PER_FRAME {
+            self.step();
+        }
+
+        self.left.end_frame(&mut Null, &mut Null);
+        self.right.end_frame(&mut Null, &mut Null);
+    }
+
+    /// Run an instruction on the gameboy behind, the left one when they are even
+    pub(crate) fn step(&mut self) {
+        if self.left.frame_ticks() <= self.right.frame_ticks() {
+            self.left.step();
+        } else {
+            self.right.step();
+        }
+    }
+
+    /// Unplug the cable, giving both gameboys back
+    pub fn into_inner(mut self) -> (GameBoy, GameBoy) {
+        self.left.mmu.serial.connect(Box::new(TextCapture::default()));
+        self.right.mmu.serial.connect(Box::new(TextCapture::default()));
+        (self.left, self.right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryBus;
+    use crate::model::Model;
+    use crate::test_rom::{ldh_writes, test_rom};
+
+    /// Longest instruction, CALL taken
+    const MAX_INSTRUCTION_TICKS: u64 = 24;
+
+    /// Send a byte over serial, then keep the byte received at $C000
+    fn link_rom(sb: u8, sc: u8) -> Vec<u8> {
+        drawing_link_rom(0x00, sb, sc)
+    }
+
+    /// Fill the background with a tile of `pattern` rows first
+    fn drawing_link_rom(pattern: u8, sb: u8, sc: u8) -> Vec<u8> {
+        let mut code = ldh_writes(&[(0x40, 0x00)]);
+        code.extend_from_slice(&[
+            0x21, 0x00, 0x80,       // LD HL, $8000
+            0x3E, pattern,          // LD A, pattern
+            0x06, 0x10,             // LD B, 16
+            0x22,                   // LD (HL+), A
+            0x05,                   // DEC B
+            0x20, 0xFC,             // JR NZ, -4
+        ]);
+        code.extend(ldh_writes(&[(0x40, 0x91), (0x01, sb), (0x02, sc)]));
+        code.extend_from_slice(&[
+            0xF0, 0x02,             // LDH A, ($02)
+            0xCB, 0x7F,             // BIT 7, A
+            0x20, 0xFA,             // JR NZ, -6
+            0xF0, 0x01,             // LDH A, ($01)
+            0xEA, 0x00, 0xC0,       // LD ($C000), A
+            0x18, 0xFE,             // JR -2
+        ]);
+        test_rom(&code)
+    }
+
+    fn dual(left_rom: Vec<u8>, right_rom: Vec<u8>) -> DualGameBoy {
+        let mut left = GameBoy::new(Model::Dmg, None);
+        left.load_rom(left_rom).unwrap();
+        let mut right = GameBoy::new(Model::Dmg, None);
+        right.load_rom(right_rom).unwrap();
+        DualGameBoy::new(left, right)
+    }
+
+    #[test]
+    fn transfer_test() {
+        let mut dual = dual(link_rom(0x12, 0x81), link_rom(0x34, 0x80));
+        dual.run_next_frame();
+
+        assert_eq!(0x34, dual.left().read(0xC000));
+        assert_eq!(0x12, dual.right().read(0xC000));
+        assert_eq!(0x7F, dual.left().read(0xFF02));
+        assert_eq!(0x7E, dual.right().read(0xFF02));
+    }
+
+    #[test]
+    fn lockstep_test() {
+        // Black screen on the left, stripes on the right
+        let mut dual = dual(drawing_link_rom(0xFF, 0x12, 0x81), drawing_link_rom(0x0F, 0x34, 0x80));
+
+        // Never more than an instruction apart, so the transfer ends on both sides together
+        let mut started = [false; 2];
+        let mut ended = [None; 2];
+        while ended.iter().any(Option::is_none) {
+            dual.step();
+            let ticks = [dual.left().frame_ticks(), dual.right().frame_ticks()];
+            assert!(ticks[0].max(ticks[1]) - ticks[0].min(ticks[1]) <= MAX_INSTRUCTION_TICKS, "{:?}", ticks);
+            assert!(ticks[0] < TICKS_PER_FRAME && ticks[1] < TICKS_PER_FRAME);
+
+            for (side, gameboy) in [dual.left(), dual.right()].iter().enumerate() {
+                let transferring = gameboy.read(0xFF02) & 0x80 != 0;
+                if transferring {
+                    started[side] = true;
+                } else if started[side] && ended[side].is_none() {
+                    ended[side] = Some(ticks[side]);
+                }
+            }
+        }
+        let (left_end, right_end) = (ended[0].unwrap(), ended[1].unwrap());
+        assert!(left_end.max(right_end) - left_end.min(right_end) <= MAX_INSTRUCTION_TICKS, "{} {}", left_end, right_end);
+
+        // Finish the frame, then one with the LCD on all along
+        for _ in 0..2 {
+            dual.run_next_frame();
+            let (left, right) = (dual.left().frame_ticks(), dual.right().frame_ticks());
+            assert!(left < MAX_INSTRUCTION_TICKS && right < MAX_INSTRUCTION_TICKS, "{} {}", left, right);
+        }
+
+        let mut blank = GameBoy::new(Model::Dmg, None);
+        blank.run_next_frame(&mut Null, &mut Null, &mut Null);
+        let (left, right) = dual.frame_buffers();
+        assert_ne!(blank.frame_buffer()[..], left[..]);
+        assert_ne!(blank.frame_buffer()[..], right[..]);
+        assert_ne!(left[..], right[..]);
+    }
+
+    #[test]
+    fn deterministic_test() {
+        let run = || {
+            let mut dual = dual(link_rom(0xAA, 0x81), link_rom(0x55, 0x81));
+            for _ in 0..3 {
+                dual.run_next_frame();
+            }
+            let (left, right) = dual.into_inner();
+            (left.cpu().registers().clone(), right.cpu().registers().clone(), left.read(0xC000), right.read(0xC000))
+        };
+
+        let first = run();
+        assert_eq!(0x55, first.2);
+        assert_eq!(0xAA, first.3);
+        assert_eq!(first, run());
+    }
+}
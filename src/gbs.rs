@@ -0,0 +1,519 @@
+//! Game Boy Sound System rips, the sound driver and music data of a game without the game
+//!
+//! The player runs the rip on the CPU, timer and SPU alone, with a small driver of its own calling
+//! the init routine for the song then the play routine on each VBlank or timer interrupt.
+
+use crate::MemoryBus;
+use crate::TICKS_PER_FRAME;
+use crate::cpu::Cpu;
+use crate::cpu::interrupt::Interrupt;
+use crate::frontend::AudioSink;
+use crate::spu::Spu;
+use crate::timer::Timer;
+
+use std::fmt;
+
+pub const HEADER_SIZE: usize = 0x70;
+
+const SIGNATURE: &[u8; 3] = b"GBS";
+
+/// Ticks between two VBlanks, 154 lines of 456 ticks
+const VBLANK_PERIOD: u64 = 154 * 456;
+
+/// Frequency of the timer input clock, by TAC clock select
+const TIMER_CLOCKS: [u32; 4] = [4096, 262_144, 65_536, 16_384];
+
+const ROM_BANK_SIZE: usize = 0x4000;
+
+/// Driver entry point and the interrupt vectors it fills, the rip is loaded from $0400 on
+const DRIVER_ADDR: u16 = 0x0100;
+const VBLANK_VECTOR: u16 = 0x0040;
+const TIMER_VECTOR: u16 = 0x0050;
+const MIN_LOAD_ADDR: u16 = 0x0400;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GbsError {
+    /// File too small to contain the header
+    MissingHeader(usize),
+
+    /// File does not start with "GBS"
+    BadSignature,
+
+    UnsupportedVersion(u8),
+
+    /// Load address over the driver, or past the end of the ROM space
+    InvalidLoadAddress(u16),
+}
+
+impl fmt::Display for GbsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingHeader(len) =>
+                write!(f, "file too small to contain a gbs header ({} bytes)", len),
+            Self::BadSignature =>
+                write!(f, "not a gbs file"),
+            Self::UnsupportedVersion(version) =>
+                write!(f, "unsupported gbs version {}", version),
+            Self::InvalidLoadAddress(addr) =>
+                write!(f, "invalid load address ${:04X}", addr),
+        }
+    }
+}
+
+impl std::error::Error for GbsError { }
+
+/// Whether the data looks like a GBS file rather than a ROM
+pub fn is_gbs(data: &[u8]) -> bool {
+    data.starts_with(SIGNATURE)
+}
+
+// 00 "GBS", version
+// 04 Song count, first song (1-based)
+// 06 Load, init and play addresses, stack pointer
+// 0E Timer modulo (TMA), timer control (TAC)
+// 10 Title, author and copyright, 32 bytes each
+// 70 Code and data, loaded at the load address
+#[derive(Clone, Debug, PartialEq)]
+pub struct GbsHeader {
+    pub version: u8,
+    pub song_count: u8,
+    pub first_song: u8,
+
+    pub load_addr: u16,
+    pub init_addr: u16,
+    pub play_addr: u16,
+    pub stack_pointer: u16,
+
+    pub timer_modulo: u8,
+    pub timer_control: u8,
+
+    pub title: String,
+    pub author: String,
+    pub copyright: String,
+}
+
+impl GbsHeader {
+    pub fn parse(data: &[u8]) -> Result<Self, GbsError> {
+        if data.len() < HEADER_SIZE {
+            return Err(GbsError::MissingHeader(data.len()));
+        }
+        if !is_gbs(data) {
+            return Err(GbsError::BadSignature);
+        }
+        if data[0x03] != 1 {
+            return Err(GbsError::UnsupportedVersion(data[0x03]));
+        }
+
+        let word = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let text = |offset: usize| {
+            let field = &data[offset..offset + 0x20];
+            let len = field.iter().position(|&c| c == 0).unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..len]).trim().to_string()
+        };
+
+        let load_addr = word(0x06);
+        if !(MIN_LOAD_ADDR..0x8000).contains(&load_addr) {
+            return Err(GbsError::InvalidLoadAddress(load_addr));
+        }
+
+        Ok(Self {
+            version: data[0x03],
+            song_count: data[0x04],
+            first_song: data[0x05],
+
+            load_addr,
+            init_addr: word(0x08),
+            play_addr: word(0x0A),
+            stack_pointer: word(0x0C),
+
+            timer_modulo: data[0x0E],
+            timer_control: data[0x0F],
+
+            title: text(0x10),
+            author: text(0x30),
+            copyright: text(0x50),
+        })
+    }
+
+    /// Play routine called on the timer interrupt rather than on VBlank
+    pub fn uses_timer(&self) -> bool {
+        self.timer_control & 0x04 != 0
+    }
+
+    /// Calls of the play routine per second
+    pub fn play_rate(&self) -> f64 {
+        if self.uses_timer() {
+            let clock = TIMER_CLOCKS[(self.timer_control & 0x03) as usize] as f64;
+            clock / (256 - self.timer_modulo as u32) as f64
+        } else {
+            crate::TICKS_PER_SECOND as f64 / VBLANK_PERIOD as f64
+        }
+    }
+}
+
+/// Memory map of the player, ROM banks switched on writes to $2000-$3FFF
+#[derive(Clone, Debug)]
+struct GbsBus {
+    // - $0000..=$3FFF (Driver and bank 0)
+    // - $4000..=$7FFF (Switchable bank)
+    rom: Vec<u8>,
+    rom_bank: usize,
+
+    // - $A000..=$BFFF (Cartridge RAM)
+    // - $C000..=$DFFF (Internal RAM, echoed at $E000..=$FDFF)
+    // - $FF80..=$FFFE (Zero Page)
+    ram: Box<[u8; 0x4000 + 127]>,
+
+    timer: Timer,
+    spu: Spu,
+}
+
+impl MemoryBus for GbsBus {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom[addr as usize],
+            0x4000..=0x7FFF => {
+                let index = self.rom_bank * ROM_BANK_SIZE + (addr - 0x4000) as usize;
+                self.rom.get(index).copied().unwrap_or(0xFF)
+            }
+            0xA000..=0xDFFF => self.ram[(addr - 0xA000) as usize],
+            0xE000..=0xFDFF => self.ram[(addr - 0xC000) as usize],
+            0xFF04..=0xFF07 => self.timer.read(addr),
+            0xFF10..=0xFF3F => self.spu.read(addr),
+            0xFF80..=0xFFFE => self.ram[0x4000 + (addr - 0xFF80) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x2000..=0x3FFF => self.rom_bank = data.max(1) as usize,
+            0xA000..=0xDFFF => self.ram[(addr - 0xA000) as usize] = data,
+            0xE000..=0xFDFF => self.ram[(addr - 0xC000) as usize] = data,
+            0xFF04..=0xFF07 => self.timer.write(addr, data),
+            0xFF10..=0xFF3F => self.spu.write(addr, data),
+            0xFF80..=0xFFFE => self.ram[0x4000 + (addr - 0xFF80) as usize] = data,
+            _ => { }
+        }
+    }
+}
+
+/// GBS rip running on the CPU, timer and SPU, a song at a time
+#[derive(Clone, Debug)]
+pub struct GbsPlayer {
+    header: GbsHeader,
+    song: u8,
+
+    // Ticks into the audio frame, and since the last VBlank
+    ticks: u64,
+    vblank_ticks: u64,
+
+    cpu: Cpu,
+    bus: GbsBus,
+}
+
+impl GbsPlayer {
+    /// Parse the header and map the rip, ready to play its first song
+    pub fn load(data: Vec<u8>) -> Result<Self, GbsError> {
+        let header = GbsHeader::parse(&data)?;
+        if header.timer_control & 0x80 != 0 {
+            warn!("GBS asks for CGB double speed, playing at normal speed");
+        }
+
+        // Rip at the load address, the driver below it
+        let mut rom = vec![0; header.load_addr as usize];
+        rom.extend_from_slice(&data[HEADER_SIZE..]);
+        rom.resize(rom.len().div_ceil(ROM_BANK_SIZE).max(2) * ROM_BANK_SIZE, 0xFF);
+        install_driver(&mut rom, &header);
+
+        let song = header.first_song.saturating_sub(1);
+        let mut player = Self {
+            header,
+            song: 0,
+            ticks: 0,
+            vblank_ticks: 0,
+            cpu: Cpu::default(),
+            bus: GbsBus {
+                rom,
+                rom_bank: 1,
+                ram: Box::new([0; 0x4000 + 127]),
+                timer: Timer::default(),
+                spu: Spu::default(),
+            },
+        };
+        player.start_song(song);
+        Ok(player)
+    }
+
+    pub fn header(&self) -> &GbsHeader {
+        &self.header
+    }
+
+    /// Song playing, 0-based
+    pub fn song(&self) -> u8 {
+        self.song
+    }
+
+    pub fn spu(&self) -> &Spu { &self.bus.spu }
+    pub fn spu_mut(&mut self) -> &mut Spu { &mut self.bus.spu }
+
+    /// Start a song (0-based) over from cleared memory
+    pub fn start_song(&mut self, song: u8) {
+        self.song = song % self.header.song_count.max(1);
+
+        self.bus.ram.iter_mut().for_each(|byte| *byte = 0);
+        self.bus.rom_bank = 1;
+        self.bus.timer = Timer::default();
+
+        // Power cycled rather than replaced, the host rate, mutes and solo stay. Back on with the
+        // master volume and panning left by the boot rom, without its chime.
+        for &(addr, data) in &[(0xFF26, 0x00), (0xFF26, 0x80), (0xFF24, 0x77), (0xFF25, 0xF3)] {
+            self.bus.spu.write(addr, data);
+        }
+
+        if self.header.uses_timer() {
+            self.bus.timer.set_modulo(self.header.timer_modulo);
+            self.bus.timer.set_counter(self.header.timer_modulo);
+            self.bus.timer.set_control(self.header.timer_control & 0x07);
+        } else {
+            self.bus.timer.set_control(0x00);
+        }
+
+        self.cpu = Cpu::default();
+        self.cpu.regs.set_a(self.song);
+        self.cpu.regs.set_sp(self.header.stack_pointer);
+        self.cpu.regs.set_pc(DRIVER_ADDR);
+        self.vblank_ticks = 0;
+    }
+
+    /// Start the song before the current one, the last one before the first
+    pub fn start_previous_song(&mut self) {
+        let song_count = self.header.song_count.max(1) as u16;
+        self.start_song(((self.song as u16 + song_count - 1) % song_count) as u8);
+    }
+
+    /// Start the song after the current one, the first one after the last
+    pub fn start_next_song(&mut self) {
+        let song_count = self.header.song_count.max(1) as u16;
+        self.start_song(((self.song as u16 + 1) % song_count) as u8);
+    }
+
+    /// Run a frame worth of ticks, handing its audio to the sink
+    pub fn run_next_frame(&mut self, audio: &mut dyn AudioSink) {
+        while self.ticks < TICKS_PER_FRAME {
+            self.step();
+        }
+        self.ticks -= TICKS_PER_FRAME;
+        self.bus.spu.end_frame(audio);
+    }
+
+    fn step(&mut self) {
+        let ticks = self.cpu.cycle(&mut self.bus);
+        self.ticks += ticks;
+
+        self.bus.timer.step(ticks);
+        self.bus.spu.step(ticks);
+
+        if self.bus.timer.frame_sequencer_clocked() {
+            self.bus.spu.step_frame_sequencer();
+        }
+
+        if self.bus.timer.overflow_interrupt_requested() {
+            self.cpu.request_interrupt(Interrupt::TIMER);
+        }
+
+        self.vblank_ticks += ticks;
+        if self.vblank_ticks >= VBLANK_PERIOD {
+            self.vblank_ticks -= VBLANK_PERIOD;
+            self.cpu.request_interrupt(Interrupt::VBLANK);
+        }
+    }
+}
+
+/// Memory as seen by the CPU, including the interrupt registers
+impl MemoryBus for GbsPlayer {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF0F | 0xFFFF => self.cpu.read(addr),
+            _ => self.bus.read(addr),
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0xFF0F | 0xFFFF => self.cpu.write(addr, data),
+            _ => self.bus.write(addr, data),
+        }
+    }
+}
+
+/// RST vectors jumping to the rip, interrupt vectors calling the play routine, and the entry point
+///
+/// $0100 CALL init
+///       LD A, IE
+///       LDH ($FF), A
+///       EI
+/// loop: HALT
+///       JR loop
+fn install_driver(rom: &mut [u8], header: &GbsHeader) {
+    let [init_lo, init_hi] = header.init_addr.to_le_bytes();
+    let [play_lo, play_hi] = header.play_addr.to_le_bytes();
+
+    // RST n jumps to the load address + n
+    for rst in (0x00..=0x38).step_by(8) {
+        let [lo, hi] = (header.load_addr + rst).to_le_bytes();
+        rom[rst as usize..rst as usize + 3].copy_from_slice(&[0xC3, lo, hi]);
+    }
+
+    // CALL play; RETI
+    for &vector in &[VBLANK_VECTOR, TIMER_VECTOR] {
+        rom[vector as usize..vector as usize + 4].copy_from_slice(&[0xCD, play_lo, play_hi, 0xD9]);
+    }
+
+    let interrupt = if header.uses_timer() { Interrupt::TIMER } else { Interrupt::VBLANK };
+    let driver = [
+        0xCD, init_lo, init_hi,
+        0x3E, interrupt.bits(),
+        0xE0, 0xFF,
+        0xFB,
+        0x76,
+        0x18, 0xFD,
+    ];
+    rom[DRIVER_ADDR as usize..DRIVER_ADDR as usize + driver.len()].copy_from_slice(&driver);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::Null;
+
+    /// Init stores the song at $C000 and starts a note on channel 2, play counts its calls at $C001
+    fn gbs(timer_modulo: u8, timer_control: u8) -> Vec<u8> {
+        let mut data = vec![0; HEADER_SIZE];
+        data[0x00..0x04].copy_from_slice(b"GBS\x01");
+        data[0x04] = 3;
+        data[0x05] = 2;
+        data[0x06..0x08].copy_from_slice(&0x0400u16.to_le_bytes());
+        data[0x08..0x0A].copy_from_slice(&0x0400u16.to_le_bytes());
+        data[0x0A..0x0C].copy_from_slice(&0x0410u16.to_le_bytes());
+        data[0x0C..0x0E].copy_from_slice(&0xDFFFu16.to_le_bytes());
+        data[0x0E] = timer_modulo;
+        data[0x0F] = timer_control;
+        data[0x10..0x14].copy_from_slice(b"Test");
+        data[0x30..0x36].copy_from_slice(b"Author");
+
+        let mut code = vec![0; 0x20];
+        code[0x00..0x0B].copy_from_slice(&[
+            0xEA, 0x00, 0xC0,   // LD ($C000), A
+            0x3E, 0xF0,         // LD A, $F0
+            0xE0, 0x17,         // LDH (NR22), A
+            0x3E, 0x87,         // LD A, $87
+            0xE0, 0x19,         // LDH (NR24), A
+        ]);
+        code[0x0B] = 0xC9;      // RET
+        code[0x10..0x18].copy_from_slice(&[
+            0xFA, 0x01, 0xC0,   // LD A, ($C001)
+            0x3C,               // INC A
+            0xEA, 0x01, 0xC0,   // LD ($C001), A
+            0xC9,               // RET
+        ]);
+        data.extend_from_slice(&code);
+        data
+    }
+
+    #[test]
+    fn header_test() {
+        let header = GbsHeader::parse(&gbs(0, 0)).unwrap();
+        assert_eq!(3, header.song_count);
+        assert_eq!(2, header.first_song);
+        assert_eq!(0x0400, header.load_addr);
+        assert_eq!(0x0410, header.play_addr);
+        assert_eq!("Test", header.title);
+        assert_eq!("Author", header.author);
+        assert_eq!("", header.copyright);
+        assert!(!header.uses_timer());
+        assert!((header.play_rate() - 59.73).abs() < 0.01);
+
+        let header = GbsHeader::parse(&gbs(0xC0, 0x04)).unwrap();
+        assert!(header.uses_timer());
+        assert_eq!(64.0, header.play_rate());
+
+        assert_eq!(Err(GbsError::MissingHeader(3)), GbsHeader::parse(b"GBS"));
+        let mut data = gbs(0, 0);
+        data[0] = b'X';
+        assert_eq!(Err(GbsError::BadSignature), GbsHeader::parse(&data));
+        data = gbs(0, 0);
+        data[0x03] = 2;
+        assert_eq!(Err(GbsError::UnsupportedVersion(2)), GbsHeader::parse(&data));
+        data = gbs(0, 0);
+        data[0x07] = 0x01;
+        assert_eq!(Err(GbsError::InvalidLoadAddress(0x0100)), GbsHeader::parse(&data));
+    }
+
+    #[test]
+    fn vblank_test() {
+        // A second of VBlanks, starting with the first song from the header
+        let mut player = GbsPlayer::load(gbs(0, 0)).unwrap();
+        for _ in 0..60 {
+            player.run_next_frame(&mut Null);
+        }
+        assert_eq!(1, player.song());
+        assert_eq!(1, player.read(0xC000));
+        assert_eq!(59, player.read(0xC001));
+        assert_eq!(0xF2, player.read(0xFF26));
+
+        // Starting over clears the memory
+        player.start_song(2);
+        assert_eq!(0, player.read(0xC001));
+        player.run_next_frame(&mut Null);
+        assert_eq!(2, player.read(0xC000));
+        player.start_song(3);
+        assert_eq!(0, player.song());
+    }
+
+    #[test]
+    fn song_switch_test() {
+        // Wrapping around either way, past the u8 range on a rip of 255 songs
+        let mut data = gbs(0, 0);
+        data[0x04] = 255;
+        let mut player = GbsPlayer::load(data).unwrap();
+        assert_eq!(1, player.song());
+        player.start_previous_song();
+        assert_eq!(0, player.song());
+        player.start_previous_song();
+        assert_eq!(254, player.song());
+        player.start_next_song();
+        assert_eq!(0, player.song());
+        player.start_next_song();
+        assert_eq!(1, player.song());
+    }
+
+    #[test]
+    fn timer_test() {
+        // 4096Hz timer overflowing every 64 clocks
+        let mut player = GbsPlayer::load(gbs(0xC0, 0x04)).unwrap();
+        for _ in 0..60 {
+            player.run_next_frame(&mut Null);
+        }
+        assert!((63..=64).contains(&player.read(0xC001)), "{}", player.read(0xC001));
+    }
+
+    #[test]
+    fn bank_test() {
+        let mut data = gbs(0, 0);
+        data.resize(HEADER_SIZE + 0xC000 - 0x0400, 0x00);
+        data.extend_from_slice(&[0x33; ROM_BANK_SIZE]);
+        let mut player = GbsPlayer::load(data).unwrap();
+
+        // Driver below the rip
+        assert_eq!(0xC3, player.read(0x0038));
+        assert_eq!(0xCD, player.read(DRIVER_ADDR));
+        assert_eq!(0xEA, player.read(0x0400));
+
+        assert_eq!(0x00, player.read(0x4000));
+        player.write(0x2000, 3);
+        assert_eq!(0x33, player.read(0x4000));
+        player.write(0x2000, 4);
+        assert_eq!(0xFF, player.read(0x4000));
+    }
+}
@@ -25,6 +25,7 @@ bitflags! {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct Joypad {
     p1: P1,
     keys: Keys,
@@ -0,0 +1,30 @@
+#[macro_use] extern crate bitflags;
+#[macro_use] extern crate log;
+
+pub mod bios;
+pub mod cartridge;
+pub mod cpu;
+pub mod gbs;
+pub mod mmu;
+pub mod midi;
+pub mod model;
+pub mod png;
+pub mod ppu;
+pub mod serial;
+pub mod spu;
+pub mod timer;
+pub mod wav;
+pub mod joypad;
+pub mod gb;
+pub mod frontend;
+
+#[cfg(test)]
+mod test_rom;
+
+pub trait MemoryBus {
+    fn read(&self, addr: u16) -> u8;
+    fn write(&mut self, addr: u16, data: u8);
+}
+
+pub const TICKS_PER_SECOND: u64 = 4_194_304;
+pub const TICKS_PER_FRAME:  u64 = TICKS_PER_SECOND / 60;
@@ -1,84 +1,548 @@
-#[macro_use] extern crate bitflags;
-#[macro_use] extern crate packed_struct;
-extern crate pretty_env_logger;
 #[macro_use] extern crate log;
-extern crate sdl2;
-
-pub mod bios;
-pub mod cpu;
-pub mod mmu;
-pub mod ppu;
-pub mod spu;
-pub mod timer;
-pub mod joypad;
-pub mod gb;
-
-use sdl2::event::Event;
+
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
 
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-pub trait MemoryBus {
-    fn read(&self, addr: u16) -> u8;
-    fn write(&mut self, addr: u16, data: u8);
+use kiwi_gb::bios;
+use kiwi_gb::gb::GameBoy;
+use kiwi_gb::gbs::{self, GbsPlayer};
+use kiwi_gb::frontend::extractor::Extractor;
+use kiwi_gb::frontend::recorder::{Recorder, STEM_NAMES};
+use kiwi_gb::frontend::scope::Scope;
+use kiwi_gb::frontend::sdl::{SdlAudio, SdlInput, SdlScope, SdlVideo};
+use kiwi_gb::cartridge::save;
+use kiwi_gb::mmu::BOOT_ROM_SIZE;
+use kiwi_gb::model::Model;
+use kiwi_gb::serial::link::LinkCable;
+use kiwi_gb::serial::printer::Printer;
+use kiwi_gb::spu::{Spu, DEFAULT_SAMPLE_RATE};
+
+const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Starts and stops recording the audio
+const RECORD_KEY: Keycode = Keycode::F9;
+
+/// Starts and stops extracting the notes to a MIDI file
+const MIDI_KEY: Keycode = Keycode::F11;
+
+/// Mute each channel
+const MUTE_KEYS: [Keycode; 4] = [Keycode::F1, Keycode::F2, Keycode::F3, Keycode::F4];
+
+/// Play each channel alone
+const SOLO_KEYS: [Keycode; 4] = [Keycode::F5, Keycode::F6, Keycode::F7, Keycode::F8];
+
+/// Open and close the oscilloscope
+const SCOPE_KEY: Keycode = Keycode::F10;
+
+/// Previous and next song of a GBS file
+const PREVIOUS_TRACK_KEY: Keycode = Keycode::Left;
+const NEXT_TRACK_KEY: Keycode = Keycode::Right;
+
+/// Samples across the oscilloscope
+const SCOPE_LENGTH: usize = 2048;
+
+type Audio = Recorder<Extractor<Scope<SdlAudio>>>;
+
+const USAGE: &str = "usage: kiwi-gb [--model dmg0|dmg|mgb|sgb|sgb2|cgb] [--boot | --boot-rom FILE] [--save-flush SECONDS] [--rtc-sync] [--sample-rate HZ] [--low-pass HZ] [--record FILE] [--stems] [--midi FILE] [--scope] [--track N] [--link-listen ADDR | --link-connect ADDR | --printer DIR] ROM|GBS";
+
+enum SerialOption {
+    Listen(String),
+    Connect(String),
+    Printer(PathBuf),
+}
+
+enum Boot {
+    Skip,
+    Internal,
+    External(PathBuf),
 }
 
-pub const TICKS_PER_SECOND: u64 = 4_194_304;
-pub const TICKS_PER_FRAME:  u64 = TICKS_PER_SECOND / 60;
+struct Options {
+    rom_path: PathBuf,
 
-pub const BUTTON_A:      Keycode = Keycode::Space;
-pub const BUTTON_B:      Keycode = Keycode::LShift;
-pub const BUTTON_UP:     Keycode = Keycode::Up;
-pub const BUTTON_DOWN:   Keycode = Keycode::Down;
-pub const BUTTON_LEFT:   Keycode = Keycode::Left;
-pub const BUTTON_RIGHT:  Keycode = Keycode::Right;
-pub const BUTTON_START:  Keycode = Keycode::Return;
-pub const BUTTON_SELECT: Keycode = Keycode::Backspace;
+    // Hardware to emulate, picks the post-boot state and the screen colors
+    model: Model,
 
-use gb::GameBoy;
+    // Run a boot rom instead of starting from the post-boot state
+    boot: Boot,
 
-const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+    // Write the save file every N seconds, when the cartridge RAM changed
+    save_flush: Option<Duration>,
+
+    // Advance the cartridge clock by the host time elapsed since the save
+    rtc_sync: bool,
+
+    // Rate the audio is resampled to, the device may settle on another one
+    sample_rate: u32,
+
+    // Cut the audio above a frequency, the way a speaker would
+    low_pass: Option<u32>,
+
+    // Record the audio as WAV from the start, the record key toggles it to ROM-NNN.wav files,
+    // with a file per channel along the mix with stems
+    record: Option<PathBuf>,
+    stems: bool,
+
+    // Extract the notes of each channel to a MIDI file from the start, the MIDI key toggles it to
+    // ROM-NNN.mid files
+    midi: Option<PathBuf>,
+
+    // Oscilloscope window of each channel and the mix opened from the start
+    scope: bool,
+
+    // First song played from a GBS file, 1-based
+    track: Option<u8>,
+
+    // Link cable to another instance, over TCP (HOST:PORT) or a Unix socket (unix:PATH),
+    // or a printer writing its pages to a directory
+    serial: Option<SerialOption>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut rom_path = None;
+        let mut model = Model::default();
+        let mut boot = Boot::Skip;
+        let mut save_flush = None;
+        let mut rtc_sync = false;
+        let mut sample_rate = DEFAULT_SAMPLE_RATE;
+        let mut low_pass = None;
+        let mut record = None;
+        let mut stems = false;
+        let mut midi = None;
+        let mut scope = false;
+        let mut track = None;
+        let mut serial = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--model" => {
+                    model = args.next().ok_or("--model expects a model name")?.parse()?;
+                }
+                "--boot" => boot = Boot::Internal,
+                "--boot-rom" => {
+                    let path = args.next().ok_or("--boot-rom expects a file")?;
+                    boot = Boot::External(PathBuf::from(path));
+                }
+                "--save-flush" => {
+                    let seconds = args.next()
+                        .and_then(|s| s.parse::<u64>().ok())
+                        .ok_or("--save-flush expects a number of seconds")?;
+                    save_flush = Some(Duration::from_secs(seconds));
+                }
+                "--rtc-sync" => rtc_sync = true,
+                "--sample-rate" => {
+                    sample_rate = args.next()
+                        .and_then(|s| s.parse::<u32>().ok())
+                        .filter(|&rate| rate > 0)
+                        .ok_or("--sample-rate expects a rate in Hz")?;
+                }
+                "--low-pass" => {
+                    low_pass = Some(args.next()
+                        .and_then(|s| s.parse::<u32>().ok())
+                        .filter(|&cutoff| cutoff > 0)
+                        .ok_or("--low-pass expects a cutoff in Hz")?);
+                }
+                "--record" => record = Some(PathBuf::from(args.next().ok_or("--record expects a file")?)),
+                "--stems" => stems = true,
+                "--midi" => midi = Some(PathBuf::from(args.next().ok_or("--midi expects a file")?)),
+                "--scope" => scope = true,
+                "--track" => {
+                    track = Some(args.next()
+                        .and_then(|s| s.parse::<u8>().ok())
+                        .filter(|&track| track > 0)
+                        .ok_or("--track expects a song number from 1")?);
+                }
+                "--link-listen" => {
+                    serial = Some(SerialOption::Listen(args.next().ok_or("--link-listen expects an address")?));
+                }
+                "--link-connect" => {
+                    serial = Some(SerialOption::Connect(args.next().ok_or("--link-connect expects an address")?));
+                }
+                "--printer" => {
+                    let dir = args.next().ok_or("--printer expects a directory")?;
+                    serial = Some(SerialOption::Printer(PathBuf::from(dir)));
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ => rom_path = Some(PathBuf::from(arg)),
+            }
+        }
+
+        Ok(Self {
+            rom_path: rom_path.ok_or("missing rom path")?,
+            model,
+            boot,
+            save_flush,
+            rtc_sync,
+            sample_rate,
+            low_pass,
+            record,
+            stems,
+            midi,
+            scope,
+            track,
+            serial,
+        })
+    }
+}
 
 fn main() {
     pretty_env_logger::init();
 
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let rom = match std::fs::read(&options.rom_path) {
+        Ok(rom) => rom,
+        Err(err) => {
+            error!("Failed to read {}: {}", options.rom_path.display(), err);
+            std::process::exit(1);
+        }
+    };
+    if gbs::is_gbs(&rom) {
+        play_gbs(&options, rom);
+        return;
+    }
+
+    let boot_rom: Option<Box<[u8; BOOT_ROM_SIZE]>> = match &options.boot {
+        Boot::Skip => None,
+        Boot::Internal => Some(Box::new(bios::DMG_BIOS)),
+        Boot::External(path) => {
+            match bios::read_boot_rom(path) {
+                Ok(boot_rom) => Some(boot_rom),
+                Err(err) => {
+                    error!("Failed to load boot rom {}: {}", path.display(), err);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    let mut gameboy = GameBoy::new(options.model, boot_rom);
+    if let Err(err) = gameboy.load_rom(rom) {
+        error!("Failed to load {}: {}", options.rom_path.display(), err);
+        std::process::exit(1);
+    }
+    gameboy.mmu_mut().spu.set_low_pass(options.low_pass);
+    gameboy.set_rumble_callback(|on| debug!("Rumble {}", if on { "on" } else { "off" }));
+
+    let save_path = save::save_path(&options.rom_path);
+    if gameboy.has_battery() {
+        match gameboy.load_save(&save_path, options.rtc_sync) {
+            Ok(true) => info!("Loaded {}", save_path.display()),
+            Ok(false) => { }
+            Err(err) => warn!("Failed to load {}: {}", save_path.display(), err),
+        }
+    }
+    let mut save_flush_timestamp = Instant::now();
+
+    let cable = match &options.serial {
+        Some(SerialOption::Listen(addr)) => {
+            info!("Waiting for the link cable on {}", addr);
+            Some(LinkCable::listen(addr))
+        }
+        Some(SerialOption::Connect(addr)) => Some(LinkCable::connect(addr)),
+        Some(SerialOption::Printer(dir)) => {
+            gameboy.mmu_mut().serial.connect(Box::new(Printer::with_output_dir(dir.clone())));
+            None
+        }
+        None => None,
+    };
+    match cable {
+        Some(Ok(cable)) => {
+            info!("Link cable connected");
+            gameboy.mmu_mut().serial.connect(Box::new(cable));
+        }
+        Some(Err(err)) => {
+            error!("Failed to connect the link cable: {}", err);
+            std::process::exit(1);
+        }
+        None => { }
+    }
+
     let sdl_context = sdl2::init().unwrap();
-    let mut gameboy = GameBoy::new(&sdl_context);
+    let mut video = SdlVideo::new(&sdl_context);
+    let mut audio = Recorder::new(Extractor::new(Scope::new(SdlAudio::new(&sdl_context, options.sample_rate), SCOPE_LENGTH)));
+    if let Some(path) = &options.record {
+        start_recording(&mut audio, path, options.stems);
+    }
+    if let Some(path) = &options.midi {
+        start_extracting(&mut audio, path);
+    }
+    let mut input = SdlInput::new(video.window_id());
 
-    let args: Vec<String> = std::env::args().collect();
-    let rom = std::fs::read(&args[1]).unwrap();
-    gameboy.load_rom(&rom);
+    let mut scope = None;
+    if options.scope {
+        scope = Some(SdlScope::new(&sdl_context));
+        audio.inner_mut().inner_mut().set_enabled(true);
+    }
 
-    let mut frame_begin_timestamp = Instant::now();
-    let mut frame_overslept_duration = Duration::from_nanos(0);
+    let mut pacer = FramePacer::new();
+    let mut event_pump = sdl_context.event_pump().unwrap();
+    'gameloop: loop {
+        gameboy.run_next_frame(&mut input, &mut video, &mut audio);
+
+        if let Some(scope) = &mut scope {
+            let spu = &gameboy.mmu().spu;
+            scope.draw(audio.inner().inner(), [spu.is_audible(0), spu.is_audible(1), spu.is_audible(2), spu.is_audible(3)]);
+        }
 
+        for event in event_pump.poll_iter() {
+            input.handle_event(&event);
+            match event {
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } | Event::Quit { .. } => break 'gameloop,
+                Event::Window { win_event: WindowEvent::Close, window_id, .. } => {
+                    if scope.as_ref().is_some_and(|scope| scope.window_id() == window_id) {
+                        scope = None;
+                        audio.inner_mut().inner_mut().set_enabled(false);
+                    } else {
+                        break 'gameloop;
+                    }
+                }
+                Event::KeyDown { keycode: Some(SCOPE_KEY), repeat: false, .. } => {
+                    scope = match scope {
+                        Some(_) => None,
+                        None => Some(SdlScope::new(&sdl_context)),
+                    };
+                    audio.inner_mut().inner_mut().set_enabled(scope.is_some());
+                }
+                Event::KeyDown { keycode: Some(keycode), repeat: false, .. } if is_channel_key(keycode) => {
+                    toggle_channel(&mut gameboy.mmu_mut().spu, keycode);
+                }
+                Event::KeyDown { keycode: Some(RECORD_KEY), repeat: false, .. } => {
+                    if audio.is_recording() {
+                        stop_recording(&mut audio);
+                    } else {
+                        start_recording(&mut audio, &next_recording_path(&options.rom_path, "wav"), options.stems);
+                    }
+                }
+                Event::KeyDown { keycode: Some(MIDI_KEY), repeat: false, .. } => {
+                    if audio.inner().is_extracting() {
+                        stop_extracting(&mut audio);
+                    } else {
+                        start_extracting(&mut audio, &next_recording_path(&options.rom_path, "mid"));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(save_flush) = options.save_flush {
+            if gameboy.has_battery() && gameboy.is_save_dirty() && save_flush_timestamp.elapsed() >= save_flush {
+                if let Err(err) = gameboy.write_save(&save_path) {
+                    warn!("Failed to write {}: {}", save_path.display(), err);
+                }
+                save_flush_timestamp = Instant::now();
+            }
+        }
+
+        pacer.wait();
+    }
+
+    if gameboy.has_battery() {
+        if let Err(err) = gameboy.write_save(&save_path) {
+            error!("Failed to write {}: {}", save_path.display(), err);
+        }
+    }
+
+    // Paper still in the printer
+    if let Some(printer) = gameboy.mmu_mut().serial.device_mut().as_any_mut().downcast_mut::<Printer>() {
+        printer.cut();
+    }
+
+    stop_recording(&mut audio);
+    stop_extracting(&mut audio);
+}
+
+/// Play a GBS file in the oscilloscope window, the arrow keys going through its songs
+fn play_gbs(options: &Options, data: Vec<u8>) {
+    if !matches!(options.boot, Boot::Skip) || options.serial.is_some() || options.save_flush.is_some() || options.rtc_sync {
+        eprintln!("--boot, --boot-rom, --link-listen, --link-connect, --printer, --save-flush and --rtc-sync need a rom\n{}", USAGE);
+        std::process::exit(2);
+    }
+
+    let mut player = match GbsPlayer::load(data) {
+        Ok(player) => player,
+        Err(err) => {
+            error!("Failed to load {}: {}", options.rom_path.display(), err);
+            std::process::exit(1);
+        }
+    };
+    if let Some(track) = options.track {
+        if track > player.header().song_count {
+            error!("No track {}, {} has {}", track, options.rom_path.display(), player.header().song_count);
+            std::process::exit(1);
+        }
+        player.start_song(track - 1);
+    }
+    player.spu_mut().set_charge_factor(options.model.charge_factor());
+    player.spu_mut().set_low_pass(options.low_pass);
+    let song_count = player.header().song_count.max(1);
+
+    let sdl_context = sdl2::init().unwrap();
+    let mut scope = SdlScope::new(&sdl_context);
+    let mut audio = Recorder::new(Extractor::new(Scope::new(SdlAudio::new(&sdl_context, options.sample_rate), SCOPE_LENGTH)));
+    audio.inner_mut().inner_mut().set_enabled(true);
+    if let Some(path) = &options.record {
+        start_recording(&mut audio, path, options.stems);
+    }
+    if let Some(path) = &options.midi {
+        start_extracting(&mut audio, path);
+    }
+
+    let mut song = None;
+    let mut pacer = FramePacer::new();
     let mut event_pump = sdl_context.event_pump().unwrap();
     'gameloop: loop {
-        gameboy.run_next_frame();
+        if song != Some(player.song()) {
+            song = Some(player.song());
+            let header = player.header();
+            info!("{} - {}, track {}/{}", header.title, header.author, player.song() + 1, song_count);
+            scope.set_title(&format!("KiwiGB - {} - {}/{}", header.title, player.song() + 1, song_count));
+        }
+
+        player.run_next_frame(&mut audio);
+
+        let spu = player.spu();
+        scope.draw(audio.inner().inner(), [spu.is_audible(0), spu.is_audible(1), spu.is_audible(2), spu.is_audible(3)]);
 
         for event in event_pump.poll_iter() {
-            gameboy.handle_event(&event);
             match event {
                 Event::KeyDown { keycode: Some(Keycode::Escape), .. } | Event::Quit { .. } => break 'gameloop,
+                Event::KeyDown { keycode: Some(PREVIOUS_TRACK_KEY), .. } => {
+                    player.start_previous_song();
+                }
+                Event::KeyDown { keycode: Some(NEXT_TRACK_KEY), .. } => {
+                    player.start_next_song();
+                }
+                Event::KeyDown { keycode: Some(RECORD_KEY), repeat: false, .. } => {
+                    if audio.is_recording() {
+                        stop_recording(&mut audio);
+                    } else {
+                        start_recording(&mut audio, &next_recording_path(&options.rom_path, "wav"), options.stems);
+                    }
+                }
+                Event::KeyDown { keycode: Some(MIDI_KEY), repeat: false, .. } => {
+                    if audio.inner().is_extracting() {
+                        stop_extracting(&mut audio);
+                    } else {
+                        start_extracting(&mut audio, &next_recording_path(&options.rom_path, "mid"));
+                    }
+                }
+                Event::KeyDown { keycode: Some(keycode), repeat: false, .. } if is_channel_key(keycode) => {
+                    toggle_channel(player.spu_mut(), keycode);
+                }
                 _ => {}
             }
         }
 
+        pacer.wait();
+    }
+
+    stop_recording(&mut audio);
+    stop_extracting(&mut audio);
+}
+
+/// Sleeps out the rest of each frame, making up for oversleeping on the next one
+struct FramePacer {
+    frame_begin_timestamp: Instant,
+    frame_overslept_duration: Duration,
+}
+
+impl FramePacer {
+    fn new() -> Self {
+        Self {
+            frame_begin_timestamp: Instant::now(),
+            frame_overslept_duration: Duration::from_nanos(0),
+        }
+    }
+
+    fn wait(&mut self) {
         let frame_complete_timestamp = Instant::now();
-        let frame_busy_duration = frame_complete_timestamp - frame_begin_timestamp;
+        let frame_busy_duration = frame_complete_timestamp - self.frame_begin_timestamp;
 
-        match FRAME_DURATION.checked_sub(frame_busy_duration + frame_overslept_duration) {
+        match FRAME_DURATION.checked_sub(frame_busy_duration + self.frame_overslept_duration) {
             Some(frame_wait_duration) => {
                 std::thread::sleep(frame_wait_duration);
-                frame_begin_timestamp = Instant::now();
-                frame_overslept_duration =
-                    (frame_begin_timestamp - frame_complete_timestamp) - frame_wait_duration;
+                self.frame_begin_timestamp = Instant::now();
+                self.frame_overslept_duration =
+                    (self.frame_begin_timestamp - frame_complete_timestamp) - frame_wait_duration;
             }
             None => {
-                warn!("Frame overrun {:?} {:?}", frame_busy_duration, frame_overslept_duration);
-                frame_begin_timestamp = frame_complete_timestamp;
-                frame_overslept_duration = Duration::from_nanos(0);
+                warn!("Frame overrun {:?} {:?}", frame_busy_duration, self.frame_overslept_duration);
+                self.frame_begin_timestamp = frame_complete_timestamp;
+                self.frame_overslept_duration = Duration::from_nanos(0);
             }
         }
     }
 }
+
+fn is_channel_key(keycode: Keycode) -> bool {
+    MUTE_KEYS.contains(&keycode) || SOLO_KEYS.contains(&keycode)
+}
+
+/// Mute a channel, or play it alone, pressed again to undo it
+fn toggle_channel(spu: &mut Spu, keycode: Keycode) {
+    if let Some(channel) = MUTE_KEYS.iter().position(|&key| key == keycode) {
+        let muted = !spu.is_muted(channel);
+        spu.set_muted(channel, muted);
+        info!("{} {}", STEM_NAMES[channel], if muted { "muted" } else { "unmuted" });
+    } else if let Some(channel) = SOLO_KEYS.iter().position(|&key| key == keycode) {
+        if spu.solo() == Some(channel) {
+            spu.set_solo(None);
+            info!("{} solo off", STEM_NAMES[channel]);
+        } else {
+            spu.set_solo(Some(channel));
+            info!("{} solo", STEM_NAMES[channel]);
+        }
+    }
+}
+
+fn start_recording(audio: &mut Audio, path: &Path, stems: bool) {
+    match audio.start(path, stems) {
+        Ok(()) => info!("Recording to {}", path.display()),
+        Err(err) => error!("Failed to record to {}: {}", path.display(), err),
+    }
+}
+
+fn stop_recording(audio: &mut Audio) {
+    if let Some(path) = audio.path().map(Path::to_path_buf) {
+        match audio.stop() {
+            Ok(()) => info!("Recorded {}", path.display()),
+            Err(err) => error!("Failed to write {}: {}", path.display(), err),
+        }
+    }
+}
+
+fn start_extracting(audio: &mut Audio, path: &Path) {
+    match audio.inner_mut().start(path) {
+        Ok(()) => info!("Extracting notes to {}", path.display()),
+        Err(err) => error!("Failed to extract notes to {}: {}", path.display(), err),
+    }
+}
+
+fn stop_extracting(audio: &mut Audio) {
+    if let Some(path) = audio.inner().path().map(Path::to_path_buf) {
+        match audio.inner_mut().stop() {
+            Ok(()) => info!("Extracted {}", path.display()),
+            Err(err) => error!("Failed to write {}: {}", path.display(), err),
+        }
+    }
+}
+
+/// First free ROM-NNN.extension next to the rom
+fn next_recording_path(rom_path: &Path, extension: &str) -> PathBuf {
+    let stem = rom_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    (1..)
+        .map(|n| rom_path.with_file_name(format!("{}-{:03}.{}", stem, n, extension)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
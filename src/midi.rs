@@ -0,0 +1,153 @@
+//! Minimal Standard MIDI File writer, format 1 with a track per part
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Ticks per quarter note
+pub const DIVISION: u16 = 480;
+
+/// Microseconds per quarter note, 120 bpm
+pub const TEMPO: u32 = 500_000;
+
+/// MIDI ticks per second at the default tempo
+pub const TICKS_PER_SECOND: u64 = DIVISION as u64 * 1_000_000 / TEMPO as u64;
+
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+const META: u8 = 0xFF;
+const META_TRACK_NAME: u8 = 0x03;
+const META_END_OF_TRACK: u8 = 0x2F;
+const META_TEMPO: u8 = 0x51;
+
+/// Events at absolute times, put in order when written
+#[derive(Clone, Debug, Default)]
+pub struct MidiTrack {
+    events: Vec<(u32, Vec<u8>)>,
+}
+
+impl MidiTrack {
+    pub fn new(name: &str) -> Self {
+        let mut track = Self::default();
+        track.meta(0, META_TRACK_NAME, name.as_bytes());
+        track
+    }
+
+    pub fn set_tempo(&mut self, time: u32, microseconds_per_quarter: u32) {
+        self.meta(time, META_TEMPO, &microseconds_per_quarter.to_be_bytes()[1..]);
+    }
+
+    pub fn note_on(&mut self, time: u32, channel: u8, key: u8, velocity: u8) {
+        self.events.push((time, vec![NOTE_ON | channel, key & 0x7F, velocity & 0x7F]));
+    }
+
+    pub fn note_off(&mut self, time: u32, channel: u8, key: u8) {
+        self.events.push((time, vec![NOTE_OFF | channel, key & 0x7F, 0x40]));
+    }
+
+    fn meta(&mut self, time: u32, kind: u8, data: &[u8]) {
+        let mut event = vec![META, kind];
+        write_variable_length(&mut event, data.len() as u32);
+        event.extend_from_slice(data);
+        self.events.push((time, event));
+    }
+
+    /// Events with their delta times, ending the track after the last one
+    fn encode(&self) -> Vec<u8> {
+        let mut events = self.events.clone();
+        events.sort_by_key(|&(time, _)| time);
+
+        let mut data = Vec::new();
+        let mut last = 0;
+        for (time, event) in &events {
+            write_variable_length(&mut data, time - last);
+            data.extend_from_slice(event);
+            last = *time;
+        }
+        data.extend_from_slice(&[0x00, META, META_END_OF_TRACK, 0x00]);
+        data
+    }
+}
+
+/// Tracks played together, DIVISION ticks per quarter note
+#[derive(Clone, Debug, Default)]
+pub struct MidiFile {
+    pub tracks: Vec<MidiTrack>,
+}
+
+impl MidiFile {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b"MThd")?;
+        writer.write_all(&6u32.to_be_bytes())?;
+        writer.write_all(&1u16.to_be_bytes())?;
+        writer.write_all(&(self.tracks.len() as u16).to_be_bytes())?;
+        writer.write_all(&DIVISION.to_be_bytes())?;
+
+        for track in &self.tracks {
+            let data = track.encode();
+            writer.write_all(b"MTrk")?;
+            writer.write_all(&(data.len() as u32).to_be_bytes())?;
+            writer.write_all(&data)?;
+        }
+        Ok(())
+    }
+}
+
+/// 7 bits a byte, most significant first, the high bit set on all but the last one
+fn write_variable_length(out: &mut Vec<u8>, value: u32) {
+    let mut bytes = vec![(value & 0x7F) as u8];
+    let mut value = value >> 7;
+    while value > 0 {
+        bytes.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    out.extend(bytes.iter().rev());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable_length(value: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_variable_length(&mut out, value);
+        out
+    }
+
+    #[test]
+    fn variable_length_test() {
+        assert_eq!(vec![0x00], variable_length(0));
+        assert_eq!(vec![0x7F], variable_length(0x7F));
+        assert_eq!(vec![0x81, 0x00], variable_length(0x80));
+        assert_eq!(vec![0xFF, 0x7F], variable_length(0x3FFF));
+        assert_eq!(vec![0x81, 0x80, 0x80, 0x00], variable_length(0x20_0000));
+    }
+
+    #[test]
+    fn file_test() {
+        let mut track = MidiTrack::new("A");
+        track.set_tempo(0, TEMPO);
+        track.note_off(480, 1, 60);
+        track.note_on(0, 1, 60, 100);
+
+        let mut data = Vec::new();
+        MidiFile { tracks: vec![track] }.write(&mut data).unwrap();
+
+        assert_eq!(b"MThd\x00\x00\x00\x06\x00\x01\x00\x01\x01\xE0", &data[..14]);
+        assert_eq!(b"MTrk", &data[14..18]);
+        assert_eq!(&[
+            0x00, 0xFF, 0x03, 0x01, b'A',
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20,
+            0x00, 0x91, 60, 100,
+            0x83, 0x60, 0x81, 60, 0x40,
+            0x00, 0xFF, 0x2F, 0x00,
+        ], &data[22..]);
+        assert_eq!(data.len() as u32 - 22, u32::from_be_bytes([data[18], data[19], data[20], data[21]]));
+    }
+}
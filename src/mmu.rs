@@ -1,21 +1,29 @@
 use crate::MemoryBus;
-use crate::cpu::Cpu;
+use crate::cartridge::Cartridge;
+use crate::cartridge::rom_only::RomOnly;
 use crate::ppu::Ppu;
+use crate::serial::Serial;
 use crate::spu::Spu;
 use crate::timer::Timer;
 use crate::joypad::Joypad;
 
-use std::ptr;
+pub const BOOT_ROM_SIZE: usize = 0x100;
 
-#[derive(Debug)]
+const BOOT_ROM_DISABLE_ADDR: u16 = 0xFF50;
+
+#[derive(Clone, Debug)]
 pub struct Mmu {
-    // Cartrige ROM
-    // - $0000..=$7FFFF (ROM)
-    pub cartridge_rom: Box<[u8; 0x8000]>,
+    // Boot ROM
+    // - $0000..=$00FF (mapped over the cartridge until $FF50 is written)
+    pub boot_rom: Option<Box<[u8; BOOT_ROM_SIZE]>>,
+
+    // Cartridge
+    // - $0000..=$7FFF (ROM)
+    // - $A000..=$BFFF (RAM)
+    pub cartridge: Box<dyn Cartridge>,
 
-    // Cartrige RAM
-    // - $A000..=$BFFFF (RAM)
-    pub cartridge_ram: Box<[u8; 0x2000]>,
+    // Set when battery backed RAM or the clock takes a write, until the save file is flushed
+    pub cartridge_ram_dirty: bool,
 
     // Random Access Memory
     // - $C000..=$DFFF (Internal RAM)
@@ -23,25 +31,38 @@ pub struct Mmu {
     // - $FF80..=$FFFE (Zero Page)
     pub ram: Box<[u8; 0x2000 + 127]>,
 
-    pub cpu: *mut Cpu,
-    pub ppu: *mut Ppu,
-    pub spu: *mut Spu,
-    pub timer: *mut Timer,
-    pub joypad: *mut Joypad,
+    // Hardware
+    // - $FF0F and $FFFF (IF, IE) are handled by the CPU
+    pub ppu: Ppu,
+    pub serial: Serial,
+    pub spu: Spu,
+    pub timer: Timer,
+    pub joypad: Joypad,
 }
 
 impl Default for Mmu {
     fn default() -> Self {
         Self {
-            cartridge_rom: Box::new([0; 0x8000]),
-            cartridge_ram: Box::new([0; 0x2000]),
+            boot_rom: None,
+            cartridge: Box::new(RomOnly::blank()),
+            cartridge_ram_dirty: false,
             ram: Box::new([0; 0x2000 + 127]),
 
-            cpu: ptr::null_mut(),
-            ppu: ptr::null_mut(),
-            spu: ptr::null_mut(),
-            timer: ptr::null_mut(),
-            joypad: ptr::null_mut(),
+            ppu: Ppu::default(),
+            serial: Serial::default(),
+            spu: Spu::default(),
+            timer: Timer::default(),
+            joypad: Joypad::default(),
+        }
+    }
+}
+
+impl Mmu {
+    /// Only the writes the cartridge took make the save dirty, when it has a battery
+    fn write_cartridge(&mut self, addr: u16, data: u8) {
+        self.cartridge.write(addr, data);
+        if self.cartridge.take_dirty() && self.cartridge.header().cartridge_type.battery {
+            self.cartridge_ram_dirty = true;
         }
     }
 }
@@ -49,11 +70,14 @@ impl Default for Mmu {
 impl MemoryBus for Mmu {
     fn read(&self, addr: u16) -> u8 {
         if addr < 0x8000 {        // 0x0000..=0x7FFF (Cartridge ROM)
-            self.cartridge_rom[addr as usize]
+            match &self.boot_rom {
+                Some(boot_rom) if (addr as usize) < BOOT_ROM_SIZE => boot_rom[addr as usize],
+                _ => self.cartridge.read(addr),
+            }
         } else if addr < 0xA000 { // 0x8000..=0x9FFF (Video RAM)
-            unsafe { (*self.ppu).read(addr) }
+            self.ppu.read(addr)
         } else if addr < 0xC000 { // 0xA000..=0xBFFF (Cartridge RAM)
-            self.cartridge_ram[addr as usize - 0xA000]
+            self.cartridge.read(addr)
         } else if addr < 0xE000 { // 0xC000..=0xDFFF (Internal RAM)
             self.ram[addr as usize - 0xC000]
         } else if addr < 0xFE00 { // 0xE000..=0xFDFF (Echo RAM)
@@ -65,86 +89,154 @@ impl MemoryBus for Mmu {
         } else if addr < 0xFF80 { // 0xFF00..=0xFF7F (Hardware IO)
             match addr {
                 // Joypad
-                0xFF00 => unsafe { (*self.joypad).p1() }
+                0xFF00 => self.joypad.p1(),
 
                 // Serial
-                0xFF01 => 0xFF,
-                0xFF02 => 0x03,
+                0xFF01..=0xFF02 => self.serial.read(addr),
 
                 // Timer
-                0xFF04..=0xFF07 => unsafe { (*self.timer).read(addr) }
-
-                // CPU
-                0xFF0F => unsafe { (*self.cpu).read(addr) }
+                0xFF04..=0xFF07 => self.timer.read(addr),
 
                 // SPU
-                0xFF10..=0xFF26 => unsafe { (*self.spu).read(addr) }
+                0xFF10..=0xFF3F => self.spu.read(addr),
 
                 // PPU
-                0xFF40..=0xFF4B => unsafe { (*self.ppu).read(addr) },
+                0xFF40..=0xFF4B => self.ppu.read(addr),
+
+                // Boot ROM
+                BOOT_ROM_DISABLE_ADDR => 0xFF,
 
                 _ => 0
             }
         } else if addr < 0xFFFF { // 0xFF80..=0xFFFE (Zero Page)
             self.ram[0x2000 + (addr - 0xFF80) as usize]
-        } else {
-            unsafe { (*self.cpu).read(addr) }
+        } else {                  // 0xFFFF (Interrupt Enable, handled by the CPU)
+            0xFF
         }
     }
 
     fn write(&mut self, addr: u16, data: u8) {
         if addr < 0x8000 {        // 0x0000..=0x7FFF (Cartridge ROM)
-            warn!("crom write {:02X} => {:04X}", data, addr);
             // read-only, but writting to it configures the rom bank switch
-            // self.cartridge_rom[addr as usize] = data;
+            self.write_cartridge(addr, data);
         } else if addr < 0xA000 { // 0x8000..=0x9FFF (Video RAM)
-            unsafe { (*self.ppu).write(addr, data) }
+            self.ppu.write(addr, data)
         } else if addr < 0xC000 { // 0xA000..=0xBFFF (Cartridge RAM)
-            self.cartridge_ram[addr as usize - 0xA000] = data;
+            self.write_cartridge(addr, data);
         } else if addr < 0xE000 { // 0xC000..=0xDFFF (Internal RAM)
             self.ram[addr as usize - 0xC000] = data
         } else if addr < 0xFE00 { // 0xE000..=0xFDFF (Echo RAM)
             self.ram[addr as usize - 0xE000] = data
         } else if addr < 0xFEA0 { // 0xFE00..=0xFE9F (OAM)
-            unsafe { (*self.ppu).write(addr, data) }
+            self.ppu.write(addr, data)
         } else if addr < 0xFF00 { // 0xFEA0..=0xFEFF (Unusable)
         } else if addr < 0xFF80 { // 0xFF00..=0xFF7F (Hardware IO)
             match addr {
                 // Joypad
-                0xFF00 => unsafe { (*self.joypad).set_p1(data) }
+                0xFF00 => self.joypad.set_p1(data),
 
-                // Timer
-                0xFF04..=0xFF07 => unsafe { (*self.timer).write(addr, data) }
+                // Serial
+                0xFF01..=0xFF02 => self.serial.write(addr, data),
 
-                // CPU
-                0xFF0F => unsafe { (*self.cpu).write(addr, data); }
+                // Timer
+                0xFF04..=0xFF07 => self.timer.write(addr, data),
 
                 // SPU
-                0xFF10..=0xFF26 => unsafe { (*self.spu).write(addr, data) }
+                0xFF10..=0xFF3F => self.spu.write(addr, data),
 
                 // DMA
-                0xFF46 => {
-                    if data <= 0xF1 {
-                        let addr = u16::from_be_bytes([data, 0x00]);
+                0xFF46 if data <= 0xF1 => {
+                    let addr = u16::from_be_bytes([data, 0x00]);
 
-                        let mut oam: [u8; 160] = [0; 160];
-                        for i in 0..160 {
-                            oam[i] = self.read(addr + i as u16)
-                        }
-
-                        unsafe { (*self.ppu).populate_object_attribute_ram(&oam) };
+                    let mut oam: [u8; 160] = [0; 160];
+                    for (i, data) in oam.iter_mut().enumerate() {
+                        *data = self.read(addr + i as u16)
                     }
+
+                    self.ppu.populate_object_attribute_ram(&oam);
                 }
 
                 // PPU
-                0xFF40..=0xFF4B => unsafe { (*self.ppu).write(addr, data) },
+                0xFF40..=0xFF4B => self.ppu.write(addr, data),
+
+                // Boot ROM
+                BOOT_ROM_DISABLE_ADDR if data != 0 => self.boot_rom = None,
 
                 _ => { }
             }
         } else if addr < 0xFFFF { // 0xFF80..=0xFFFE (Zero Page)
             self.ram[0x2000 + (addr - 0xFF80) as usize] = data
-        } else {
-            unsafe { (*self.cpu).write(addr, data) }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boot_rom_test() {
+        let mut mmu = Mmu::default();
+        mmu.cartridge.rom_mut()[0x0000] = 0x11;
+        mmu.cartridge.rom_mut()[0x0100] = 0x22;
+        mmu.boot_rom = Some(Box::new([0xAA; BOOT_ROM_SIZE]));
+
+        assert_eq!(0xAA, mmu.read(0x0000));
+        assert_eq!(0xAA, mmu.read(0x00FF));
+        assert_eq!(0x22, mmu.read(0x0100));
+
+        mmu.write(BOOT_ROM_DISABLE_ADDR, 0x00);
+        assert_eq!(0xAA, mmu.read(0x0000));
+
+        mmu.write(BOOT_ROM_DISABLE_ADDR, 0x01);
+        assert_eq!(0x11, mmu.read(0x0000));
+        assert!(mmu.boot_rom.is_none());
+    }
+
+    fn with_cartridge(cartridge_type: u8) -> Mmu {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0147] = cartridge_type;
+        rom[0x0149] = 0x02;
+        Mmu { cartridge: crate::cartridge::load(rom).unwrap(), ..Mmu::default() }
+    }
+
+    #[test]
+    fn save_dirty_test() {
+        // MBC3+TIMER+RAM+BATTERY, RAM writes only count while enabled
+        let mut mmu = with_cartridge(0x10);
+        mmu.write(0xA000, 0x12);
+        assert!(!mmu.cartridge_ram_dirty);
+        mmu.write(0x0000, 0x0A);
+        assert!(!mmu.cartridge_ram_dirty);
+        mmu.write(0xA000, 0x12);
+        assert!(mmu.cartridge_ram_dirty);
+
+        // Clock registers and latching as well
+        mmu.cartridge_ram_dirty = false;
+        mmu.write(0x4000, 0x08);
+        mmu.write(0xA000, 0x30);
+        assert!(mmu.cartridge_ram_dirty);
+        mmu.cartridge_ram_dirty = false;
+        mmu.write(0x6000, 0x00);
+        assert!(!mmu.cartridge_ram_dirty);
+        mmu.write(0x6000, 0x01);
+        assert!(mmu.cartridge_ram_dirty);
+
+        // MBC1+RAM, nothing to save
+        let mut mmu = with_cartridge(0x02);
+        mmu.write(0x0000, 0x0A);
+        mmu.write(0xA000, 0x12);
+        assert_eq!(0x12, mmu.read(0xA000));
+        assert!(!mmu.cartridge_ram_dirty);
+    }
+
+    #[test]
+    fn wave_ram_test() {
+        let mut mmu = Mmu::default();
+        for addr in 0xFF30..=0xFF3F {
+            mmu.write(addr, addr as u8);
+        }
+        assert_eq!(0x30, mmu.read(0xFF30));
+        assert_eq!(0x3F, mmu.read(0xFF3F));
+    }
+}
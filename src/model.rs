@@ -0,0 +1,277 @@
+use crate::cartridge::Cartridge;
+use crate::cartridge::header::Licensee;
+use crate::cpu::flags::Flags;
+use crate::cpu::registers::Registers;
+use crate::ppu::{Color, SHADE};
+use crate::spu::filter::{CGB_CHARGE_FACTOR, DMG_CHARGE_FACTOR};
+
+use std::fmt;
+use std::str::FromStr;
+
+const TITLE_BEGIN: usize = 0x0134;
+const TITLE_END: usize = 0x0144;
+
+/// Hardware revision, each one leaves a different state after the boot rom
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Model {
+    /// Original Game Boy, early boot rom revision
+    Dmg0,
+    /// Original Game Boy
+    #[default]
+    Dmg,
+    /// Game Boy Pocket
+    Mgb,
+    /// Super Game Boy
+    Sgb,
+    /// Super Game Boy 2
+    Sgb2,
+    /// Game Boy Color, running a DMG cartridge
+    CgbDmg,
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dmg0" => Ok(Self::Dmg0),
+            "dmg" => Ok(Self::Dmg),
+            "mgb" => Ok(Self::Mgb),
+            "sgb" => Ok(Self::Sgb),
+            "sgb2" => Ok(Self::Sgb2),
+            "cgb" => Ok(Self::CgbDmg),
+            _ => Err(format!("unknown model {} (dmg0, dmg, mgb, sgb, sgb2, cgb)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Dmg0 => "DMG0",
+            Self::Dmg => "DMG",
+            Self::Mgb => "MGB",
+            Self::Sgb => "SGB",
+            Self::Sgb2 => "SGB2",
+            Self::CgbDmg => "CGB",
+        })
+    }
+}
+
+/// Hardware registers after the boot rom, the APU power (NR52) goes first
+const POST_BOOT_IO: [(u16, u8); 37] = [
+    (0xFF26, 0xF1), // NR52
+    (0xFF01, 0x00), // SB
+    (0xFF02, 0x7E), // SC
+    (0xFF05, 0x00), // TIMA
+    (0xFF06, 0x00), // TMA
+    (0xFF07, 0xF8), // TAC
+    (0xFF0F, 0xE1), // IF
+    (0xFF10, 0x80), // NR10
+    (0xFF11, 0xBF), // NR11
+    (0xFF12, 0xF3), // NR12
+    (0xFF13, 0xFF), // NR13
+    (0xFF14, 0xBF), // NR14
+    (0xFF16, 0x3F), // NR21
+    (0xFF17, 0x00), // NR22
+    (0xFF18, 0xFF), // NR23
+    (0xFF19, 0xBF), // NR24
+    (0xFF1A, 0x7F), // NR30
+    (0xFF1B, 0xFF), // NR31
+    (0xFF1C, 0x9F), // NR32
+    (0xFF1D, 0xFF), // NR33
+    (0xFF1E, 0xBF), // NR34
+    (0xFF20, 0xFF), // NR41
+    (0xFF21, 0x00), // NR42
+    (0xFF22, 0x00), // NR43
+    (0xFF23, 0xBF), // NR44
+    (0xFF24, 0x77), // NR50
+    (0xFF25, 0xF3), // NR51
+    (0xFF40, 0x91), // LCDC
+    (0xFF42, 0x00), // SCY
+    (0xFF43, 0x00), // SCX
+    (0xFF45, 0x00), // LYC
+    (0xFF47, 0xFC), // BGP
+    (0xFF48, 0xFF), // OBP0
+    (0xFF49, 0xFF), // OBP1
+    (0xFF4A, 0x00), // WY
+    (0xFF4B, 0x00), // WX
+    (0xFFFF, 0x00), // IE
+];
+
+impl Model {
+    /// CPU registers after the boot rom, some depend on the cartridge header
+    pub fn post_boot_registers(self, cartridge: &dyn Cartridge) -> Registers {
+        let header = cartridge.header();
+        let mut regs = Registers::default();
+
+        match self {
+            Self::Dmg0 => {
+                regs.set_af(0x0100);
+                regs.set_bc(0xFF13);
+                regs.set_de(0x00C1);
+                regs.set_hl(0x8403);
+            }
+            Self::Dmg | Self::Mgb => {
+                regs.set_a(if self == Self::Dmg { 0x01 } else { 0xFF });
+                // H and C are left set by the header checksum routine, unless it is zero
+                regs.set_flags(if header.header_checksum == 0 {
+                    Flags::Z
+                } else {
+                    Flags::Z | Flags::H | Flags::C
+                });
+                regs.set_bc(0x0013);
+                regs.set_de(0x00D8);
+                regs.set_hl(0x014D);
+            }
+            Self::Sgb | Self::Sgb2 => {
+                regs.set_af(if self == Self::Sgb { 0x0100 } else { 0xFF00 });
+                regs.set_bc(0x0014);
+                regs.set_de(0x0000);
+                regs.set_hl(0xC060);
+            }
+            Self::CgbDmg => {
+                // Nintendo games get their palette picked by the title checksum
+                let nintendo = match header.licensee {
+                    Licensee::Old(code) => code == 0x01,
+                    Licensee::New(code) => code == *b"01",
+                };
+                let b = if nintendo {
+                    cartridge.rom()[TITLE_BEGIN..TITLE_END].iter().fold(0u8, |x, c| x.wrapping_add(*c))
+                } else {
+                    0x00
+                };
+
+                regs.set_af(0x1180);
+                regs.set_b(b);
+                regs.set_c(0x00);
+                regs.set_de(0x0008);
+                regs.set_hl(if b == 0x43 || b == 0x58 { 0x991A } else { 0x007C });
+            }
+        }
+
+        regs.set_sp(0xFFFE);
+        regs.set_pc(0x0100);
+        regs
+    }
+
+    /// Hardware registers after the boot rom
+    pub fn post_boot_io(self) -> Vec<(u16, u8)> {
+        let mut io = POST_BOOT_IO.to_vec();
+        if let Self::Sgb | Self::Sgb2 = self {
+            io[0] = (0xFF26, 0xF0);
+        }
+        if let Self::CgbDmg = self {
+            io[2] = (0xFF02, 0x7F);
+        }
+        io
+    }
+
+    /// DIV after the boot rom, it depends on how long the boot rom took
+    pub fn post_boot_divider(self) -> u8 {
+        match self {
+            Self::Dmg0 => 0x18,
+            Self::Dmg | Self::Mgb => 0xAB,
+            Self::Sgb | Self::Sgb2 | Self::CgbDmg => 0x00,
+        }
+    }
+
+    /// Share of the audio output capacitor charge kept each tick, it leaks faster on CGB
+    pub fn charge_factor(self) -> f64 {
+        match self {
+            Self::Dmg0 | Self::Dmg | Self::Mgb | Self::Sgb | Self::Sgb2 => DMG_CHARGE_FACTOR,
+            Self::CgbDmg => CGB_CHARGE_FACTOR,
+        }
+    }
+
+    /// Colors of the four shades on the screen
+    pub fn shades(self) -> [Color; 4] {
+        match self {
+            Self::Dmg0 | Self::Dmg => SHADE,
+            Self::Mgb => [
+                Color::rgb(0xC4, 0xCF, 0xA1),
+                Color::rgb(0x8B, 0x95, 0x6D),
+                Color::rgb(0x4D, 0x53, 0x3C),
+                Color::rgb(0x1F, 0x1F, 0x1F),
+            ],
+            // Default border palette (1-A)
+            Self::Sgb | Self::Sgb2 => [
+                Color::rgb(0xF8, 0xE8, 0xC8),
+                Color::rgb(0xD8, 0x90, 0x48),
+                Color::rgb(0xA8, 0x28, 0x20),
+                Color::rgb(0x30, 0x18, 0x50),
+            ],
+            // Background colors of the default compatibility palette
+            Self::CgbDmg => [
+                Color::rgb(0xFF, 0xFF, 0xFF),
+                Color::rgb(0x7B, 0xFF, 0x31),
+                Color::rgb(0x00, 0x63, 0xC5),
+                Color::rgb(0x00, 0x00, 0x00),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge;
+
+    fn cartridge(title: &[u8], old_licensee: u8, header_checksum: u8) -> Box<dyn Cartridge> {
+        let mut rom = vec![0; 0x8000];
+        rom[TITLE_BEGIN..TITLE_BEGIN + title.len()].copy_from_slice(title);
+        rom[0x014B] = old_licensee;
+        rom[0x014D] = header_checksum;
+        cartridge::load(rom).unwrap()
+    }
+
+    #[test]
+    fn from_str_test() {
+        assert_eq!(Ok(Model::Dmg0), "dmg0".parse());
+        assert_eq!(Ok(Model::Mgb), "MGB".parse());
+        assert_eq!(Ok(Model::CgbDmg), "cgb".parse());
+        assert!("gba".parse::<Model>().is_err());
+    }
+
+    #[test]
+    fn dmg_registers_test() {
+        let regs = Model::Dmg.post_boot_registers(cartridge(b"TETRIS", 0x01, 0x0A).as_ref());
+        assert_eq!(0x01B0, regs.af());
+        assert_eq!(0x0013, regs.bc());
+        assert_eq!(0x00D8, regs.de());
+        assert_eq!(0x014D, regs.hl());
+        assert_eq!(0xFFFE, regs.sp());
+        assert_eq!(0x0100, regs.pc());
+
+        let regs = Model::Dmg.post_boot_registers(cartridge(b"TETRIS", 0x01, 0x00).as_ref());
+        assert_eq!(0x0180, regs.af());
+
+        let regs = Model::Mgb.post_boot_registers(cartridge(b"TETRIS", 0x01, 0x0A).as_ref());
+        assert_eq!(0xFFB0, regs.af());
+    }
+
+    #[test]
+    fn sgb_registers_test() {
+        let regs = Model::Sgb.post_boot_registers(cartridge(b"TETRIS", 0x01, 0x0A).as_ref());
+        assert_eq!(0x0100, regs.af());
+        assert_eq!(0x0014, regs.bc());
+        assert_eq!(0xC060, regs.hl());
+
+        let regs = Model::Sgb2.post_boot_registers(cartridge(b"TETRIS", 0x01, 0x0A).as_ref());
+        assert_eq!(0xFF00, regs.af());
+    }
+
+    #[test]
+    fn cgb_registers_test() {
+        let title_sum = b"TETRIS".iter().fold(0u8, |x, c| x.wrapping_add(*c));
+
+        let regs = Model::CgbDmg.post_boot_registers(cartridge(b"TETRIS", 0x01, 0x0A).as_ref());
+        assert_eq!(0x1180, regs.af());
+        assert_eq!(title_sum, regs.b());
+        assert_eq!(0x0008, regs.de());
+        assert_eq!(0x007C, regs.hl());
+
+        let regs = Model::CgbDmg.post_boot_registers(cartridge(b"TETRIS", 0x08, 0x0A).as_ref());
+        assert_eq!(0x00, regs.b());
+    }
+}
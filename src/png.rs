@@ -0,0 +1,127 @@
+//! Minimal PNG encoder, grayscale without compression
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+const COLOR_TYPE_GRAYSCALE: u8 = 0;
+
+// Stored (uncompressed) deflate blocks hold up to 64KiB
+const DEFLATE_BLOCK_SIZE: usize = 0xFFFF;
+
+/// Encode a grayscale image, one sample per byte (0 is black, 2^depth - 1 is white)
+///
+/// Depth is the bits per sample, 1, 2, 4 or 8.
+pub fn encode_grayscale(width: usize, height: usize, depth: u8, samples: &[u8]) -> Vec<u8> {
+    assert!(matches!(depth, 1 | 2 | 4 | 8), "unsupported bit depth {}", depth);
+    assert_eq!(width * height, samples.len());
+
+    // Scanlines start with the filter type (none), samples are packed from the high bits
+    let samples_per_byte = 8 / depth as usize;
+    let stride = width.div_ceil(samples_per_byte);
+    let mut raw = Vec::with_capacity((stride + 1) * height);
+    for row in samples.chunks(width.max(1)).take(height) {
+        raw.push(0);
+        for packed in row.chunks(samples_per_byte) {
+            let byte = packed.iter().enumerate().fold(0u8, |byte, (i, &sample)| {
+                byte | (sample & ((1u16 << depth) - 1) as u8) << (8 - depth as usize * (i + 1))
+            });
+            raw.push(byte);
+        }
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[depth, COLOR_TYPE_GRAYSCALE, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Zlib stream made of stored deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(DEFLATE_BLOCK_SIZE).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[0x01, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(if blocks.peek().is_none() { 0x01 } else { 0x00 });
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 }
+        })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_test() {
+        assert_eq!(0xAE42_6082, crc32(b"IEND"));
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        assert_eq!(0x11E6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn encode_test() {
+        let png = encode_grayscale(3, 2, 2, &[3, 2, 1, 0, 1, 2]);
+        assert_eq!(SIGNATURE, png[0..8]);
+
+        // IHDR
+        assert_eq!(&[0, 0, 0, 13], &png[8..12]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!(&[0, 0, 0, 3, 0, 0, 0, 2, 2, 0, 0, 0, 0], &png[16..29]);
+
+        // IDAT, a single stored block of two scanlines, the filter type and a packed byte
+        assert_eq!(&[0, 0, 0, 15], &png[33..37]);
+        assert_eq!(b"IDAT", &png[37..41]);
+        assert_eq!(&[0x78, 0x01, 0x01, 4, 0, 0xFB, 0xFF], &png[41..48]);
+        assert_eq!(&[0x00, 0b1110_0100, 0x00, 0b0001_1000], &png[48..52]);
+
+        // IEND
+        assert_eq!(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82], &png[png.len() - 12..]);
+    }
+
+    #[test]
+    fn large_image_test() {
+        let png = encode_grayscale(256, 512, 8, &vec![0x80; 256 * 512]);
+
+        // Stored blocks split at 64KiB, the image takes three
+        let idat_len = u32::from_be_bytes([png[33], png[34], png[35], png[36]]) as usize;
+        assert_eq!(2 + (257 * 512) + 3 * 5 + 4, idat_len);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod color;
 pub mod lcd_control;
 pub mod lcd_control_status;
 pub mod palette;
@@ -13,7 +14,7 @@ use std::collections::VecDeque;
 
 use crate::MemoryBus;
 
-use sdl2::pixels::Color;
+pub use color::Color;
 
 pub const SCREEN_PIXEL_WIDTH:  usize = 160;
 pub const SCREEN_PIXEL_HEIGHT: usize = 144;
@@ -24,10 +25,10 @@ pub const ARGB_BYTES_PER_PIXEL: usize = 4;
 pub const SCREEN_BUFFER_SIZE: usize = SCREEN_PIXEL_SIZE * ARGB_BYTES_PER_PIXEL;
 pub const SCREEN_BUFFER_WIDTH: usize = SCREEN_PIXEL_WIDTH * ARGB_BYTES_PER_PIXEL;
 
-pub const SHADE_0: Color = Color::RGB(0x9B, 0xBC, 0x0F); // Light
-pub const SHADE_1: Color = Color::RGB(0x8B, 0xAC, 0x0F); // Light Gray
-pub const SHADE_2: Color = Color::RGB(0x30, 0x62, 0x30); // Dark Gray
-pub const SHADE_3: Color = Color::RGB(0x0F, 0x38, 0x0F); // Dark
+pub const SHADE_0: Color = Color::rgb(0x9B, 0xBC, 0x0F); // Light
+pub const SHADE_1: Color = Color::rgb(0x8B, 0xAC, 0x0F); // Light Gray
+pub const SHADE_2: Color = Color::rgb(0x30, 0x62, 0x30); // Dark Gray
+pub const SHADE_3: Color = Color::rgb(0x0F, 0x38, 0x0F); // Dark
 pub const SHADE: [Color; 4] = [SHADE_0, SHADE_1, SHADE_2, SHADE_3];
 
 pub const TILE_SIZE: usize = 16;
@@ -38,7 +39,7 @@ pub const TILE_PER_ROW: usize = 32;
 #[allow(dead_code)]
 pub const TILE_PER_COL: usize = 32;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Ppu {
     lcdc: LcdControl,
     stat: LcdControlStatus,
@@ -64,6 +65,7 @@ pub struct Ppu {
     back_buffer_index: usize,
     front_buffer_index: usize,
     frame_buffer: [Box<[u8; SCREEN_BUFFER_SIZE]>; 2],
+    shades: [Color; 4],
 
     object_attribute_ram: Box<[Sprite; 40]>,
     video_ram: Box<[u8; 0x2000]>,
@@ -72,14 +74,8 @@ pub struct Ppu {
 impl Default for Ppu {
     fn default() -> Self {
         let mut blank_frame: [u8; SCREEN_BUFFER_SIZE] = [0; SCREEN_BUFFER_SIZE];
-        for index in 0..blank_frame.len() {
-            match index % 4 {
-                0 => blank_frame[index] = SHADE_0.a, // A
-                1 => blank_frame[index] = SHADE_0.r, // R
-                2 => blank_frame[index] = SHADE_0.g, // G
-                3 => blank_frame[index] = SHADE_0.b, // B
-                _ => panic!(),
-            }
+        for pixel in blank_frame.chunks_mut(ARGB_BYTES_PER_PIXEL) {
+            pixel.copy_from_slice(&SHADE_0.argb());
         }
 
         Self {
@@ -106,6 +102,7 @@ impl Default for Ppu {
             back_buffer_index: 0,
             front_buffer_index: 1,
             frame_buffer: [Box::new(blank_frame), Box::new(blank_frame)],
+            shades: SHADE,
 
             object_attribute_ram: Box::new([Sprite::default(); 40]),
             video_ram: Box::new([0; 0x2000]),
@@ -320,6 +317,16 @@ impl Ppu {
         &self.frame_buffer[self.front_buffer_index]
     }
 
+    /// Change the colors of the four shades, clearing the screen with the lightest one
+    pub fn set_shades(&mut self, shades: [Color; 4]) {
+        self.shades = shades;
+        for frame_buffer in self.frame_buffer.iter_mut() {
+            for pixel in frame_buffer.chunks_mut(ARGB_BYTES_PER_PIXEL) {
+                pixel.copy_from_slice(&shades[0].argb());
+            }
+        }
+    }
+
     pub fn read_object_attribute_ram(&self, addr: u16) -> u8 {
         let sprite_index = addr as usize / 4;
         let sprite_field = addr % 4;
@@ -347,7 +354,7 @@ impl Ppu {
     pub fn populate_object_attribute_ram(&mut self, data: &[u8; 160]) {
         for sprite_index in 0..40 {
             let beg = sprite_index * 4;
-            self.object_attribute_ram[sprite_index] = [data[beg+0],
+            self.object_attribute_ram[sprite_index] = [data[beg],
                                                        data[beg+1],
                                                        data[beg+2],
                                                        data[beg+3]].into();
@@ -404,8 +411,8 @@ impl Ppu {
 
             let pos: usize = (x + y * SCREEN_PIXEL_WIDTH) * ARGB_BYTES_PER_PIXEL;
 
-            let shade = &SHADE[shade_index];
-            frame_buffer[pos + 0] = shade.a;
+            let shade = &self.shades[shade_index];
+            frame_buffer[pos] = shade.a;
             frame_buffer[pos + 1] = shade.r;
             frame_buffer[pos + 2] = shade.g;
             frame_buffer[pos + 3] = shade.b;
@@ -438,7 +445,7 @@ impl Ppu {
 
                 let mut tile_line = (y as u8).wrapping_sub(sprite.screen_y());
                 if sprite.vertical_flip() {
-                    tile_line = (sprite_height as u8).wrapping_sub(1).wrapping_sub(tile_line);
+                    tile_line = sprite_height.wrapping_sub(1).wrapping_sub(tile_line);
                 }
 
                 let tile_addr = sprite.tile() as usize * TILE_SIZE + tile_line as usize * 2;
@@ -468,11 +475,11 @@ impl Ppu {
                     let x = right as usize;
 
                     if shade_index != 0 {
-                        let shade = &SHADE[shade_index as usize];
+                        let shade = &self.shades[shade_index as usize];
 
                         let pos = (x + y * SCREEN_PIXEL_WIDTH) * ARGB_BYTES_PER_PIXEL;
 
-                        frame_buffer[pos + 0] = shade.a;
+                        frame_buffer[pos] = shade.a;
                         frame_buffer[pos + 1] = shade.r;
                         frame_buffer[pos + 2] = shade.g;
                         frame_buffer[pos + 3] = shade.b;
@@ -511,9 +518,7 @@ impl Ppu {
                         self.reset_scanline();
 
                         // Swap frame buffers (XOR SWAP)
-                        self.back_buffer_index  ^= self.front_buffer_index;
-                        self.front_buffer_index ^= self.back_buffer_index;
-                        self.back_buffer_index  ^= self.front_buffer_index;
+                        std::mem::swap(&mut self.back_buffer_index, &mut self.front_buffer_index);
                     }
                 }
             }
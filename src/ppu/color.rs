@@ -0,0 +1,19 @@
+/// Color of a shade on the screen, stored in the frame buffer as ARGB
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    /// Opaque color
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 0xFF }
+    }
+
+    pub const fn argb(self) -> [u8; 4] {
+        [self.a, self.r, self.g, self.b]
+    }
+}
@@ -31,9 +31,9 @@ impl Default for LcdControl {
     }
 }
 
-impl Into<u8> for LcdControl {
-    fn into(self) -> u8 {
-        self.bits()
+impl From<LcdControl> for u8 {
+    fn from(val: LcdControl) -> Self {
+        val.bits()
     }
 }
 
@@ -32,9 +32,9 @@ pub enum LcdControlMode {
     Transfering,
 }
 
-impl Into<u8> for LcdControlStatus {
-    fn into(self) -> u8 {
-        self.bits()
+impl From<LcdControlStatus> for u8 {
+    fn from(val: LcdControlStatus) -> Self {
+        val.bits()
     }
 }
 
@@ -44,13 +44,13 @@ impl From<u8> for LcdControlStatus {
     }
 }
 
-impl Into<u8> for LcdControlMode {
-    fn into(self) -> u8 {
-        match self {
-            Self::Transfering => 3,
-            Self::ScanningOAM => 2,
-            Self::VerticalBlank => 1,
-            Self::HorizontalBlank => 0,
+impl From<LcdControlMode> for u8 {
+    fn from(val: LcdControlMode) -> Self {
+        match val {
+            LcdControlMode::Transfering => 3,
+            LcdControlMode::ScanningOAM => 2,
+            LcdControlMode::VerticalBlank => 1,
+            LcdControlMode::HorizontalBlank => 0,
         }
     }
 }
@@ -67,9 +67,9 @@ impl From<u8> for LcdControlMode {
     }
 }
 
-impl Into<LcdControlStatus> for LcdControlMode {
-    fn into(self) -> LcdControlStatus {
-        LcdControlStatus::from_bits(self.into()).unwrap()
+impl From<LcdControlMode> for LcdControlStatus {
+    fn from(val: LcdControlMode) -> Self {
+        LcdControlStatus::from_bits(val.into()).unwrap()
     }
 }
 
@@ -88,7 +88,7 @@ impl From<LcdControlStatus> for LcdControlMode {
 #[allow(dead_code)]
 impl LcdControlStatus {
     pub fn mode(&self) -> LcdControlMode {
-        self.clone().into()
+        (*self).into()
     }
 
     pub fn set_mode(&mut self, mode: LcdControlMode) {
@@ -8,9 +8,9 @@ pub struct Palette {
     pub palette: u8
 }
 
-impl Into<u8> for Palette {
-    fn into(self) -> u8 {
-        self.palette
+impl From<Palette> for u8 {
+    fn from(val: Palette) -> Self {
+        val.palette
     }
 }
 
@@ -20,9 +20,9 @@ impl From<u8> for Palette {
     }
 }
 
-impl Into<u32> for Palette {
-    fn into(self) -> u32 {
-        self.palette as u32
+impl From<Palette> for u32 {
+    fn from(val: Palette) -> Self {
+        val.palette as u32
     }
 }
 
@@ -20,9 +20,9 @@ bitflags! {
     }
 }
 
-impl Into<u8> for Flags {
-    fn into(self) -> u8 {
-        self.bits()
+impl From<Flags> for u8 {
+    fn from(val: Flags) -> Self {
+        val.bits()
     }
 }
 
@@ -40,9 +40,9 @@ pub struct Sprite {
     flags: Flags,
 }
 
-impl Into<[u8; 4]> for Sprite {
-    fn into(self) -> [u8; 4] {
-        [self.x(), self.y(), self.tile(), self.flags()]
+impl From<Sprite> for [u8; 4] {
+    fn from(val: Sprite) -> Self {
+        [val.x(), val.y(), val.tile(), val.flags()]
     }
 }
 
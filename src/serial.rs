@@ -0,0 +1,231 @@
+pub mod link;
+pub mod printer;
+
+use crate::MemoryBus;
+use crate::TICKS_PER_SECOND;
+
+use std::any::Any;
+use std::fmt;
+
+const SB_ADDR: u16 = 0xFF01;
+const SC_ADDR: u16 = 0xFF02;
+
+/// Internal clock, 8192Hz
+const TICKS_PER_BIT: u64 = TICKS_PER_SECOND / 8192;
+const TICKS_PER_TRANSFER: u64 = TICKS_PER_BIT * 8;
+
+bitflags! {
+    #[derive(Default)]
+    pub struct SerialControl: u8 {
+        const TRANSFER_START = 0b1000_0000;
+        const INTERNAL_CLOCK = 0b0000_0001;
+    }
+}
+
+/// Whatever is plugged on the other end of the link cable
+pub trait SerialDevice: fmt::Debug + Send {
+    /// Transfer on the internal clock, taking the byte shifted out of SB and giving the one shifted in
+    ///
+    /// None while the other side did not answer yet, the same byte is offered again on the next step.
+    fn exchange(&mut self, data: u8) -> Option<u8>;
+
+    /// Transfer on the external clock, polled on every step while waiting on the other side to clock one
+    ///
+    /// Gives the byte shifted in, once a transfer completed with `data` shifted out.
+    fn poll_external(&mut self, _data: u8) -> Option<u8> {
+        None
+    }
+
+    /// Copy of the whole device state, for save states
+    fn box_clone(&self) -> Box<dyn SerialDevice>;
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl Clone for Box<dyn SerialDevice> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Nothing connected, keeps the bytes sent as text
+///
+/// Test roms (Blargg) print their results over serial.
+#[derive(Clone, Debug, Default)]
+pub struct TextCapture {
+    text: String,
+    line_start: usize,
+}
+
+impl TextCapture {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl SerialDevice for TextCapture {
+    fn exchange(&mut self, data: u8) -> Option<u8> {
+        if data == b'\n' {
+            info!("Serial: {}", &self.text[self.line_start..]);
+            self.line_start = self.text.len() + 1;
+        }
+        self.text.push(data as char);
+
+        // The input line is pulled up when disconnected
+        Some(0xFF)
+    }
+
+    fn box_clone(&self) -> Box<dyn SerialDevice> { Box::new(self.clone()) }
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+#[derive(Clone, Debug)]
+pub struct Serial {
+    interrupt_requested: bool,
+
+    data: u8,
+    control: SerialControl,
+
+    // Ticks into the current transfer
+    transfer_ticks: u64,
+
+    device: Box<dyn SerialDevice>,
+}
+
+impl Default for Serial {
+    fn default() -> Self {
+        Self {
+            interrupt_requested: false,
+            data: 0,
+            control: SerialControl::empty(),
+            transfer_ticks: 0,
+            device: Box::new(TextCapture::default()),
+        }
+    }
+}
+
+impl MemoryBus for Serial {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            SB_ADDR => self.data,
+            SC_ADDR => self.control.bits() | 0x7E, // unused bits read as 1
+            _ => panic!()
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            SB_ADDR => self.data = data,
+            SC_ADDR => {
+                self.control = SerialControl::from_bits_truncate(data);
+                self.transfer_ticks = 0;
+            }
+            _ => panic!()
+        }
+    }
+}
+
+impl Serial {
+    /// Plug a device in, handing back the one it replaces
+    pub fn connect(&mut self, device: Box<dyn SerialDevice>) -> Box<dyn SerialDevice> {
+        std::mem::replace(&mut self.device, device)
+    }
+
+    pub fn device(&self) -> &dyn SerialDevice {
+        self.device.as_ref()
+    }
+
+    pub fn device_mut(&mut self) -> &mut dyn SerialDevice {
+        self.device.as_mut()
+    }
+
+    /// Text sent so far, when the default device is connected
+    pub fn captured_text(&self) -> Option<&str> {
+        self.device.as_any().downcast_ref::<TextCapture>().map(TextCapture::text)
+    }
+
+    pub fn interrupt_requested(&self) -> bool {
+        self.interrupt_requested
+    }
+
+    pub fn step(&mut self, ticks: u64) {
+        self.interrupt_requested = false;
+
+        if !self.control.contains(SerialControl::TRANSFER_START) {
+            return;
+        }
+        self.transfer_ticks += ticks;
+
+        let received = if !self.control.contains(SerialControl::INTERNAL_CLOCK) {
+            // With the external clock, the transfer waits on the other side to clock it
+            self.device.poll_external(self.data)
+        } else if self.transfer_ticks >= TICKS_PER_TRANSFER {
+            self.device.exchange(self.data)
+        } else {
+            None
+        };
+
+        if let Some(data) = received {
+            self.data = data;
+            self.control.remove(SerialControl::TRANSFER_START);
+            self.interrupt_requested = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(serial: &mut Serial, data: u8) -> u64 {
+        serial.write(SB_ADDR, data);
+        serial.write(SC_ADDR, 0x81);
+
+        let mut ticks = 0;
+        loop {
+            serial.step(4);
+            ticks += 4;
+            if serial.interrupt_requested() {
+                return ticks;
+            }
+        }
+    }
+
+    #[test]
+    fn transfer_test() {
+        let mut serial = Serial::default();
+        assert_eq!(0x7E, serial.read(SC_ADDR));
+
+        assert_eq!(TICKS_PER_TRANSFER, send(&mut serial, b'A'));
+        assert_eq!(0xFF, serial.read(SB_ADDR));
+        assert_eq!(0x7F, serial.read(SC_ADDR));
+
+        serial.step(4);
+        assert!(!serial.interrupt_requested());
+    }
+
+    #[test]
+    fn external_clock_test() {
+        let mut serial = Serial::default();
+        serial.write(SB_ADDR, b'A');
+        serial.write(SC_ADDR, 0x80);
+
+        for _ in 0..TICKS_PER_TRANSFER {
+            serial.step(4);
+            assert!(!serial.interrupt_requested());
+        }
+        assert_eq!(0xFE, serial.read(SC_ADDR));
+        assert_eq!(Some(""), serial.captured_text());
+    }
+
+    #[test]
+    fn text_capture_test() {
+        let mut serial = Serial::default();
+        for &data in b"Passed\n" {
+            send(&mut serial, data);
+        }
+        assert_eq!(Some("Passed\n"), serial.captured_text());
+    }
+}
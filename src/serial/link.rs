@@ -0,0 +1,422 @@
+use super::SerialDevice;
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex};
+
+const PROTOCOL_VERSION: u8 = 1;
+
+// Messages are two bytes, the kind followed by the data
+const HELLO: u8 = 0x4B;    // protocol version, sent by both sides once connected
+const TRANSFER: u8 = 0x01; // byte shifted out by the side driving the clock
+const REPLY: u8 = 0x02;    // byte shifted out by the side on the external clock
+
+/// Unix-domain socket addresses take this prefix, anything else is a TCP address
+pub const UNIX_PREFIX: &str = "unix:";
+
+/// The device is polled on every instruction, sockets are only read once every this many polls (~8KHz)
+const SOCKET_READ_INTERVAL: u32 = 64;
+
+/// In-process end of a pipe, bytes written to one end are read from the other
+#[derive(Clone, Debug, Default)]
+struct Pipe {
+    rx: Arc<Mutex<VecDeque<u8>>>,
+    tx: Arc<Mutex<VecDeque<u8>>>,
+}
+
+impl Pipe {
+    fn pair() -> (Self, Self) {
+        let a = Self::default();
+        let b = Self { rx: a.tx.clone(), tx: a.rx.clone() };
+        (a, b)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Socket {
+    Tcp(Arc<TcpStream>),
+    #[cfg(unix)]
+    Unix(Arc<UnixStream>),
+    Pipe(Pipe),
+}
+
+impl Socket {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Socket::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            #[cfg(unix)]
+            Socket::Unix(stream) => stream.set_nonblocking(nonblocking),
+            Socket::Pipe(_) => Ok(()),
+        }
+    }
+
+    /// Reading costs a system call
+    fn is_os_socket(&self) -> bool {
+        !matches!(self, Socket::Pipe(_))
+    }
+}
+
+impl Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Socket::Tcp(stream) => (&**stream).read(buf),
+            #[cfg(unix)]
+            Socket::Unix(stream) => (&**stream).read(buf),
+            Socket::Pipe(pipe) => {
+                let mut rx = pipe.rx.lock().unwrap();
+                if rx.is_empty() {
+                    // The other end is gone once nobody else holds the queues
+                    return if Arc::strong_count(&pipe.tx) == 1 {
+                        Ok(0)
+                    } else {
+                        Err(io::ErrorKind::WouldBlock.into())
+                    };
+                }
+
+                let len = buf.len().min(rx.len());
+                for (dst, src) in buf.iter_mut().zip(rx.drain(..len)) {
+                    *dst = src;
+                }
+                Ok(len)
+            }
+        }
+    }
+}
+
+impl Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Socket::Tcp(stream) => (&**stream).write(buf),
+            #[cfg(unix)]
+            Socket::Unix(stream) => (&**stream).write(buf),
+            Socket::Pipe(pipe) => {
+                pipe.tx.lock().unwrap().extend(buf);
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Socket::Tcp(stream) => (&**stream).flush(),
+            #[cfg(unix)]
+            Socket::Unix(stream) => (&**stream).flush(),
+            Socket::Pipe(_) => Ok(()),
+        }
+    }
+}
+
+/// Link cable to another emulator, over a TCP or Unix-domain socket, or to another gameboy in-process
+///
+/// The side whose SC selects the internal clock sends its byte and the side waiting on the external
+/// clock answers with its own once it started a transfer, so both complete with the bytes swapped.
+/// The clock is stretched until the answer arrives. When both sides pick the internal clock, each
+/// takes the other's byte, as the real hardware would shift them into each other.
+///
+/// Clones (save states) share the connection.
+#[derive(Clone, Debug)]
+pub struct LinkCable {
+    // None once the other side hung up
+    socket: Option<Socket>,
+
+    // Partial message read so far
+    rx: Vec<u8>,
+    polls: u32,
+
+    // Transfer on our clock waiting on the answer
+    sent: bool,
+    reply: Option<u8>,
+
+    // Transfers on the other side's clock waiting on ours to start
+    incoming: VecDeque<u8>,
+}
+
+impl LinkCable {
+    /// Wait for the other emulator to connect, on a TCP address or `unix:PATH`
+    pub fn listen(addr: &str) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            if let Some(path) = addr.strip_prefix(UNIX_PREFIX) {
+                let (stream, _) = UnixListener::bind(path)?.accept()?;
+                return Self::from_unix(stream);
+            }
+        }
+        let (stream, _) = TcpListener::bind(addr)?.accept()?;
+        Self::from_tcp(stream)
+    }
+
+    /// Connect to the other emulator, on a TCP address or `unix:PATH`
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            if let Some(path) = addr.strip_prefix(UNIX_PREFIX) {
+                return Self::from_unix(UnixStream::connect(path)?);
+            }
+        }
+        Self::from_tcp(TcpStream::connect(addr)?)
+    }
+
+    pub fn from_tcp(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        Self::handshake(Socket::Tcp(Arc::new(stream)))
+    }
+
+    #[cfg(unix)]
+    pub fn from_unix(stream: UnixStream) -> io::Result<Self> {
+        Self::handshake(Socket::Unix(Arc::new(stream)))
+    }
+
+    /// Both ends of a cable between two gameboys in the same process
+    pub fn pair() -> (Self, Self) {
+        let (a, b) = Pipe::pair();
+        (Self::new(Socket::Pipe(a)), Self::new(Socket::Pipe(b)))
+    }
+
+    fn new(socket: Socket) -> Self {
+        Self {
+            socket: Some(socket),
+            rx: Vec::new(),
+            polls: 0,
+            sent: false,
+            reply: None,
+            incoming: VecDeque::new(),
+        }
+    }
+
+    fn handshake(mut socket: Socket) -> io::Result<Self> {
+        socket.write_all(&[HELLO, PROTOCOL_VERSION])?;
+
+        let mut hello = [0; 2];
+        socket.read_exact(&mut hello)?;
+        if hello != [HELLO, PROTOCOL_VERSION] {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unexpected handshake {:02X?}", hello)));
+        }
+
+        socket.set_nonblocking(true)?;
+        Ok(Self::new(socket))
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.socket.is_some()
+    }
+
+    fn disconnect(&mut self, reason: &str) {
+        warn!("Link cable disconnected: {}", reason);
+        self.socket = None;
+    }
+
+    fn send(&mut self, kind: u8, data: u8) {
+        if let Some(socket) = &mut self.socket {
+            if let Err(err) = socket.write_all(&[kind, data]) {
+                self.disconnect(&err.to_string());
+            }
+        }
+    }
+
+    fn receive(&mut self) {
+        self.polls = self.polls.wrapping_add(1);
+        if self.socket.as_ref().is_some_and(Socket::is_os_socket) && self.polls % SOCKET_READ_INTERVAL != 0 {
+            return;
+        }
+
+        let mut buf = [0; 64];
+        while let Some(socket) = &mut self.socket {
+            match socket.read(&mut buf) {
+                Ok(0) => self.disconnect("closed by the other side"),
+                Ok(len) => self.rx.extend_from_slice(&buf[..len]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => { }
+                Err(err) => self.disconnect(&err.to_string()),
+            }
+        }
+
+        let len = self.rx.len() & !1;
+        let messages: Vec<u8> = self.rx.drain(..len).collect();
+        for message in messages.chunks_exact(2) {
+            match message[0] {
+                // Both sides on the internal clock, take its byte as the answer
+                TRANSFER if self.sent && self.reply.is_none() => self.reply = Some(message[1]),
+                TRANSFER => self.incoming.push_back(message[1]),
+                REPLY => self.reply = Some(message[1]),
+                HELLO => { }
+                kind => warn!("Link cable message {:02X} unknown", kind),
+            }
+        }
+    }
+}
+
+impl SerialDevice for LinkCable {
+    fn exchange(&mut self, data: u8) -> Option<u8> {
+        if !self.sent {
+            self.send(TRANSFER, data);
+            self.sent = true;
+        }
+        self.receive();
+
+        // A transfer the other side clocked before ours started crossed with it
+        let reply = self.reply.take().or_else(|| self.incoming.pop_front());
+        match reply {
+            Some(_) => {
+                self.sent = false;
+                reply
+            }
+            None if !self.is_connected() => {
+                self.sent = false;
+                Some(0xFF)
+            }
+            None => None,
+        }
+    }
+
+    fn poll_external(&mut self, data: u8) -> Option<u8> {
+        self.receive();
+
+        let received = self.incoming.pop_front()?;
+        self.send(REPLY, data);
+        Some(received)
+    }
+
+    fn box_clone(&self) -> Box<dyn SerialDevice> { Box::new(self.clone()) }
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryBus;
+    use crate::serial::Serial;
+
+    use std::time::{Duration, Instant};
+
+    fn tcp_pair() -> (LinkCable, LinkCable) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let client = std::thread::spawn(move || LinkCable::connect(&addr).unwrap());
+        let (stream, _) = listener.accept().unwrap();
+        let host = LinkCable::from_tcp(stream).unwrap();
+        (host, client.join().unwrap())
+    }
+
+    fn serial(cable: LinkCable, sb: u8, sc: u8) -> Serial {
+        let mut serial = Serial::default();
+        serial.connect(Box::new(cable));
+        serial.write(0xFF01, sb);
+        serial.write(0xFF02, sc);
+        serial
+    }
+
+    /// Step both sides until their transfers complete, giving the bytes received
+    fn transfer(a: &mut Serial, b: &mut Serial) -> (u8, u8) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let (mut a_done, mut b_done) = (false, false);
+        while !(a_done && b_done) {
+            assert!(Instant::now() < deadline, "transfer timed out");
+            a.step(4);
+            b.step(4);
+            a_done |= a.interrupt_requested();
+            b_done |= b.interrupt_requested();
+        }
+        (a.read(0xFF01), b.read(0xFF01))
+    }
+
+    #[test]
+    fn master_slave_test() {
+        let (host, client) = tcp_pair();
+        let mut master = serial(host, 0x12, 0x81);
+        let mut slave = serial(client, 0x34, 0x80);
+
+        assert_eq!((0x34, 0x12), transfer(&mut master, &mut slave));
+        assert_eq!(0x7F, master.read(0xFF02));
+        assert_eq!(0x7E, slave.read(0xFF02));
+
+        // Swap the roles on the same cable
+        master.write(0xFF01, 0x56);
+        master.write(0xFF02, 0x80);
+        slave.write(0xFF01, 0x78);
+        slave.write(0xFF02, 0x81);
+        assert_eq!((0x78, 0x56), transfer(&mut master, &mut slave));
+    }
+
+    #[test]
+    fn both_internal_clock_test() {
+        let (host, client) = tcp_pair();
+        let mut a = serial(host, 0xAA, 0x81);
+        let mut b = serial(client, 0x55, 0x81);
+
+        assert_eq!((0x55, 0xAA), transfer(&mut a, &mut b));
+    }
+
+    #[test]
+    fn slave_waits_test() {
+        let (host, client) = tcp_pair();
+        let mut master = serial(host, 0x12, 0x81);
+        let mut slave = serial(client, 0x34, 0x00);
+
+        // The transfer hangs until the other side starts its own
+        for _ in 0..0x4000 {
+            master.step(4);
+            slave.step(4);
+            assert!(!master.interrupt_requested());
+        }
+
+        slave.write(0xFF02, 0x80);
+        assert_eq!((0x34, 0x12), transfer(&mut master, &mut slave));
+    }
+
+    #[test]
+    fn disconnect_test() {
+        let (host, client) = tcp_pair();
+        drop(client);
+
+        let mut cable = host;
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cable.exchange(0x12).is_none() {
+            assert!(Instant::now() < deadline, "disconnect not noticed");
+        }
+        assert!(!cable.is_connected());
+        assert_eq!(Some(0xFF), cable.exchange(0x12));
+    }
+
+    #[test]
+    fn pair_test() {
+        let (a, b) = LinkCable::pair();
+        let mut master = serial(a, 0x12, 0x81);
+        let mut slave = serial(b, 0x34, 0x80);
+        assert_eq!((0x34, 0x12), transfer(&mut master, &mut slave));
+
+        let (mut a, b) = LinkCable::pair();
+        drop(b);
+        assert_eq!(Some(0xFF), a.exchange(0x12));
+        assert!(!a.is_connected());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_test() {
+        let path = std::env::temp_dir().join(format!("kiwi-gb-link-{}.sock", std::process::id()));
+        let addr = format!("{}{}", UNIX_PREFIX, path.display());
+
+        let listen_addr = addr.clone();
+        let host = std::thread::spawn(move || LinkCable::listen(&listen_addr).unwrap());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let client = loop {
+            match LinkCable::connect(&addr) {
+                Ok(client) => break client,
+                Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+                Err(err) => panic!("{}", err),
+            }
+        };
+        let host = host.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut master = serial(client, 0x12, 0x81);
+        let mut slave = serial(host, 0x34, 0x80);
+        assert_eq!((0x34, 0x12), transfer(&mut master, &mut slave));
+    }
+}
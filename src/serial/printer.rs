@@ -0,0 +1,427 @@
+use super::SerialDevice;
+
+use crate::png;
+
+use std::any::Any;
+use std::path::PathBuf;
+
+const MAGIC: [u8; 2] = [0x88, 0x33];
+
+// Commands
+const INIT: u8 = 0x01;
+const PRINT: u8 = 0x02;
+const DATA: u8 = 0x04;
+const STATUS: u8 = 0x0F;
+
+/// Answer to the first of the two bytes closing a packet
+const DEVICE_ID: u8 = 0x81;
+
+bitflags! {
+    #[derive(Default)]
+    pub struct PrinterStatus: u8 {
+        const LOW_BATTERY      = 0b1000_0000;
+        const OTHER_ERROR      = 0b0100_0000;
+        const PAPER_JAM        = 0b0010_0000;
+        const PACKET_ERROR     = 0b0001_0000;
+        const UNPROCESSED_DATA = 0b0000_1000;
+        const IMAGE_DATA_FULL  = 0b0000_0100;
+        const BUSY             = 0b0000_0010;
+        const CHECKSUM_ERROR   = 0b0000_0001;
+    }
+}
+
+/// Printed width, a strip of 20 tiles
+pub const PAPER_WIDTH: usize = 160;
+
+const TILE_BYTES: usize = 16;
+const TILES_PER_ROW: usize = PAPER_WIDTH / 8;
+
+/// The printer memory holds 9 packets of 2 tile rows, a full screen
+const BUFFER_SIZE: usize = 9 * 2 * TILES_PER_ROW * TILE_BYTES;
+
+/// Blank lines fed for each unit of margin
+const MARGIN_LINES: usize = 8;
+
+/// Status requests answered busy after a print, before it is done
+const PRINT_BUSY_POLLS: u8 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Magic(usize),
+    Command,
+    Compression,
+    Length(usize),
+    Data,
+    Checksum(usize),
+    DeviceId,
+    Status,
+}
+
+/// Sheet of paper, each pixel is a shade from 0 (white) to 3 (black)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Page {
+    pub pixels: Vec<u8>,
+}
+
+impl Page {
+    pub fn width(&self) -> usize {
+        PAPER_WIDTH
+    }
+
+    pub fn height(&self) -> usize {
+        self.pixels.len() / PAPER_WIDTH
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let samples: Vec<u8> = self.pixels.iter().map(|shade| 3 - shade).collect();
+        png::encode_grayscale(PAPER_WIDTH, self.height(), 2, &samples)
+    }
+}
+
+/// Game Boy Printer, speaking the packet protocol over the link cable
+///
+/// ```text
+/// 0x88 0x33 | command | compression | length (LE) | data | checksum (LE) | 0x00 0x00
+/// ```
+///
+/// The printer answers 0x00 until the last two bytes, its device id (0x81) and its status. Pages are
+/// cut whenever a print asks for a margin after the image, and written out as PNG files when an
+/// output directory is set.
+#[derive(Clone, Debug)]
+pub struct Printer {
+    state: State,
+    status: PrinterStatus,
+    busy_polls: u8,
+
+    // Packet being received
+    command: u8,
+    compressed: bool,
+    length: u16,
+    data: Vec<u8>,
+    checksum: u16,
+    received_checksum: u16,
+
+    // Tiles received since the last print
+    buffer: Vec<u8>,
+
+    // Paper fed since the last cut
+    paper: Vec<u8>,
+    pages: Vec<Page>,
+
+    output_dir: Option<PathBuf>,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self {
+            state: State::Magic(0),
+            status: PrinterStatus::empty(),
+            busy_polls: 0,
+
+            command: 0,
+            compressed: false,
+            length: 0,
+            data: Vec::new(),
+            checksum: 0,
+            received_checksum: 0,
+
+            buffer: Vec::new(),
+            paper: Vec::new(),
+            pages: Vec::new(),
+
+            output_dir: None,
+        }
+    }
+}
+
+impl Printer {
+    /// Printer writing each page as `print-NNN.png` into the directory
+    pub fn with_output_dir(dir: PathBuf) -> Self {
+        Self { output_dir: Some(dir), ..Self::default() }
+    }
+
+    /// Pages cut so far
+    pub fn pages(&self) -> &[Page] {
+        &self.pages
+    }
+
+    pub fn status(&self) -> PrinterStatus {
+        self.status
+    }
+
+    /// Cut the paper fed so far into a page, if any
+    pub fn cut(&mut self) {
+        if self.paper.is_empty() {
+            return;
+        }
+
+        let page = Page { pixels: std::mem::take(&mut self.paper) };
+        if let Some(dir) = &self.output_dir {
+            let path = dir.join(format!("print-{:03}.png", self.pages.len() + 1));
+            match std::fs::write(&path, page.to_png()) {
+                Ok(()) => info!("Printed {}", path.display()),
+                Err(err) => warn!("Failed to write {}: {}", path.display(), err),
+            }
+        }
+        self.pages.push(page);
+    }
+
+    fn feed(&mut self, lines: usize) {
+        self.paper.resize(self.paper.len() + lines * PAPER_WIDTH, 0);
+    }
+
+    fn receive(&mut self, data: u8) {
+        // The checksum covers the header and the data
+        if matches!(self.state, State::Command | State::Compression | State::Length(_) | State::Data) {
+            self.checksum = self.checksum.wrapping_add(data as u16);
+        }
+
+        self.state = match self.state {
+            State::Magic(i) if data != MAGIC[i] => State::Magic(if data == MAGIC[0] { 1 } else { 0 }),
+            State::Magic(0) => State::Magic(1),
+            State::Magic(_) => {
+                self.checksum = 0;
+                State::Command
+            }
+            State::Command => {
+                self.command = data;
+                State::Compression
+            }
+            State::Compression => {
+                self.compressed = data & 1 != 0;
+                State::Length(0)
+            }
+            State::Length(0) => {
+                self.length = data as u16;
+                State::Length(1)
+            }
+            State::Length(_) => {
+                self.length |= (data as u16) << 8;
+                self.data.clear();
+                if self.length == 0 { State::Checksum(0) } else { State::Data }
+            }
+            State::Data => {
+                self.data.push(data);
+                if self.data.len() == self.length as usize { State::Checksum(0) } else { State::Data }
+            }
+            State::Checksum(0) => {
+                self.received_checksum = data as u16;
+                State::Checksum(1)
+            }
+            State::Checksum(_) => {
+                self.received_checksum |= (data as u16) << 8;
+                if self.received_checksum == self.checksum {
+                    self.status.remove(PrinterStatus::CHECKSUM_ERROR);
+                    self.run_command();
+                } else {
+                    self.status.insert(PrinterStatus::CHECKSUM_ERROR);
+                }
+                State::DeviceId
+            }
+            State::DeviceId => State::Status,
+            State::Status => State::Magic(0),
+        };
+    }
+
+    fn run_command(&mut self) {
+        match self.command {
+            INIT => {
+                self.buffer.clear();
+                self.status = PrinterStatus::empty();
+            }
+            DATA => {
+                let data = if self.compressed { decompress(&self.data) } else { std::mem::take(&mut self.data) };
+                let free = BUFFER_SIZE - self.buffer.len();
+                self.buffer.extend_from_slice(&data[..data.len().min(free)]);
+
+                self.status.set(PrinterStatus::UNPROCESSED_DATA, !self.buffer.is_empty());
+                self.status.set(PrinterStatus::IMAGE_DATA_FULL, self.buffer.len() == BUFFER_SIZE);
+            }
+            PRINT if self.data.len() == 4 => {
+                // The last byte is the exposure, the darkness of the whole print
+                self.print(self.data[0], self.data[1], self.data[2]);
+                self.status.remove(PrinterStatus::UNPROCESSED_DATA | PrinterStatus::IMAGE_DATA_FULL);
+                self.status.insert(PrinterStatus::BUSY);
+                self.busy_polls = PRINT_BUSY_POLLS;
+            }
+            STATUS if self.busy_polls > 0 => {
+                self.busy_polls -= 1;
+                if self.busy_polls == 0 {
+                    self.status.remove(PrinterStatus::BUSY);
+                }
+            }
+            STATUS => { }
+            _ => self.status.insert(PrinterStatus::PACKET_ERROR),
+        }
+    }
+
+    /// Feed the margins around the image, colored with the palette (as BGP)
+    ///
+    /// Without sheets, only the paper is fed.
+    fn print(&mut self, sheets: u8, margins: u8, palette: u8) {
+        // Games leave the palette at zero for the default one
+        let palette = if palette == 0 { 0xE4 } else { palette };
+
+        self.feed((margins >> 4) as usize * MARGIN_LINES);
+
+        let buffer = std::mem::take(&mut self.buffer);
+        for tile_row in buffer.chunks_exact(TILES_PER_ROW * TILE_BYTES).filter(|_| sheets > 0) {
+            for line in 0..8 {
+                for tile in tile_row.chunks_exact(TILE_BYTES) {
+                    let lo = tile[line * 2];
+                    let hi = tile[line * 2 + 1];
+                    for bit in (0..8).rev() {
+                        let color = (hi >> bit & 1) << 1 | (lo >> bit & 1);
+                        self.paper.push(palette >> (color * 2) & 0x3);
+                    }
+                }
+            }
+        }
+
+        self.feed((margins & 0x0F) as usize * MARGIN_LINES);
+        if margins & 0x0F != 0 {
+            self.cut();
+        }
+    }
+}
+
+/// Run-length encoded data, runs start with their length, with the top bit set for a repeated byte
+fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let control = data[i];
+        if control & 0x80 != 0 {
+            let len = (control & 0x7F) as usize + 2;
+            if let Some(&byte) = data.get(i + 1) {
+                output.resize(output.len() + len, byte);
+            }
+            i += 2;
+        } else {
+            let len = control as usize + 1;
+            let end = (i + 1 + len).min(data.len());
+            output.extend_from_slice(&data[i + 1..end]);
+            i = end;
+        }
+    }
+    output
+}
+
+impl SerialDevice for Printer {
+    fn exchange(&mut self, data: u8) -> Option<u8> {
+        let reply = match self.state {
+            State::DeviceId => DEVICE_ID,
+            State::Status => self.status.bits(),
+            _ => 0x00,
+        };
+        self.receive(data);
+        Some(reply)
+    }
+
+    fn box_clone(&self) -> Box<dyn SerialDevice> { Box::new(self.clone()) }
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(command: u8, compression: u8, data: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x88, 0x33, command, compression];
+        packet.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        packet.extend_from_slice(data);
+
+        let checksum = packet[2..].iter().fold(0u16, |sum, &byte| sum.wrapping_add(byte as u16));
+        packet.extend_from_slice(&checksum.to_le_bytes());
+        packet.extend_from_slice(&[0x00, 0x00]);
+        packet
+    }
+
+    /// Send the packet, giving the device id and status answered
+    fn send(printer: &mut Printer, packet: &[u8]) -> (u8, u8) {
+        let replies: Vec<u8> = packet.iter().map(|&byte| printer.exchange(byte).unwrap()).collect();
+        assert!(replies[..replies.len() - 2].iter().all(|&reply| reply == 0x00));
+        (replies[replies.len() - 2], replies[replies.len() - 1])
+    }
+
+    /// Two tile rows, the first all color 1 and the second all color 3
+    fn tiles() -> Vec<u8> {
+        let mut tiles = vec![0x00; 2 * TILES_PER_ROW * TILE_BYTES];
+        for (i, byte) in tiles.iter_mut().enumerate() {
+            let second_row = i >= TILES_PER_ROW * TILE_BYTES;
+            let hi = i % 2 == 1;
+            *byte = if !hi || second_row { 0xFF } else { 0x00 };
+        }
+        tiles
+    }
+
+    #[test]
+    fn print_test() {
+        let mut printer = Printer::default();
+
+        assert_eq!((DEVICE_ID, 0x00), send(&mut printer, &packet(INIT, 0, &[])));
+        assert_eq!((DEVICE_ID, 0x08), send(&mut printer, &packet(DATA, 0, &tiles())));
+        assert_eq!((DEVICE_ID, 0x08), send(&mut printer, &packet(DATA, 0, &[])));
+        assert_eq!((DEVICE_ID, 0x02), send(&mut printer, &packet(PRINT, 0, &[1, 0x01, 0xE4, 0x40])));
+
+        // Busy for a while, then done
+        for _ in 1..PRINT_BUSY_POLLS {
+            assert_eq!((DEVICE_ID, 0x02), send(&mut printer, &packet(STATUS, 0, &[])));
+        }
+        assert_eq!((DEVICE_ID, 0x00), send(&mut printer, &packet(STATUS, 0, &[])));
+
+        assert_eq!(1, printer.pages().len());
+        let page = &printer.pages()[0];
+        assert_eq!(16 + MARGIN_LINES, page.height());
+        assert!(page.pixels[..8 * PAPER_WIDTH].iter().all(|&shade| shade == 1));
+        assert!(page.pixels[8 * PAPER_WIDTH..16 * PAPER_WIDTH].iter().all(|&shade| shade == 3));
+        assert!(page.pixels[16 * PAPER_WIDTH..].iter().all(|&shade| shade == 0));
+    }
+
+    #[test]
+    fn palette_test() {
+        let mut printer = Printer::default();
+        send(&mut printer, &packet(DATA, 0, &tiles()));
+        send(&mut printer, &packet(PRINT, 0, &[1, 0x00, 0x1B, 0x40]));
+
+        // Without a margin after, the paper is not cut yet
+        assert!(printer.pages().is_empty());
+        printer.cut();
+
+        let page = &printer.pages()[0];
+        assert_eq!(16, page.height());
+        assert_eq!(2, page.pixels[0]);
+        assert_eq!(0, page.pixels[8 * PAPER_WIDTH]);
+    }
+
+    #[test]
+    fn compression_test() {
+        assert_eq!(vec![0xAA, 0xAA, 0xAA, 0x01, 0x02], decompress(&[0x81, 0xAA, 0x01, 0x01, 0x02]));
+
+        let mut printer = Printer::default();
+        let compressed: Vec<u8> = tiles()
+            .chunks(TILES_PER_ROW * TILE_BYTES)
+            .flat_map(|row| {
+                let mut runs = Vec::new();
+                for pair in row.chunks(2) {
+                    runs.extend_from_slice(&[0x01, pair[0], pair[1]]);
+                }
+                runs
+            })
+            .collect();
+        send(&mut printer, &packet(DATA, 1, &compressed));
+        assert_eq!(tiles(), printer.buffer);
+    }
+
+    #[test]
+    fn checksum_error_test() {
+        let mut printer = Printer::default();
+        let mut data = packet(DATA, 0, &[0x12; 16]);
+        let len = data.len();
+        data[len - 4] ^= 0xFF;
+
+        assert_eq!((DEVICE_ID, 0x01), send(&mut printer, &data));
+        assert!(printer.buffer.is_empty());
+    }
+}
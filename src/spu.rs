@@ -1,30 +1,45 @@
+pub mod blip;
+pub mod channel;
+pub mod filter;
 pub mod flags;
 pub mod noise;
-pub mod sampler;
+pub mod note;
+pub mod sequencer;
 pub mod square;
 pub mod util;
 pub mod wave;
 
+use blip::StereoBuffer;
+use channel::Channel;
+use filter::Filter;
 use flags::*;
-use sampler::Sampler;
+use sequencer::{Clocks, FrameSequencer};
 use square::Square;
 use noise::Noise;
-use wave::Wave;
-use sdl2::audio::AudioQueue;
+use note::{Note, NoteEvent};
+use wave::{Wave, WAVE_RAM_SIZE};
 
 use packed_struct::prelude::*;
 
 use crate::MemoryBus;
+use crate::frontend::AudioSink;
 
-#[allow(dead_code)]
-#[derive(Default)]
+/// Host rate until the audio sink asks for another one
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+/// Four channels at full volume on a side (4 * 15 * 8) stay below i16::MAX
+const VOLUME_SCALE: i32 = 64;
+
+#[derive(Clone, Debug, Default)]
 pub struct Spu {
     enabled: bool,
 
     // SO2
+    left_vin: bool,
     left_volume: u8,
 
     // SO1
+    right_vin: bool,
     right_volume: u8,
 
     // TONE & SWEEP
@@ -38,18 +53,286 @@ pub struct Spu {
 
     // NOISE
     channel4: Noise,
+
+    sequencer: FrameSequencer,
+
+    // Channels left out of the mix, or the one mixed alone
+    muted: [bool; 4],
+    solo: Option<usize>,
+
+    // Mix resampled to the host rate, each channel apart as well when the sink wants stems
+    output: StereoBuffer,
+    stems: Option<Box<[StereoBuffer; 4]>>,
+    time: u32,
+
+    // Output capacitor and low-pass, run on the mix and on each stem apart
+    filter: Filter,
+    stem_filters: [Filter; 4],
+
+    // Note changes of the frame when the sink wants them, and the note each channel is on
+    notes: Option<Vec<NoteEvent>>,
+    channel_notes: [Option<Note>; 4],
 }
 
 impl Spu {
-    pub fn enqueue_audio_samples(&mut self,
-            channel1: &mut AudioQueue<i8>,
-            channel2: &mut AudioQueue<i8>,
-            channel3: &mut AudioQueue<i8>,
-            channel4: &mut AudioQueue<i8>) {
-        self.channel1.enqueue_audio_samples(channel1);
-        self.channel2.enqueue_audio_samples(channel2);
-        self.channel3.enqueue_audio_samples(channel3);
-        self.channel4.enqueue_audio_samples(channel4);
+    /// Run the channels along with the CPU, mixing each change of their output
+    pub fn step(&mut self, ticks: u64) {
+        // Register writes and the frame sequencer changed the output since the last step
+        self.update_amplitude();
+
+        let mut ticks = ticks as u32;
+        while ticks > 0 {
+            let run = [
+                self.channel1.next_event(),
+                self.channel2.next_event(),
+                self.channel3.next_event(),
+                self.channel4.next_event(),
+            ].iter().flatten().fold(ticks, |run, &event| run.min(event)).max(1);
+
+            self.channel1.step(run);
+            self.channel2.step(run);
+            self.channel3.step(run);
+            self.channel4.step(run);
+            self.time += run;
+            ticks -= run;
+
+            self.update_amplitude();
+        }
+    }
+
+    /// Clock the frame sequencer, on a falling edge of DIV bit 4
+    pub fn step_frame_sequencer(&mut self) {
+        let clocks = self.sequencer.step();
+        if clocks.contains(Clocks::LENGTH) {
+            self.channel1.step_length();
+            self.channel2.step_length();
+            self.channel3.step_length();
+            self.channel4.step_length();
+        }
+        if clocks.contains(Clocks::SWEEP) {
+            self.channel1.step_sweep();
+        }
+        if clocks.contains(Clocks::ENVELOPE) {
+            self.channel1.step_envelope();
+            self.channel2.step_envelope();
+            self.channel4.step_envelope();
+        }
+        self.update_notes(None);
+    }
+
+    /// Hand the samples of the frame to the sink, as interleaved stereo at its rate
+    pub fn end_frame(&mut self, sink: &mut dyn AudioSink) {
+        if sink.sample_rate() != self.output.sample_rate() {
+            self.set_sample_rate(sink.sample_rate());
+        }
+
+        let time = self.time;
+        self.time = 0;
+        let samples = self.output.end_frame(time);
+        self.filter.apply(samples);
+        sink.queue(samples);
+
+        if let Some(stems) = &mut self.stems {
+            let [square1, square2, wave, noise] = &mut **stems;
+            let [square1, square2, wave, noise] = [
+                square1.end_frame(time),
+                square2.end_frame(time),
+                wave.end_frame(time),
+                noise.end_frame(time),
+            ];
+            let [filter1, filter2, filter3, filter4] = &mut self.stem_filters;
+            filter1.apply(square1);
+            filter2.apply(square2);
+            filter3.apply(wave);
+            filter4.apply(noise);
+            sink.queue_stems([square1, square2, wave, noise]);
+        }
+
+        if let Some(notes) = &mut self.notes {
+            sink.queue_notes(notes, time);
+            notes.clear();
+        }
+
+        // Stems start with the next frame, from silence
+        if sink.wants_stems() != self.stems.is_some() {
+            self.set_stems(sink.wants_stems());
+        }
+
+        // Notes as well, the ones sounding already start it
+        if sink.wants_notes() != self.notes.is_some() {
+            self.notes = if sink.wants_notes() { Some(Vec::new()) } else { None };
+            self.channel_notes = [None; 4];
+            self.update_notes(None);
+        }
+    }
+
+    /// Resample to another host rate, dropping the samples of the current frame
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.output = StereoBuffer::new(sample_rate);
+        self.set_filter(self.filter.with_sample_rate(sample_rate));
+        if self.stems.is_some() {
+            self.set_stems(true);
+        }
+    }
+
+    /// Output capacitor charge kept each tick, the model sets it, 1 leaves the capacitor out
+    pub fn set_charge_factor(&mut self, charge_factor: f64) {
+        self.set_filter(Filter::new(self.filter.sample_rate(), charge_factor, self.filter.low_pass()));
+    }
+
+    /// Cut the output above a frequency in Hz, or leave it as is
+    pub fn set_low_pass(&mut self, cutoff: Option<u32>) {
+        self.set_filter(Filter::new(self.filter.sample_rate(), self.filter.charge_factor(), cutoff));
+    }
+
+    /// Leave a channel (0-3) out of the mix, its stem still plays
+    pub fn set_muted(&mut self, channel: usize, muted: bool) {
+        self.muted[channel] = muted;
+    }
+
+    pub fn is_muted(&self, channel: usize) -> bool {
+        self.muted[channel]
+    }
+
+    /// Mix a single channel (0-3), muted or not, or all the unmuted ones again
+    pub fn set_solo(&mut self, channel: Option<usize>) {
+        self.solo = channel;
+    }
+
+    pub fn solo(&self) -> Option<usize> {
+        self.solo
+    }
+
+    /// Whether the channel makes it into the mix
+    pub fn is_audible(&self, channel: usize) -> bool {
+        match self.solo {
+            Some(solo) => solo == channel,
+            None => !self.muted[channel],
+        }
+    }
+
+    /// Start over from a discharged filter, on the mix and the stems alike
+    fn set_filter(&mut self, filter: Filter) {
+        self.stem_filters = [filter.aligned(), filter.aligned(), filter.aligned(), filter.aligned()];
+        self.filter = filter;
+    }
+
+    /// Render each channel apart as well
+    fn set_stems(&mut self, enable: bool) {
+        self.stems = if enable {
+            Some(Box::new([
+                self.output.aligned(),
+                self.output.aligned(),
+                self.output.aligned(),
+                self.output.aligned(),
+            ]))
+        } else {
+            None
+        };
+        self.stem_filters = [self.filter.aligned(), self.filter.aligned(), self.filter.aligned(), self.filter.aligned()];
+    }
+
+    /// Log the channels whose note changed, while the sink wants them
+    fn update_notes(&mut self, trigger: Option<usize>) {
+        if self.notes.is_none() {
+            return;
+        }
+
+        let notes = self.sounding_notes();
+        if let Some(events) = &mut self.notes {
+            for (channel, (&note, playing)) in notes.iter().zip(self.channel_notes.iter_mut()).enumerate() {
+                let trigger = trigger == Some(channel) && note.is_some();
+                if note != *playing || trigger {
+                    events.push(NoteEvent { time: self.time, channel, note, trigger });
+                    *playing = note;
+                }
+            }
+        }
+    }
+
+    /// Note of each channel while it is heard, muted or not
+    fn sounding_notes(&self) -> [Option<Note>; 4] {
+        let note = |playing: bool, frequency: u32, volume: u8| {
+            if playing && volume > 0 { Some(Note { frequency, volume }) } else { None }
+        };
+        [
+            note(self.channel1.is_playing(), self.channel1.frequency(), self.channel1.volume()),
+            note(self.channel2.is_playing(), self.channel2.frequency(), self.channel2.volume()),
+            note(self.channel3.is_playing(), self.channel3.frequency(), self.channel3.volume()),
+            note(self.channel4.is_playing(), self.channel4.frequency(), self.channel4.volume()),
+        ]
+    }
+
+    /// Clear every register but wave RAM, they stay cleared until powered on again
+    fn power_off(&mut self) {
+        let ram = self.channel3.ram;
+
+        self.left_vin = false;
+        self.left_volume = 0;
+        self.right_vin = false;
+        self.right_volume = 0;
+        self.channel1 = Square::default();
+        self.channel2 = Square::default();
+        self.channel3 = Wave::default();
+        self.channel4 = Noise::default();
+
+        self.channel3.ram = ram;
+    }
+
+    /// While channel 3 plays, wave RAM accesses land on the byte it is playing
+    fn wave_ram_index(&self, addr: u16) -> usize {
+        if self.channel3.is_playing() {
+            self.channel3.position() / 2
+        } else {
+            (addr - WAVE_RAM_START) as usize
+        }
+    }
+
+    fn update_amplitude(&mut self) {
+        let channels = self.channel_amplitudes();
+        let mix = channels.iter().enumerate()
+            .filter(|&(channel, _)| self.is_audible(channel))
+            .fold((0, 0), |mix, (_, amplitude)| (mix.0 + amplitude.0, mix.1 + amplitude.1));
+        self.output.set_amplitude(self.time, mix);
+
+        if let Some(stems) = &mut self.stems {
+            for (stem, &channel) in stems.iter_mut().zip(channels.iter()) {
+                stem.set_amplitude(self.time, channel);
+            }
+        }
+    }
+
+    /// Each channel panned by NR51, then scaled by the NR50 master volume
+    fn channel_amplitudes(&self) -> [(i32, i32); 4] {
+        let channels: [(&dyn Channel, bool, bool); 4] = [
+            (&self.channel1, self.channel1.left_enable, self.channel1.right_enable),
+            (&self.channel2, self.channel2.left_enable, self.channel2.right_enable),
+            (&self.channel3, self.channel3.left_enable, self.channel3.right_enable),
+            (&self.channel4, self.channel4.left_enable, self.channel4.right_enable),
+        ];
+
+        let left_volume = (self.left_volume as i32 + 1) * VOLUME_SCALE;
+        let right_volume = (self.right_volume as i32 + 1) * VOLUME_SCALE;
+        let mut amplitudes = [(0, 0); 4];
+        for (amplitude, (channel, left_enable, right_enable)) in amplitudes.iter_mut().zip(channels.iter()) {
+            let output = dac(*channel);
+            if *left_enable {
+                amplitude.0 = output * left_volume;
+            }
+            if *right_enable {
+                amplitude.1 = output * right_volume;
+            }
+        }
+        amplitudes
+    }
+}
+
+/// Digital output (0-15) on a linear -15..=15 scale, nothing while the DAC is off
+fn dac(channel: &dyn Channel) -> i32 {
+    if channel.dac_enabled() {
+        channel.output() as i32 * 2 - 15
+    } else {
+        0
     }
 }
 
@@ -64,7 +347,6 @@ const NR22: u16 = 0xFF17;
 const NR23: u16 = 0xFF18;
 const NR24: u16 = 0xFF19;
 
-/*
 const NR30: u16 = 0xFF1A;
 const NR31: u16 = 0xFF1B;
 const NR32: u16 = 0xFF1C;
@@ -75,20 +357,81 @@ const NR41: u16 = 0xFF20;
 const NR42: u16 = 0xFF21;
 const NR43: u16 = 0xFF22;
 const NR44: u16 = 0xFF23;
-*/
 
 const NR50: u16 = 0xFF24;
 const NR51: u16 = 0xFF25;
 const NR52: u16 = 0xFF26;
 
+const WAVE_RAM_START: u16 = 0xFF30;
+const WAVE_RAM_END: u16 = WAVE_RAM_START + WAVE_RAM_SIZE as u16 - 1;
+
 impl MemoryBus for Spu {
+    /// Registers read back with their unused and write-only bits set
     fn read(&self, addr: u16) -> u8 {
         match addr {
-            _ => 0
+            NR10 => self.channel1.r0() | 0x80,
+            NR11 => self.channel1.r1() | 0x3F,
+            NR12 => self.channel1.r2(),
+            NR14 => self.channel1.r4() | 0xBF,
+
+            NR21 => self.channel2.r1() | 0x3F,
+            NR22 => self.channel2.r2(),
+            NR24 => self.channel2.r4() | 0xBF,
+
+            NR30 => self.channel3.r0() | 0x7F,
+            NR32 => self.channel3.r2() | 0x9F,
+            NR34 => self.channel3.r4() | 0xBF,
+
+            NR42 => self.channel4.r2(),
+            NR43 => self.channel4.r3(),
+            NR44 => self.channel4.r4() | 0xBF,
+
+            NR50 => {
+                let r = MasterVolumeControl {
+                    left_channel_5_enable: self.left_vin,
+                    left_volume: self.left_volume,
+                    right_channel_5_enable: self.right_vin,
+                    right_volume: self.right_volume,
+                };
+                r.pack().unwrap()[0]
+            }
+            NR51 => {
+                let r = MasterOutputControl {
+                    left_channel_4_enable: self.channel4.left_enable,
+                    left_channel_3_enable: self.channel3.left_enable,
+                    left_channel_2_enable: self.channel2.left_enable,
+                    left_channel_1_enable: self.channel1.left_enable,
+
+                    right_channel_4_enable: self.channel4.right_enable,
+                    right_channel_3_enable: self.channel3.right_enable,
+                    right_channel_2_enable: self.channel2.right_enable,
+                    right_channel_1_enable: self.channel1.right_enable,
+                };
+                r.pack().unwrap()[0]
+            }
+            NR52 => {
+                let mut r = MasterOnOffControl::default();
+                r.all_channels_enable = self.enabled;
+                r.channel_4_enable = self.channel4.is_playing();
+                r.channel_3_enable = self.channel3.is_playing();
+                r.channel_2_enable = self.channel2.is_playing();
+                r.channel_1_enable = self.channel1.is_playing();
+                r.pack().unwrap()[0] | 0x70
+            }
+
+            WAVE_RAM_START..=WAVE_RAM_END => self.channel3.ram[self.wave_ram_index(addr)],
+
+            // NRx3 frequencies and NRx1 lengths are write-only, the rest is unmapped
+            _ => 0xFF
         }
     }
 
     fn write(&mut self, addr: u16, data: u8) {
+        // Powered off, only NR52 and wave RAM can be written
+        if !self.enabled && !matches!(addr, NR52 | WAVE_RAM_START..=WAVE_RAM_END) {
+            return;
+        }
+
         match addr {
             NR10 => self.channel1.set_r0(data),
             NR11 => self.channel1.set_r1(data),
@@ -101,10 +444,28 @@ impl MemoryBus for Spu {
             NR23 => self.channel2.set_r3(data),
             NR24 => self.channel2.set_r4(data),
 
+            NR30 => self.channel3.set_r0(data),
+            NR31 => self.channel3.set_r1(data),
+            NR32 => self.channel3.set_r2(data),
+            NR33 => self.channel3.set_r3(data),
+            NR34 => self.channel3.set_r4(data),
+
+            NR41 => self.channel4.set_r1(data),
+            NR42 => self.channel4.set_r2(data),
+            NR43 => self.channel4.set_r3(data),
+            NR44 => self.channel4.set_r4(data),
+
+            WAVE_RAM_START..=WAVE_RAM_END => {
+                let index = self.wave_ram_index(addr);
+                self.channel3.ram[index] = data;
+            }
+
             NR50 => {
                 let data: [u8; 1] = [data];
                 let r = MasterVolumeControl::unpack(&data).unwrap();
+                self.left_vin = r.left_channel_5_enable;
                 self.left_volume = r.left_volume;
+                self.right_vin = r.right_channel_5_enable;
                 self.right_volume = r.right_volume;
             }
             NR51 => {
@@ -124,9 +485,251 @@ impl MemoryBus for Spu {
             NR52 => {
                 let data: [u8; 1] = [data];
                 let r = MasterOnOffControl::unpack(&data).unwrap();
+                if self.enabled && !r.all_channels_enable {
+                    self.power_off();
+                } else if !self.enabled && r.all_channels_enable {
+                    self.sequencer.reset();
+                }
                 self.enabled = r.all_channels_enable;
             }
             _ => { }
         }
+
+        // A trigger starts the note over, even when it stays the same
+        let trigger = match addr {
+            NR14 => Some(0),
+            NR24 => Some(1),
+            NR34 => Some(2),
+            NR44 => Some(3),
+            _ => None,
+        };
+        self.update_notes(trigger.filter(|_| data & 0x80 != 0));
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use filter::{CGB_CHARGE_FACTOR, DMG_CHARGE_FACTOR};
+
+    /// Sink keeping whatever is queued
+    #[derive(Default)]
+    struct Capture {
+        samples: Vec<i16>,
+    }
+
+    impl AudioSink for Capture {
+        fn sample_rate(&self) -> u32 { 48_000 }
+        fn queue(&mut self, samples: &[i16]) { self.samples.extend_from_slice(samples) }
+    }
+
+    #[test]
+    fn mix_test() {
+        // Channel 2 at 1048Hz, left only, at full master volume
+        let mut spu = Spu::default();
+        spu.set_sample_rate(48_000);
+        spu.write(NR52, 0x80);
+        spu.write(NR50, 0x77);
+        spu.write(NR51, 0x20);
+        spu.write(NR21, 0x80);
+        spu.write(NR22, 0xF0);
+        spu.write(NR23, 0x83);
+        spu.write(NR24, 0x87);
+
+        let mut capture = Capture::default();
+        for _ in 0..60 {
+            spu.step(crate::TICKS_PER_FRAME);
+            spu.end_frame(&mut capture);
+        }
+
+        // Resampled to the sink rate, a second of interleaved stereo
+        assert_eq!(47_999, capture.samples.len() / 2);
+        let left: Vec<i16> = capture.samples.iter().step_by(2).cloned().collect();
+        let right: Vec<i16> = capture.samples.iter().skip(1).step_by(2).cloned().collect();
+        assert!(right.iter().all(|&s| s == 0));
+
+        let high = 15 * 8 * VOLUME_SCALE as i16;
+        assert!(left.iter().any(|&s| s >= high - 100));
+        assert!(left.iter().any(|&s| s <= -high + 100));
+
+        // A rising edge every period
+        let edges = left.windows(2).filter(|w| w[0] < 0 && w[1] >= 0).count();
+        assert!((1045..=1055).contains(&edges), "{} edges", edges);
+    }
+
+    #[test]
+    fn high_pass_test() {
+        // Channel 2 DAC on at volume 0, its offset only
+        fn offset(charge_factor: f64) -> Vec<i16> {
+            let mut spu = Spu::default();
+            spu.set_sample_rate(48_000);
+            spu.set_charge_factor(charge_factor);
+            spu.write(NR52, 0x80);
+            spu.write(NR50, 0x77);
+            spu.write(NR51, 0x22);
+            spu.write(NR22, 0x08);
+            spu.write(NR24, 0x80);
+
+            let mut capture = Capture::default();
+            for _ in 0..30 {
+                spu.step(crate::TICKS_PER_FRAME);
+                spu.end_frame(&mut capture);
+            }
+            capture.samples
+        }
+
+        let offset_level = -15 * 8 * VOLUME_SCALE as i16;
+        let raw = offset(1.0);
+        assert!(raw[1000..].iter().all(|&s| s == offset_level));
+
+        // The capacitor takes it out, faster on CGB
+        let dmg = offset(DMG_CHARGE_FACTOR);
+        let cgb = offset(CGB_CHARGE_FACTOR);
+        assert!(dmg[..100].iter().any(|&s| s < offset_level / 2));
+        assert!(dmg[10_000..].iter().all(|&s| s == 0));
+        assert!(cgb[500].abs() < dmg[500].abs());
+    }
+
+    #[test]
+    fn mute_test() {
+        /// Sink keeping the mix and the stems
+        #[derive(Default)]
+        struct Stems {
+            mix: Vec<i16>,
+            stems: [Vec<i16>; 4],
+        }
+
+        impl AudioSink for Stems {
+            fn sample_rate(&self) -> u32 { 48_000 }
+            fn queue(&mut self, samples: &[i16]) { self.mix.extend_from_slice(samples) }
+            fn wants_stems(&self) -> bool { true }
+            fn queue_stems(&mut self, stems: [&[i16]; 4]) {
+                for (stem, samples) in self.stems.iter_mut().zip(stems.iter()) {
+                    stem.extend_from_slice(samples);
+                }
+            }
+        }
+
+        // Mix of the next frames, against the sum of the stems mixed, past the previous frame tail
+        fn check(spu: &mut Spu, mixed: &[usize]) {
+            let mut sink = Stems::default();
+            for _ in 0..3 {
+                spu.step(crate::TICKS_PER_FRAME);
+                spu.end_frame(&mut sink);
+            }
+            assert!(sink.stems[0].iter().any(|&s| s != 0));
+            assert!(sink.stems[1].iter().any(|&s| s != 0));
+            for (i, &sample) in sink.mix.iter().enumerate().skip(32) {
+                let sum: i32 = mixed.iter().map(|&channel| sink.stems[channel][i] as i32).sum();
+                assert!((sample as i32 - sum).abs() <= 4, "{}: {} != {}", i, sample, sum);
+            }
+        }
+
+        // Both squares playing, without the capacitor which starts over as channels leave the mix
+        let mut spu = Spu::default();
+        spu.set_sample_rate(48_000);
+        spu.set_charge_factor(1.0);
+        spu.write(NR52, 0x80);
+        spu.write(NR50, 0x77);
+        spu.write(NR51, 0x33);
+        spu.write(NR12, 0xF0);
+        spu.write(NR14, 0x87);
+        spu.write(NR22, 0xF0);
+        spu.write(NR24, 0x86);
+        spu.step(crate::TICKS_PER_FRAME);
+        spu.end_frame(&mut Stems::default());
+        check(&mut spu, &[0, 1]);
+
+        // Muted, channel 1 leaves the mix but keeps its stem
+        spu.set_muted(0, true);
+        assert!(spu.is_muted(0));
+        assert!(!spu.is_audible(0));
+        check(&mut spu, &[1]);
+
+        spu.set_muted(1, true);
+        check(&mut spu, &[]);
+
+        // Solo takes over the muted channels
+        spu.set_solo(Some(0));
+        assert!(spu.is_audible(0));
+        assert!(!spu.is_audible(1));
+        check(&mut spu, &[0]);
+
+        spu.set_solo(None);
+        spu.set_muted(0, false);
+        spu.set_muted(1, false);
+        check(&mut spu, &[0, 1]);
+    }
+
+    #[test]
+    fn read_mask_test() {
+        let mut spu = Spu::default();
+        spu.write(NR52, 0x80);
+
+        const MASKS: [u8; 0x20] = [
+            0x80, 0x3F, 0x00, 0xFF, 0xBF, // NR10-NR14
+            0xFF, 0x3F, 0x00, 0xFF, 0xBF, // NR20-NR24
+            0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30-NR34
+            0xFF, 0xFF, 0x00, 0x00, 0xBF, // NR40-NR44
+            0x00, 0x00, 0xF0,             // NR50-NR52
+            0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        ];
+        for (addr, &mask) in (0xFF10..).zip(MASKS.iter()) {
+            assert_eq!(mask, spu.read(addr), "{:04X}", addr);
+        }
+
+        // Readable bits come back as written
+        for &(addr, data) in &[(NR10, 0x7F), (NR11, 0xC0), (NR12, 0xF3), (NR14, 0x40), (NR32, 0x60), (NR43, 0xAB), (NR50, 0xF7), (NR51, 0xA5)] {
+            spu.write(addr, data);
+            assert_eq!(data | MASKS[(addr - NR10) as usize], spu.read(addr), "{:04X}", addr);
+        }
+    }
+
+    #[test]
+    fn status_test() {
+        let mut spu = Spu::default();
+        spu.write(NR52, 0x80);
+        spu.write(NR12, 0xF0);
+        spu.write(NR14, 0x80);
+        spu.write(NR30, 0x80);
+        spu.write(NR34, 0x80);
+        assert_eq!(0xF5, spu.read(NR52));
+
+        // Length expired
+        spu.write(NR11, 0x3F);
+        spu.write(NR14, 0xC0);
+        spu.step_frame_sequencer();
+        assert_eq!(0xF4, spu.read(NR52));
+
+        // DAC off
+        spu.write(NR30, 0x00);
+        assert_eq!(0xF0, spu.read(NR52));
+    }
+
+    #[test]
+    fn power_test() {
+        let mut spu = Spu::default();
+        spu.write(NR52, 0x80);
+        spu.write(NR50, 0x77);
+        spu.write(NR12, 0xF0);
+        spu.write(NR14, 0x80);
+        spu.write(WAVE_RAM_START, 0x12);
+
+        // Powering off clears the registers but wave RAM
+        spu.write(NR52, 0x00);
+        assert_eq!(0x70, spu.read(NR52));
+        assert_eq!(0x00, spu.read(NR50));
+        assert_eq!(0x00, spu.read(NR12));
+        assert_eq!(0x12, spu.read(WAVE_RAM_START));
+
+        // And ignores writes until powered on again
+        spu.write(NR50, 0x77);
+        assert_eq!(0x00, spu.read(NR50));
+        spu.write(WAVE_RAM_START, 0x34);
+        assert_eq!(0x34, spu.read(WAVE_RAM_START));
+
+        spu.write(NR52, 0x80);
+        spu.write(NR50, 0x77);
+        assert_eq!(0x77, spu.read(NR50));
+    }
+}
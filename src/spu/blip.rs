@@ -0,0 +1,287 @@
+//! Band-limited step synthesis, resampling amplitude changes at CPU ticks to the host rate
+//!
+//! Each change of the output adds a windowed sinc impulse at its exact position between two host
+//! samples, the output is the running sum of those impulses. Steps come out band-limited, so square
+//! waves at any frequency neither alias nor jitter from one sample to the next.
+
+use crate::TICKS_PER_SECOND;
+
+/// Positions between two samples the impulse is computed at
+const PHASE_BITS: u32 = 6;
+const PHASES: usize = 1 << PHASE_BITS;
+
+/// Samples on each side of the impulse
+const HALF_WIDTH: usize = 8;
+const WIDTH: usize = HALF_WIDTH * 2;
+
+/// Fractional bits of the sample position
+const FRAC_BITS: u32 = 32;
+
+/// Fractional bits of the impulse taps
+const TAP_BITS: u32 = 15;
+
+/// Cutoff below the host Nyquist frequency, leaving room for the window roll-off
+const CUTOFF: f64 = 0.9;
+
+#[derive(Clone, Debug)]
+pub struct BlipBuffer {
+    sample_rate: u32,
+
+    // Samples per tick, and the position of the frame start
+    factor: u64,
+    offset: u64,
+
+    kernel: Box<[[i32; WIDTH]; PHASES]>,
+    buffer: Vec<i64>,
+    integrator: i64,
+}
+
+impl Default for BlipBuffer {
+    fn default() -> Self {
+        Self::new(super::DEFAULT_SAMPLE_RATE)
+    }
+}
+
+impl BlipBuffer {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            factor: ((sample_rate as u64) << FRAC_BITS) / TICKS_PER_SECOND,
+            offset: 0,
+            kernel: kernel(),
+            buffer: Vec::new(),
+            integrator: 0,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Silent buffer at the same rate, whose samples line up with the ones of this buffer
+    pub fn aligned(&self) -> Self {
+        Self {
+            sample_rate: self.sample_rate,
+            factor: self.factor,
+            offset: self.offset,
+            kernel: self.kernel.clone(),
+            buffer: Vec::new(),
+            integrator: 0,
+        }
+    }
+
+    /// Change the amplitude by `delta`, `time` ticks into the frame
+    pub fn add_delta(&mut self, time: u32, delta: i32) {
+        let position = self.offset + time as u64 * self.factor;
+        let index = (position >> FRAC_BITS) as usize;
+        let phase = ((position >> (FRAC_BITS - PHASE_BITS)) as usize) & (PHASES - 1);
+
+        if self.buffer.len() < index + WIDTH {
+            self.buffer.resize(index + WIDTH, 0);
+        }
+        for (sample, &tap) in self.buffer[index..index + WIDTH].iter_mut().zip(self.kernel[phase].iter()) {
+            *sample += tap as i64 * delta as i64;
+        }
+    }
+
+    /// Close the frame after `time` ticks, its samples become available
+    pub fn end_frame(&mut self, time: u32) {
+        self.offset += time as u64 * self.factor;
+    }
+
+    /// Complete samples, the last impulses spread over the next frame
+    pub fn samples_available(&self) -> usize {
+        (self.offset >> FRAC_BITS) as usize
+    }
+
+    /// Take the available samples into every `stride`th one of `out`, 2 for interleaved stereo
+    pub fn read_samples(&mut self, out: &mut [i16], stride: usize) -> usize {
+        let count = self.samples_available().min(out.len().div_ceil(stride));
+        if self.buffer.len() < count + WIDTH {
+            self.buffer.resize(count + WIDTH, 0);
+        }
+
+        for (delta, sample) in self.buffer.drain(..count).zip(out.iter_mut().step_by(stride)) {
+            self.integrator += delta;
+            *sample = (self.integrator >> TAP_BITS).clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+        }
+        self.offset -= (count as u64) << FRAC_BITS;
+        count
+    }
+
+    /// Drop everything, back to silence
+    pub fn clear(&mut self) {
+        self.offset = 0;
+        self.buffer.clear();
+        self.integrator = 0;
+    }
+}
+
+/// Left and right buffers following a stereo amplitude, read out interleaved
+#[derive(Clone, Debug, Default)]
+pub struct StereoBuffer {
+    left: BlipBuffer,
+    right: BlipBuffer,
+    amplitude: (i32, i32),
+    samples: Vec<i16>,
+}
+
+impl StereoBuffer {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            left: BlipBuffer::new(sample_rate),
+            right: BlipBuffer::new(sample_rate),
+            amplitude: (0, 0),
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.left.sample_rate()
+    }
+
+    /// Silent buffer at the same rate, whose samples line up with the ones of this buffer
+    pub fn aligned(&self) -> Self {
+        Self {
+            left: self.left.aligned(),
+            right: self.right.aligned(),
+            amplitude: (0, 0),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Move to the amplitude, `time` ticks into the frame
+    pub fn set_amplitude(&mut self, time: u32, amplitude: (i32, i32)) {
+        if amplitude.0 != self.amplitude.0 {
+            self.left.add_delta(time, amplitude.0 - self.amplitude.0);
+        }
+        if amplitude.1 != self.amplitude.1 {
+            self.right.add_delta(time, amplitude.1 - self.amplitude.1);
+        }
+        self.amplitude = amplitude;
+    }
+
+    /// Close the frame after `time` ticks, giving its samples as interleaved stereo
+    pub fn end_frame(&mut self, time: u32) -> &mut [i16] {
+        self.left.end_frame(time);
+        self.right.end_frame(time);
+
+        self.samples.clear();
+        self.samples.resize(self.left.samples_available() * 2, 0);
+        self.left.read_samples(&mut self.samples, 2);
+        self.right.read_samples(&mut self.samples[1..], 2);
+        &mut self.samples
+    }
+}
+
+/// Blackman windowed sinc impulses at each phase, each summing to exactly 1 << TAP_BITS
+fn kernel() -> Box<[[i32; WIDTH]; PHASES]> {
+    let mut kernel = Box::new([[0; WIDTH]; PHASES]);
+    for (phase, taps) in kernel.iter_mut().enumerate() {
+        let frac = phase as f64 / PHASES as f64;
+        let impulse: Vec<f64> = (0..WIDTH).map(|i| {
+            let x = i as f64 - (HALF_WIDTH - 1) as f64 - frac;
+            let sinc = if x == 0.0 {
+                CUTOFF
+            } else {
+                (std::f64::consts::PI * x * CUTOFF).sin() / (std::f64::consts::PI * x)
+            };
+            let w = std::f64::consts::PI * x / HALF_WIDTH as f64;
+            let window = 0.42 + 0.5 * w.cos() + 0.08 * (2.0 * w).cos();
+            sinc * window
+        }).collect();
+
+        let sum: f64 = impulse.iter().sum();
+        for (tap, value) in taps.iter_mut().zip(impulse.iter()) {
+            *tap = (value / sum * (1 << TAP_BITS) as f64).round() as i32;
+        }
+
+        // Rounding error on the center tap, a step must settle on its exact height
+        let error = (1 << TAP_BITS) - taps.iter().sum::<i32>();
+        taps[HALF_WIDTH - 1] += error;
+    }
+    kernel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(blip: &mut BlipBuffer) -> Vec<i16> {
+        let mut samples = vec![0; blip.samples_available()];
+        let count = blip.read_samples(&mut samples, 1);
+        samples.truncate(count);
+        samples
+    }
+
+    #[test]
+    fn kernel_test() {
+        for taps in kernel().iter() {
+            assert_eq!(1 << TAP_BITS, taps.iter().sum::<i32>());
+        }
+    }
+
+    #[test]
+    fn step_test() {
+        // A second of ticks gives a second of samples
+        let mut blip = BlipBuffer::new(48_000);
+        blip.add_delta(1000, 10_000);
+        blip.end_frame(TICKS_PER_SECOND as u32);
+        assert_eq!(48_000, blip.samples_available());
+
+        // Rises around the step and settles on its height
+        let samples = read_all(&mut blip);
+        assert_eq!(0, samples[0]);
+        assert_eq!(10_000, samples[100]);
+        assert_eq!(10_000, samples[47_999]);
+    }
+
+    #[test]
+    fn frame_test() {
+        // Frames carry the fractional sample over
+        let mut blip = BlipBuffer::new(44_100);
+        let mut total = 0;
+        for _ in 0..60 {
+            blip.end_frame(crate::TICKS_PER_FRAME as u32);
+            total += read_all(&mut blip).len();
+        }
+        assert!((44_090..=44_100).contains(&total));
+    }
+
+    #[test]
+    fn square_test() {
+        // 1kHz square wave, the band-limited ringing stays bounded
+        let mut blip = BlipBuffer::new(44_100);
+        let half_period = (TICKS_PER_SECOND / 2000) as u32;
+        let mut delta = 1000;
+        for i in 0..100 {
+            blip.add_delta(i * half_period, delta);
+            delta = if i % 2 == 0 { -2000 } else { 2000 };
+        }
+        blip.end_frame(100 * half_period);
+
+        let samples = read_all(&mut blip);
+        assert!(samples.iter().all(|&s| s.abs() <= 1300));
+        assert!(samples[20..].iter().any(|&s| s >= 950));
+        assert!(samples[20..].iter().any(|&s| s <= -950));
+    }
+
+    #[test]
+    fn stereo_test() {
+        let mut left = BlipBuffer::new(44_100);
+        let mut right = BlipBuffer::new(44_100);
+        left.add_delta(0, 100);
+        right.add_delta(0, -100);
+        left.end_frame(10_000);
+        right.end_frame(10_000);
+
+        let mut samples = vec![0; left.samples_available() * 2];
+        left.read_samples(&mut samples, 2);
+        right.read_samples(&mut samples[1..], 2);
+        assert_eq!(&[100, -100], &samples[samples.len() - 2..]);
+
+        let mut stereo = StereoBuffer::new(44_100);
+        stereo.set_amplitude(0, (100, -100));
+        assert_eq!(samples, stereo.end_frame(10_000));
+    }
+}
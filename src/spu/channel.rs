@@ -0,0 +1,13 @@
+/// Sound channel clocked by CPU ticks
+pub trait Channel {
+    /// Ticks until the frequency timer next changes the output, None while it is stopped
+    fn next_event(&self) -> Option<u32>;
+
+    /// Run the frequency timer
+    fn step(&mut self, ticks: u32);
+
+    /// Digital output, 0-15
+    fn output(&self) -> u8;
+
+    fn dac_enabled(&self) -> bool;
+}
@@ -0,0 +1,130 @@
+use crate::TICKS_PER_SECOND;
+
+/// Share of the output capacitor charge kept each tick
+pub const DMG_CHARGE_FACTOR: f64 = 0.999958;
+pub const CGB_CHARGE_FACTOR: f64 = 0.998943;
+
+/// Analog stages between the mixer and the output jack, run on interleaved stereo samples
+///
+/// The capacitor is a high-pass taking out the offset of the DACs, which is there whenever one is
+/// on, playing or not. The low-pass stands in for the speaker or headphones, off by default.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    sample_rate: u32,
+    charge_factor: f64,
+    low_pass: Option<u32>,
+
+    // Charge kept and share of the input taken each sample, from the settings above
+    charge: f32,
+    smoothing: f32,
+
+    capacitor: [f32; 2],
+    smoothed: [f32; 2],
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::new(super::DEFAULT_SAMPLE_RATE, DMG_CHARGE_FACTOR, None)
+    }
+}
+
+impl Filter {
+    /// A charge factor of 1 leaves the capacitor out, the low-pass cuts above its frequency in Hz
+    pub fn new(sample_rate: u32, charge_factor: f64, low_pass: Option<u32>) -> Self {
+        let ticks_per_sample = TICKS_PER_SECOND as f64 / sample_rate as f64;
+        let smoothing = match low_pass {
+            Some(cutoff) => 1.0 - (-2.0 * std::f64::consts::PI * cutoff as f64 / sample_rate as f64).exp(),
+            None => 1.0,
+        };
+        Self {
+            sample_rate,
+            charge_factor,
+            low_pass,
+            charge: charge_factor.powf(ticks_per_sample) as f32,
+            smoothing: smoothing as f32,
+            capacitor: [0.0; 2],
+            smoothed: [0.0; 2],
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn charge_factor(&self) -> f64 {
+        self.charge_factor
+    }
+
+    pub fn low_pass(&self) -> Option<u32> {
+        self.low_pass
+    }
+
+    /// Same settings at another rate, starting discharged
+    pub fn with_sample_rate(&self, sample_rate: u32) -> Self {
+        Self::new(sample_rate, self.charge_factor, self.low_pass)
+    }
+
+    /// Same settings, starting discharged
+    pub fn aligned(&self) -> Self {
+        self.with_sample_rate(self.sample_rate)
+    }
+
+    pub fn apply(&mut self, samples: &mut [i16]) {
+        for pair in samples.chunks_mut(2) {
+            for (side, sample) in pair.iter_mut().enumerate() {
+                let input = *sample as f32;
+                let output = input - self.capacitor[side];
+                self.capacitor[side] = input - output * self.charge;
+
+                self.smoothed[side] += (output - self.smoothed[side]) * self.smoothing;
+                *sample = self.smoothed[side].round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn high_pass_test() {
+        // A DAC turned on and left silent, its offset fades out
+        let mut filter = Filter::new(48_000, DMG_CHARGE_FACTOR, None);
+        let mut samples = vec![-1000; 2 * 48_000];
+        filter.apply(&mut samples);
+        assert_eq!(&[-1000, -1000], &samples[..2]);
+        assert!(samples[2 * 4800..].iter().all(|&s| s == 0));
+
+        // Faster on CGB
+        let mut dmg = Filter::new(48_000, DMG_CHARGE_FACTOR, None);
+        let mut cgb = Filter::new(48_000, CGB_CHARGE_FACTOR, None);
+        let mut dmg_samples = vec![-1000; 200];
+        let mut cgb_samples = vec![-1000; 200];
+        dmg.apply(&mut dmg_samples);
+        cgb.apply(&mut cgb_samples);
+        assert!(cgb_samples[198] > dmg_samples[198]);
+
+        // Left out
+        let mut filter = Filter::new(48_000, 1.0, None);
+        let mut samples = vec![-1000, 500, -1000, 500];
+        filter.apply(&mut samples);
+        assert_eq!(vec![-1000, 500, -1000, 500], samples);
+    }
+
+    #[test]
+    fn low_pass_test() {
+        // Alternating samples at the Nyquist rate, cut well below
+        let mut filter = Filter::new(48_000, 1.0, Some(1000));
+        let mut samples: Vec<i16> = (0..2000).map(|i| if i / 2 % 2 == 0 { 8000 } else { -8000 }).collect();
+        filter.apply(&mut samples);
+        assert!(samples[1000..].iter().all(|&s| s.abs() < 1000));
+
+        // A step settles on its height
+        let mut filter = Filter::new(48_000, 1.0, Some(1000));
+        let mut samples = vec![8000; 2000];
+        filter.apply(&mut samples);
+        assert!(samples[0] < 8000);
+        assert_eq!(8000, samples[1999]);
+    }
+}
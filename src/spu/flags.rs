@@ -1,3 +1,6 @@
+// PackedStruct derives on byte wide fields discard a borrow, nothing to act on
+#![allow(unused_must_use)]
+
 use packed_struct::prelude::*;
 
 #[derive(PackedStruct, Default, PartialEq)]
@@ -36,18 +39,6 @@ pub struct SequenceControl {
     pub data_length: u8,
 }
 
-impl SequenceControl {
-    pub fn phase_duty(&self) -> f32 {
-        match self.duty {
-            0 => 0.125,
-            1 => 0.25,
-            2 => 0.5,
-            3 => 0.75,
-            _ => 0.5
-        }
-    }
-}
-
 #[derive(PackedStruct, Default, PartialEq)]
 #[packed_struct(bit_numbering="lsb0",size_bytes="1")]
 pub struct EnvelopeControl {
@@ -107,20 +98,22 @@ impl std::fmt::Debug for FrequencyHigherData {
 /// FF10 - NR10 - Channel 1 Sweep register (R/W)
 /// - Bit 6-4 - Sweep Time
 /// - Bit 3   - Sweep Increase/Decrease
-///            0: Addition    (frequency increases)
-///            1: Subtraction (frequency decreases)
+///   0: Addition    (frequency increases)
+///   1: Subtraction (frequency decreases)
 /// - Bit 2-0 - Number of sweep shift (n: 0-7)
 ///
 /// Sweep Time:
 ///
-///     000: sweep off - no freq change
-///     001: 7.8 ms  (1/128Hz)
-///     010: 15.6 ms (2/128Hz)
-///     011: 23.4 ms (3/128Hz)
-///     100: 31.3 ms (4/128Hz)
-///     101: 39.1 ms (5/128Hz)
-///     110: 46.9 ms (6/128Hz)
-///     111: 54.7 ms (7/128Hz)
+/// ```text
+/// 000: sweep off - no freq change
+/// 001: 7.8 ms  (1/128Hz)
+/// 010: 15.6 ms (2/128Hz)
+/// 011: 23.4 ms (3/128Hz)
+/// 100: 31.3 ms (4/128Hz)
+/// 101: 39.1 ms (5/128Hz)
+/// 110: 46.9 ms (6/128Hz)
+/// 111: 54.7 ms (7/128Hz)
+/// ```
 ///
 /// The change of frequency (NR13,NR14) at each shift is calculated by the following formula where X(0) is initial freq & X(t-1) is last freq:
 ///
@@ -133,10 +126,12 @@ pub type Channel1SweepControl = SweepControl;
 ///
 /// Wave Duty:
 ///
-///     00: 12.5% ( _-------_-------_------- )
-///     01: 25%   ( __------__------__------ )
-///     10: 50%   ( ____----____----____---- ) (normal)
-///     11: 75%   ( ______--______--______-- )
+/// ```text
+/// 00: 12.5% ( _-------_-------_------- )
+/// 01: 25%   ( __------__------__------ )
+/// 10: 50%   ( ____----____----____---- ) (normal)
+/// 11: 75%   ( ______--______--______-- )
+/// ```
 ///
 /// Sound Length = (64-t1)*(1/256) seconds.
 ///
@@ -147,7 +142,7 @@ pub type Channel1SequenceControl = SequenceControl;
 /// - Bit 7-4 - Initial Volume of envelope (0-0Fh) (0=No Sound)
 /// - Bit 3   - Envelope Direction (0=Decrease, 1=Increase)
 /// - Bit 2-0 - Number of envelope sweep (n: 0-7)
-///             (If zero, stop envelope operation.)
+///   (If zero, stop envelope operation.)
 ///
 /// Length of 1 step = n*(1/64) seconds
 pub type Channel1EnvelopeControl = EnvelopeControl;
@@ -161,7 +156,7 @@ pub type Channel1FrequencyLowerData = FrequencyLowerData;
 /// FF14 - NR14 - Channel 1 Frequency high (R/W)
 /// - Bit 7   - Initial (1=Restart Sound)     (Write Only)
 /// - Bit 6   - Counter/consecutive selection (Read/Write)
-///           (1=Stop output when length in NR11 expires)
+///   (1=Stop output when length in NR11 expires)
 /// - Bit 2-0 - Frequency's higher 3 bits (x) (Write Only)
 ///
 /// Frequency = 131072/(2048-x) Hz
@@ -173,10 +168,12 @@ pub type Channel1FrequencyHigherData = FrequencyHigherData;
 ///
 /// Wave Duty:
 ///
-///     00: 12.5% ( _-------_-------_------- )
-///     01: 25%   ( __------__------__------ )
-///     10: 50%   ( ____----____----____---- ) (normal)
-///     11: 75%   ( ______--______--______-- )
+/// ```text
+/// 00: 12.5% ( _-------_-------_------- )
+/// 01: 25%   ( __------__------__------ )
+/// 10: 50%   ( ____----____----____---- ) (normal)
+/// 11: 75%   ( ______--______--______-- )
+/// ```
 ///
 /// Sound Length = (64-t1)*(1/256) seconds.
 ///
@@ -187,7 +184,7 @@ pub type Channel2SequenceControl = SequenceControl;
 /// - Bit 7-4 - Initial Volume of envelope (0-0Fh) (0=No Sound)
 /// - Bit 3   - Envelope Direction (0=Decrease, 1=Increase)
 /// - Bit 2-0 - Number of envelope sweep (n: 0-7)
-///              (If zero, stop envelope operation.)
+///   (If zero, stop envelope operation.)
 ///
 /// Length of 1 step = n*(1/64) seconds
 pub type Channel2EnvelopeControl = EnvelopeControl;
@@ -201,7 +198,7 @@ pub type Channel2FrequencyLowerData = FrequencyLowerData;
 /// FF19 - NR24 - Channel 2 Frequency hi data (R/W)
 /// - Bit 7   - Initial (1=Restart Sound)     (Write Only)
 /// - Bit 6   - Counter/consecutive selection (Read/Write)
-///            (1=Stop output when length in NR21 expires)
+///   (1=Stop output when length in NR21 expires)
 /// - Bit 2-0 - Frequency's higher 3 bits (x) (Write Only)
 ///
 /// Frequency = 131072/(2048-x) Hz
@@ -237,10 +234,12 @@ pub struct Channel3SoundSequenceLength {
 ///
 /// Possible Output levels are:
 ///
-///     0: Mute (No sound)
-///     1: 100% Volume (Produce Wave Pattern RAM Data as it is)
-///     2:  50% Volume (Produce Wave Pattern RAM data shifted once to the right)
-///     3:  25% Volume (Produce Wave Pattern RAM data shifted twice to the right)
+/// ```text
+/// 0: Mute (No sound)
+/// 1: 100% Volume (Produce Wave Pattern RAM Data as it is)
+/// 2:  50% Volume (Produce Wave Pattern RAM data shifted once to the right)
+/// 3:  25% Volume (Produce Wave Pattern RAM data shifted twice to the right)
+/// ```
 #[derive(PackedStruct, Default, Debug, PartialEq)]
 #[packed_struct(bit_numbering="lsb0",size_bytes="1")]
 pub struct Channel3VolumeSelection {
@@ -261,7 +260,7 @@ pub type Channel3FrequencyLowerData = FrequencyLowerData;
 /// FF1E - NR34 - Channel 3 Frequency's higher data (R/W)
 /// - Bit 7   - Initial (1=Restart Sound)     (Write Only)
 /// - Bit 6   - Counter/consecutive selection (Read/Write)
-///            (1=Stop output when length in NR31 expires)
+///   (1=Stop output when length in NR31 expires)
 /// - Bit 2-0 - Frequency's higher 3 bits (x) (Write Only)
 pub type Channel3FrequencyHigherData = FrequencyHigherData;
 
@@ -275,7 +274,7 @@ pub type Channel3FrequencyHigherData = FrequencyHigherData;
 #[packed_struct(bit_numbering="lsb0",size_bytes="1")]
 pub struct Channel4SoundSequenceLength {
     #[packed_field(bits="6..=7")]
-    _unused: ReservedOnes<packed_bits::Bits3>,
+    _unused: ReservedOnes<packed_bits::Bits2>,
 
     #[packed_field(bits="0..=5")]
     pub data_length: u8,
@@ -285,7 +284,7 @@ pub struct Channel4SoundSequenceLength {
 /// - Bit 7-4 - Initial Volume of envelope (0-0Fh) (0=No Sound)
 /// - Bit 3   - Envelope Direction (0=Decrease, 1=Increase)
 /// - Bit 2-0 - Number of envelope sweep (n: 0-7)
-///              (If zero, stop envelope operation.)
+///   (If zero, stop envelope operation.)
 ///
 /// Length of 1 step = n*(1/64) seconds.
 pub type Channel4EnvelopeControl = EnvelopeControl;
@@ -315,8 +314,8 @@ pub struct Channel4PolynomialCounterParameterControl {
 /// FF23 - NR44 - Channel 4 Counter/consecutive; Inital (R/W)
 /// - Bit 7   - Initial (1=Restart Sound)     (Write Only)
 /// - Bit 6   - Counter/consecutive selection (Read/Write)
-///            (1=Stop output when length in NR41 expires)
-pub type Channel4PolynomialCounterSequenceControl = SequenceControl;
+///   (1=Stop output when length in NR41 expires)
+pub type Channel4PolynomialCounterSequenceControl = FrequencyHigherData;
 
 /// FF24 - NR50 - Channel control / ON-OFF / Volume (R/W)
 /// - Bit 7   - Output Vin to SO2 terminal (1=Enable)
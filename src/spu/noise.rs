@@ -1,5 +1,16 @@
-use super::sampler::Sampler;
-use sdl2::audio::AudioQueue;
+use super::flags::*;
+use super::channel::Channel;
+use super::sequencer::{Envelope, Length};
+
+use packed_struct::prelude::*;
+
+use crate::TICKS_PER_SECOND;
+
+/// Timer period in ticks of each divisor code, shifted left by the clock shift
+const DIVISORS: [u32; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+/// Shifts of 14 and 15 stop the LFSR
+const MAX_CLOCK_SHIFT: u8 = 13;
 
 //         Noise
 // FF1F ---- ---- Not used
@@ -7,26 +18,290 @@ use sdl2::audio::AudioQueue;
 // NR42 FF21 VVVV APPP Starting volume, Envelope add mode, period
 // NR43 FF22 SSSS WDDD Clock shift, Width mode of LFSR, Divisor code
 // NR44 FF23 TL-- ---- Trigger, Length enable
-#[allow(dead_code)]
-#[derive(Default)]
+#[derive(Clone, Debug)]
 pub struct Noise {
     pub left_enable: bool,
     pub right_enable: bool,
 
     playing: bool,
-    repeat: bool,
 
-    envelope_add_mode: bool,
-    envelope_start_volume: u8,
-    envelope_sweep_number: u8,
+    length_load: u8,
+    length: Length,
+    envelope: Envelope,
 
     clock_shift: u8,
     clock_width_mode: u8,
     clock_divisor_code: u8,
+
+    lfsr: u16,
+    timer: u32,
 }
 
-impl Sampler for Noise {
-    fn enqueue_audio_samples(&mut self, _queue: &mut AudioQueue<i8>) {
+impl Default for Noise {
+    fn default() -> Self {
+        Self {
+            left_enable: false,
+            right_enable: false,
+
+            playing: false,
+
+            length_load: 0,
+            length: Length::default(),
+            envelope: Envelope::default(),
+
+            clock_shift: 0,
+            clock_width_mode: 0,
+            clock_divisor_code: 0,
+
+            lfsr: 0x7FFF,
+            timer: 0,
+        }
+    }
+}
+
+impl Channel for Noise {
+    fn next_event(&self) -> Option<u32> {
+        if self.playing && self.clock_shift <= MAX_CLOCK_SHIFT { Some(self.timer) } else { None }
+    }
+
+    fn step(&mut self, ticks: u32) {
+        if self.next_event().is_none() {
+            return;
+        }
+
+        let mut ticks = ticks;
+        while ticks >= self.timer {
+            ticks -= self.timer;
+            self.timer = self.period();
+            self.step_lfsr();
+        }
+        self.timer -= ticks;
+    }
+
+    /// High while bit 0 of the LFSR is clear
+    fn output(&self) -> u8 {
+        if self.playing && self.lfsr & 1 == 0 { self.envelope.volume() } else { 0 }
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.envelope.dac_power()
+    }
+}
 
+impl Noise {
+    pub fn is_playing(&self) -> bool {
+        self.playing
     }
-}
\ No newline at end of file
+
+    /// LFSR clock, in Hz
+    pub fn frequency(&self) -> u32 {
+        TICKS_PER_SECOND as u32 / self.period()
+    }
+
+    /// Envelope volume, 0-15
+    pub fn volume(&self) -> u8 {
+        self.envelope.volume()
+    }
+
+    /// Ticks per LFSR step
+    fn period(&self) -> u32 {
+        DIVISORS[self.clock_divisor_code as usize] << self.clock_shift
+    }
+
+    /// Xor the two low bits into bit 14, and bit 6 as well in 7-bit mode
+    fn step_lfsr(&mut self) {
+        let bit = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+        self.lfsr = (self.lfsr >> 1) | (bit << 14);
+        if self.clock_width_mode != 0 {
+            self.lfsr = (self.lfsr & !(1 << 6)) | (bit << 6);
+        }
+    }
+
+    pub fn step_length(&mut self) {
+        if self.length.step() {
+            self.playing = false;
+        }
+    }
+
+    pub fn step_envelope(&mut self) {
+        self.envelope.step();
+    }
+
+    pub fn r1(&self) -> u8 {
+        self.length_load
+    }
+
+    pub fn set_r1(&mut self, r: u8) {
+        let r: [u8; 1] = [r];
+        let r = Channel4SoundSequenceLength::unpack(&r).unwrap();
+
+        self.length_load = r.data_length;
+        self.length.load(64, r.data_length);
+    }
+
+    pub fn r2(&self) -> u8 {
+        let ec = Channel4EnvelopeControl {
+            initial_volume: self.envelope.start_volume,
+            envelope_direction: self.envelope.add_mode,
+            envelope_step: self.envelope.period,
+        };
+        ec.pack().unwrap()[0]
+    }
+
+    pub fn set_r2(&mut self, r: u8) {
+        let r: [u8; 1] = [r];
+        let r = Channel4EnvelopeControl::unpack(&r).unwrap();
+
+        self.envelope.start_volume = r.initial_volume;
+        self.envelope.add_mode = r.envelope_direction;
+        self.envelope.period = r.envelope_step;
+        if !self.envelope.dac_power() {
+            self.playing = false;
+        }
+    }
+
+    pub fn r3(&self) -> u8 {
+        let pc = Channel4PolynomialCounterParameterControl {
+            frequency_shift: self.clock_shift,
+            step_width: self.clock_width_mode,
+            frequency_divider: self.clock_divisor_code,
+        };
+        pc.pack().unwrap()[0]
+    }
+
+    pub fn set_r3(&mut self, r: u8) {
+        let r: [u8; 1] = [r];
+        let r = Channel4PolynomialCounterParameterControl::unpack(&r).unwrap();
+
+        self.clock_shift = r.frequency_shift;
+        self.clock_width_mode = r.step_width;
+        self.clock_divisor_code = r.frequency_divider;
+    }
+
+    pub fn r4(&self) -> u8 {
+        let mut r = Channel4PolynomialCounterSequenceControl::default();
+        r.stop_on_complete = self.length.enable;
+        r.pack().unwrap()[0]
+    }
+
+    pub fn set_r4(&mut self, r: u8) {
+        let r: [u8; 1] = [r];
+        let r = Channel4PolynomialCounterSequenceControl::unpack(&r).unwrap();
+
+        self.length.enable = r.stop_on_complete;
+
+        if r.restart_sequence {
+            self.trigger();
+        }
+    }
+
+    /// Reset the LFSR and the envelope, with the full length if it had expired
+    fn trigger(&mut self) {
+        self.length.trigger(64);
+        self.envelope.trigger();
+        self.lfsr = 0x7FFF;
+        self.timer = self.period();
+        self.playing = self.envelope.dac_power();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise() -> Noise {
+        let mut noise = Noise { left_enable: true, right_enable: true, ..Default::default() };
+        noise.set_r2(0xF0);
+        noise
+    }
+
+    #[test]
+    fn frequency_test() {
+        let mut noise = noise();
+        noise.set_r3(0x00);
+        assert_eq!(524_288, noise.frequency());
+        noise.set_r3(0x03);
+        assert_eq!(87_381, noise.frequency());
+        noise.set_r3(0x27);
+        assert_eq!(9_362, noise.frequency());
+        assert_eq!(0x27, noise.r3());
+    }
+
+    #[test]
+    fn lfsr_test() {
+        let mut noise = noise();
+        noise.step_lfsr();
+        assert_eq!(0x3FFF, noise.lfsr);
+
+        // Bits 0 and 1 differ, a one shifts in
+        noise.lfsr = 0x0001;
+        noise.step_lfsr();
+        assert_eq!(0x4000, noise.lfsr);
+
+        // 15-bit mode repeats every 32767 steps
+        noise.lfsr = 0x7FFF;
+        let period = (1..).find(|_| { noise.step_lfsr(); noise.lfsr == 0x7FFF }).unwrap();
+        assert_eq!(32_767, period);
+
+        // 7-bit mode every 127 steps
+        noise.set_r3(0x08);
+        let period = (1..).find(|_| { noise.step_lfsr(); noise.lfsr & 0x7F == 0x7F }).unwrap();
+        assert_eq!(127, period);
+    }
+
+    #[test]
+    fn playback_test() {
+        // 128 ticks an LFSR step
+        let mut noise = noise();
+        noise.set_r3(0x40);
+        noise.set_r4(0x80);
+        assert!(noise.is_playing());
+        assert_eq!(Some(128), noise.next_event());
+
+        let mut lfsr = Noise::default();
+        for _ in 0..64 {
+            noise.step(128);
+            lfsr.step_lfsr();
+            let expected = if lfsr.lfsr & 1 == 0 { 15 } else { 0 };
+            assert_eq!(expected, noise.output());
+        }
+
+        // Clock shifts of 14 and 15 stop the LFSR
+        noise.set_r3(0xE0);
+        assert_eq!(None, noise.next_event());
+    }
+
+    #[test]
+    fn length_test() {
+        let mut noise = noise();
+        noise.set_r1(62);
+        noise.set_r4(0xC0);
+        assert!(noise.is_playing());
+
+        noise.step_length();
+        noise.step_length();
+        assert!(!noise.is_playing());
+
+        assert_eq!(0, noise.output());
+
+        noise.set_r4(0xC0);
+        assert_eq!(64, noise.length.counter());
+    }
+
+    #[test]
+    fn dac_test() {
+        let mut noise = noise();
+        noise.set_r4(0x80);
+        assert!(noise.is_playing());
+
+        noise.set_r2(0x00);
+        assert!(!noise.is_playing());
+        noise.set_r4(0x80);
+        assert!(!noise.is_playing());
+
+        // Increasing from zero keeps the DAC on
+        noise.set_r2(0x08);
+        noise.set_r4(0x80);
+        assert!(noise.is_playing());
+    }
+}
@@ -0,0 +1,22 @@
+/// Pitch and loudness of a channel while it sounds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Note {
+    /// Tone in Hz, the LFSR clock for the noise channel
+    pub frequency: u32,
+
+    /// 0-15, the envelope volume or the wave volume code on the same scale
+    pub volume: u8,
+}
+
+/// Note of a channel changing, `time` ticks into the frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoteEvent {
+    pub time: u32,
+    pub channel: usize,
+
+    /// None once the channel falls silent
+    pub note: Option<Note>,
+
+    /// Restarted by a trigger, even if the note did not change
+    pub trigger: bool,
+}
@@ -1,5 +0,0 @@
-use sdl2::audio::AudioQueue;
-
-pub trait Sampler {
-    fn enqueue_audio_samples(&mut self, queue: &mut AudioQueue<i8>);
-}
\ No newline at end of file
@@ -0,0 +1,175 @@
+bitflags! {
+    /// Units clocked on a step of the frame sequencer
+    pub struct Clocks: u8 {
+        const LENGTH   = 0b0000_0001;
+        const SWEEP    = 0b0000_0010;
+        const ENVELOPE = 0b0000_0100;
+    }
+}
+
+/// 512Hz clock of the length counters, envelopes and sweep, driven by DIV
+///
+/// Step   Length  Sweep   Envelope
+/// -------------------------------
+/// 0      Clock   -       -
+/// 1      -       -       -
+/// 2      Clock   Clock   -
+/// 3      -       -       -
+/// 4      Clock   -       -
+/// 5      -       -       -
+/// 6      Clock   Clock   -
+/// 7      -       -       Clock
+#[derive(Clone, Debug, Default)]
+pub struct FrameSequencer {
+    step: u8,
+}
+
+impl FrameSequencer {
+    /// Run the current step, giving the units it clocks
+    pub fn step(&mut self) -> Clocks {
+        let clocks = match self.step {
+            0 | 4 => Clocks::LENGTH,
+            2 | 6 => Clocks::LENGTH | Clocks::SWEEP,
+            7 => Clocks::ENVELOPE,
+            _ => Clocks::empty(),
+        };
+        self.step = (self.step + 1) % 8;
+        clocks
+    }
+
+    pub fn reset(&mut self) {
+        self.step = 0;
+    }
+}
+
+/// Length counter, silencing the channel once it runs out
+#[derive(Clone, Debug, Default)]
+pub struct Length {
+    pub enable: bool,
+    counter: u16,
+}
+
+impl Length {
+    /// Length load, counting down from `max - data`
+    pub fn load(&mut self, max: u16, data: u8) {
+        self.counter = max - data as u16;
+    }
+
+    /// Full length if it had expired
+    pub fn trigger(&mut self, max: u16) {
+        if self.counter == 0 {
+            self.counter = max;
+        }
+    }
+
+    pub fn counter(&self) -> u16 {
+        self.counter
+    }
+
+    /// Clock the counter, true when it expires
+    pub fn step(&mut self) -> bool {
+        if self.enable && self.counter > 0 {
+            self.counter -= 1;
+            return self.counter == 0;
+        }
+        false
+    }
+}
+
+/// Volume envelope, a step up or down every `period` clocks
+#[derive(Clone, Debug, Default)]
+pub struct Envelope {
+    pub start_volume: u8,
+    pub add_mode: bool,
+    pub period: u8,
+
+    volume: u8,
+    timer: u8,
+}
+
+impl Envelope {
+    pub fn trigger(&mut self) {
+        self.volume = self.start_volume;
+        self.timer = self.period;
+    }
+
+    /// Current volume, 0-15
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    /// Without the volume or the add mode, the DAC is off
+    pub fn dac_power(&self) -> bool {
+        self.start_volume != 0 || self.add_mode
+    }
+
+    pub fn step(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.add_mode && self.volume < 0xF {
+                self.volume += 1;
+            } else if !self.add_mode && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_sequencer_test() {
+        let mut sequencer = FrameSequencer::default();
+        let clocks: Vec<Clocks> = (0..16).map(|_| sequencer.step()).collect();
+
+        assert_eq!(8, clocks.iter().filter(|c| c.contains(Clocks::LENGTH)).count());
+        assert_eq!(4, clocks.iter().filter(|c| c.contains(Clocks::SWEEP)).count());
+        assert_eq!(2, clocks.iter().filter(|c| c.contains(Clocks::ENVELOPE)).count());
+        assert_eq!(Clocks::LENGTH | Clocks::SWEEP, clocks[2]);
+        assert_eq!(Clocks::ENVELOPE, clocks[15]);
+    }
+
+    #[test]
+    fn length_test() {
+        let mut length = Length::default();
+        length.load(64, 62);
+        assert!(!length.step());
+
+        length.enable = true;
+        assert!(!length.step());
+        assert!(length.step());
+        assert!(!length.step());
+
+        length.trigger(64);
+        assert_eq!(64, length.counter());
+    }
+
+    #[test]
+    fn envelope_test() {
+        let mut envelope = Envelope { start_volume: 2, period: 2, ..Default::default() };
+        envelope.trigger();
+        envelope.step();
+        assert_eq!(2, envelope.volume());
+        envelope.step();
+        assert_eq!(1, envelope.volume());
+        for _ in 0..4 {
+            envelope.step();
+        }
+        assert_eq!(0, envelope.volume());
+
+        envelope.add_mode = true;
+        envelope.start_volume = 0xE;
+        envelope.trigger();
+        for _ in 0..8 {
+            envelope.step();
+        }
+        assert_eq!(0xF, envelope.volume());
+    }
+}
@@ -1,7 +1,7 @@
 use super::flags::*;
 use super::util::*;
-use super::sampler::*;
-use sdl2::audio::AudioQueue;
+use super::channel::Channel;
+use super::sequencer::{Envelope, Length};
 
 use packed_struct::prelude::*;
 
@@ -20,144 +20,135 @@ use packed_struct::prelude::*;
     NR23 FF18 FFFF FFFF Frequency LSB
     NR24 FF19 TL-- -FFF Trigger, Length enable, Frequency MSB
 */
-#[allow(dead_code)]
+/// 11-bit frequency
+const MAX_FREQUENCY_PARAM: u32 = 0x7FF;
+
+// Duty   Waveform    Ratio
+// -------------------------
+// 0      00000001    12.5%
+// 1      10000001    25%
+// 2      10000111    50%
+// 3      01111110    75%
+const DUTY_WAVEFORMS: [u8; 4] = [0b0000_0001, 0b1000_0001, 0b1000_0111, 0b0111_1110];
+
+#[derive(Clone, Debug, Default)]
 pub struct Square {
     pub left_enable: bool,
     pub right_enable: bool,
 
     playing: bool,
-    restart: bool,
-    repeat: bool,
 
-    frequency: u32,
     fparam: u32,
+    timer: u32,
+    duty_step: u8,
 
-    envelope_direction: bool,
-    envelope_start_volume: u8,
-    envelope_sweep_number: u8,
+    envelope: Envelope,
+    length: Length,
 
     sweep_inverse: bool,
     sweep_period: u8,
     sweep_shift: u8,
+    sweep_enabled: bool,
+    sweep_timer: u8,
+    sweep_shadow: u32,
 
     wave_duty: u8,
     wave_length: u8,
-
-    buffer: Box<[i8; 8192]>,
-    phase_duty: f32,
-    phase_pos: f32,
-    step_counter: f32,
-    volume_step: u8,
-    volume: i8,
 }
 
-impl Default for Square {
-    fn default() -> Self {
-        Self {
-            left_enable: false,
-            right_enable: false,
-
-            playing: false,
-            restart: false,
-            repeat: false,
-            frequency: calculate_frequency(0),
-            fparam: 0,
-
-            envelope_start_volume: 0,
-            envelope_sweep_number: 0,
-            envelope_direction: true,
-
-            sweep_inverse: false,
-            sweep_period: 0,
-            sweep_shift: 0,
-            wave_duty: 0,
-            wave_length: 0,
-
-            buffer: Box::new([0; 8192]),
-            phase_duty: 0.5,
-            phase_pos: 0.0,
-            step_counter: 0.0,
-            volume_step: 0,
-            volume: 0,
+impl Channel for Square {
+    fn next_event(&self) -> Option<u32> {
+        if self.playing { Some(self.timer) } else { None }
+    }
+
+    fn step(&mut self, ticks: u32) {
+        if !self.playing {
+            return;
+        }
+
+        let mut ticks = ticks;
+        while ticks >= self.timer {
+            ticks -= self.timer;
+            self.timer = self.period();
+            self.duty_step = (self.duty_step + 1) % 8;
         }
+        self.timer -= ticks;
+    }
+
+    fn output(&self) -> u8 {
+        let high = DUTY_WAVEFORMS[self.wave_duty as usize] & (0x80 >> self.duty_step) != 0;
+        if self.playing && high { self.envelope.volume() } else { 0 }
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.envelope.dac_power()
     }
 }
 
-impl Sampler for Square {
-    fn enqueue_audio_samples(&mut self, queue: &mut AudioQueue<i8>) {
-        if self.restart {
-            self.restart = false;
-            self.playing = true;
-            self.phase_pos = 0.0;
-            queue.clear();
-        }
+impl Square {
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
 
-        if !self.playing {
-            return;
+    /// Tone, in Hz
+    pub fn frequency(&self) -> u32 {
+        calculate_frequency(self.fparam)
+    }
+
+    /// Envelope volume, 0-15
+    pub fn volume(&self) -> u8 {
+        self.envelope.volume()
+    }
+
+    /// Ticks per duty step, a whole waveform at 131072/(2048-x) Hz
+    fn period(&self) -> u32 {
+        (2048 - self.fparam) * 4
+    }
+
+    pub fn step_length(&mut self) {
+        if self.length.step() {
+            self.playing = false;
         }
+    }
+
+    pub fn step_envelope(&mut self) {
+        self.envelope.step();
+    }
 
-        if !self.left_enable && !self.right_enable && self.wave_length != 0 {
+    /// Channel 1 only, channel 2 never enables its sweep
+    pub fn step_sweep(&mut self) {
+        self.sweep_timer = self.sweep_timer.saturating_sub(1);
+        if self.sweep_timer != 0 {
             return;
         }
 
-        let phase_inc = self.frequency as f32 / queue.spec().freq as f32;
-        let step_size = self.envelope_sweep_number as f32 * (queue.spec().freq as f32 / 64.0);
-
-        let length = self.buffer.len();
-        if (queue.size() as usize) < length {
-            let length = length / 2;
-            for i in 0..length {
-                // envelope
-                if step_size > 0.0 {
-                    if self.step_counter >= step_size {
-                        self.step_counter -= step_size;
-                        if self.envelope_direction {
-                            if self.volume_step < 0xF {
-                                self.volume_step += 1;
-                            }
-                        } else {
-                            if self.volume_step >= 0x1 {
-                                self.volume_step -= 1;
-                            }
-                        }
-                        self.volume = calculate_volume(self.volume_step);
-                        if self.volume_step == 0 {
-                            self.playing = false;
-                        }
-                    }
-                    self.step_counter += 1.0;
-                }
-
-                // Duty   Waveform    Ratio
-                // -------------------------
-                // 0      00000001    12.5%
-                // 1      10000001    25%
-                // 2      10000111    50%
-                // 3      01111110    75%
-                let sample = self.volume * match self.wave_duty {
-                    1 => if self.phase_pos >= 0.875 { 1 } else { -1 },
-                    2 => if self.phase_pos <= 0.125 || self.phase_pos >= 0.875 { 1 } else { -1 },
-                    3 => if self.phase_pos <= 0.125 || self.phase_pos >= 0.625 { 1 } else { -1 },
-                    4 => if self.phase_pos >= 0.125 && self.phase_pos <= 0.875 { 1 } else { -1 },
-                    _ => -1,
-                };
-
-                // left
-                self.buffer[i * 2] = if self.left_enable { sample } else { 0 };
-
-                // right
-                self.buffer[i * 2 + 1] = if self.right_enable { sample } else { 0 };
-
-                self.phase_pos += phase_inc;
-                self.phase_pos %= 1.0;
+        self.sweep_timer = sweep_timer_period(self.sweep_period);
+        if self.sweep_enabled && self.sweep_period != 0 {
+            let fparam = self.sweep_frequency();
+            if fparam <= MAX_FREQUENCY_PARAM && self.sweep_shift != 0 {
+                self.sweep_shadow = fparam;
+                self.fparam = fparam;
+
+                // Checked again with the new frequency, without applying it
+                self.sweep_frequency();
             }
-            queue.queue(&*self.buffer);
         }
     }
-}
 
-#[allow(dead_code)]
-impl Square {
+    /// Next frequency of the sweep, disabling the channel on overflow
+    fn sweep_frequency(&mut self) -> u32 {
+        let delta = self.sweep_shadow >> self.sweep_shift;
+        let fparam = if self.sweep_inverse {
+            self.sweep_shadow - delta
+        } else {
+            self.sweep_shadow + delta
+        };
+        if fparam > MAX_FREQUENCY_PARAM {
+            self.playing = false;
+        }
+        fparam
+    }
+
     pub fn r0(&self) -> u8 {
         let mut sc = SweepControl::default();
         sc.sweep_inverse = self.sweep_inverse;
@@ -177,9 +168,10 @@ impl Square {
     }
 
     pub fn r1(&self) -> u8 {
-        let mut sc = SequenceControl::default();
-        sc.duty         = self.wave_duty;
-        sc.data_length  = self.wave_length;
+        let sc = SequenceControl {
+            duty:        self.wave_duty,
+            data_length: self.wave_length,
+        };
         sc.pack().unwrap()[0]
     }
 
@@ -190,14 +182,15 @@ impl Square {
 
         self.wave_duty = r.duty;
         self.wave_length = r.data_length;
-        self.phase_duty = r.phase_duty();
+        self.length.load(64, r.data_length);
     }
 
     pub fn r2(&self) -> u8 {
-        let mut ec = EnvelopeControl::default();
-        ec.initial_volume = self.envelope_start_volume;
-        ec.envelope_direction = self.envelope_direction;
-        ec.envelope_step = self.envelope_sweep_number;
+        let ec = EnvelopeControl {
+            initial_volume: self.envelope.start_volume,
+            envelope_direction: self.envelope.add_mode,
+            envelope_step: self.envelope.period,
+        };
         ec.pack().unwrap()[0]
     }
 
@@ -206,11 +199,12 @@ impl Square {
         let r = EnvelopeControl::unpack(&r).unwrap();
         //println!("{:?}", r);
 
-        self.envelope_start_volume = r.initial_volume;
-        self.envelope_direction = r.envelope_direction;
-        self.envelope_sweep_number = r.envelope_step;
-        self.volume_step = self.envelope_start_volume;
-        self.volume = calculate_volume(self.volume_step);
+        self.envelope.start_volume = r.initial_volume;
+        self.envelope.add_mode = r.envelope_direction;
+        self.envelope.period = r.envelope_step;
+        if !self.envelope.dac_power() {
+            self.playing = false;
+        }
     }
 
     pub fn r3(&self) -> u8 {
@@ -219,11 +213,13 @@ impl Square {
 
     pub fn set_r3(&mut self, data: u8) {
         self.fparam = set_low_frequency_param(self.fparam, data as u32);
-        self.frequency = calculate_frequency(self.fparam);
     }
 
     pub fn r4(&self) -> u8 {
-        ((self.fparam & 0x0700) >> 8) as u8
+        let mut r = FrequencyHigherData::default();
+        r.stop_on_complete = self.length.enable;
+        r.frequency_higher = ((self.fparam & 0x0700) >> 8) as u8;
+        r.pack().unwrap()[0]
     }
 
     pub fn set_r4(&mut self, r: u8) {
@@ -232,8 +228,129 @@ impl Square {
         //println!("{:?}", r);
 
         self.fparam = set_high_frequency_param(self.fparam, r.frequency_higher as u32);
-        self.frequency = calculate_frequency(self.fparam);
-        self.repeat = !r.stop_on_complete;
-        self.restart = r.restart_sequence;
+        self.length.enable = r.stop_on_complete;
+
+        if r.restart_sequence {
+            self.trigger();
+        }
+    }
+
+    /// Restart the envelope and the sweep, with the full length if it had expired
+    fn trigger(&mut self) {
+        self.playing = self.envelope.dac_power();
+        self.timer = self.period();
+        self.length.trigger(64);
+        self.envelope.trigger();
+
+        self.sweep_shadow = self.fparam;
+        self.sweep_timer = sweep_timer_period(self.sweep_period);
+        self.sweep_enabled = self.sweep_period != 0 || self.sweep_shift != 0;
+        if self.sweep_shift != 0 {
+            self.sweep_frequency();
+        }
+    }
+}
+
+/// A period of 0 reloads the timer with 8
+fn sweep_timer_period(period: u8) -> u8 {
+    if period == 0 { 8 } else { period }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Square {
+        let mut square = Square::default();
+        square.set_r2(0xF0);
+        square
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn length_test() {
+        let mut square = square();
+        square.set_r1(62);
+        square.set_r4(0xC0);
+        assert!(square.is_playing());
+
+        square.step_length();
+        assert!(square.is_playing());
+        square.step_length();
+        assert!(!square.is_playing());
+    }
+
+    #[test]
+    fn envelope_test() {
+        let mut square = square();
+        square.set_r2(0x31);
+        square.set_r4(0x80);
+        for _ in 0..3 {
+            square.step_envelope();
+        }
+        assert_eq!(0, square.envelope.volume());
+
+        // The DAC stays on, the channel plays silence
+        assert!(square.is_playing());
+    }
+
+    #[test]
+    fn duty_test() {
+        // 50%, 8 ticks a step at the highest frequency
+        let mut square = square();
+        square.set_r1(0x80);
+        square.set_r3(0xFE);
+        square.set_r4(0x87);
+        assert_eq!(Some(8), square.next_event());
+
+        let mut waveform = Vec::new();
+        for _ in 0..8 {
+            square.step(8);
+            waveform.push(square.output());
+        }
+        assert_eq!(vec![0, 0, 0, 0, 15, 15, 15, 15], waveform);
+    }
+
+    #[test]
+    fn sweep_test() {
+        // Up by f/2 every other clock
+        let mut square = square();
+        square.set_r0(0x21);
+        square.set_r3(0x00);
+        square.set_r4(0x81);
+        assert_eq!(0x100, square.fparam);
+
+        square.step_sweep();
+        assert_eq!(0x100, square.fparam);
+        square.step_sweep();
+        assert_eq!(0x180, square.fparam);
+        square.step_sweep();
+        square.step_sweep();
+        assert_eq!(0x240, square.fparam);
+        assert!(square.is_playing());
+
+        // Down, never overflowing
+        square.set_r0(0x29);
+        square.set_r4(0x82);
+        square.step_sweep();
+        square.step_sweep();
+        assert_eq!(0x120, square.fparam);
+    }
+
+    #[test]
+    fn sweep_overflow_test() {
+        let mut square = square();
+        square.set_r0(0x11);
+        square.set_r3(0xFF);
+        square.set_r4(0x84);
+        assert!(square.is_playing());
+
+        // 0x4FF goes up to 0x77E, the check of the next one overflows
+        square.step_sweep();
+        assert_eq!(0x77E, square.fparam);
+        assert!(!square.is_playing());
+
+        // 0x7FF + 0x3FF, already on trigger
+        square.set_r4(0x87);
+        assert!(!square.is_playing());
+    }
+}
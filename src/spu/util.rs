@@ -11,60 +11,9 @@ pub fn calculate_frequency(f: u32) -> u32 {
     131_072 / (2048 - f)
 }
 
-pub fn calculate_phase_duty(d: u8) -> f32 {
-    match d {
-        0 => 0.125,
-        1 => 0.25,
-        2 => 0.5,
-        3 => 0.75,
-        _ => 0.5
-    }
-}
-
 pub fn calculate_volume(v: u8) -> i8 {
     let v: f32 = v as f32;
     let coef: f32 = 1.0 / 15.0;
     let maxv: f32 = 127.0;
     (v * coef * maxv) as i8
 }
-
-pub fn calculate_sample(s: u8) -> i8 {
-    /*
-    F |               **
-    E |              *  *
-    D |             *    *
-    C |            *      *
-    B |           *        *
-    A |          *          *
-    9 |         *            *
-    8 |        *              *
-    7 |       *                *
-    6 |      *                  *
-    5 |     *                    *
-    4 |    *                      *
-    3 |   *                        *
-    2 |  *                          *
-    1 | *                            *
-    0 |*                              *
-    *  --------------------------------
-    */
-    match s {
-        0 => -127,
-        1 => -109,
-        2 => -90,
-        3 => -72,
-        4 => -54,
-        5 => -36,
-        6 => -18,
-        7 => 0,
-        8 => 0,
-        9 => 18,
-        10 => 36,
-        11 => 54,
-        12 => 72,
-        13 => 90,
-        14 => 109,
-        15 => 127,
-        _ => panic!(),
-    }
-}
@@ -1,5 +1,12 @@
-use super::sampler::Sampler;
-use sdl2::audio::AudioQueue;
+use super::flags::*;
+use super::util::*;
+use super::channel::Channel;
+use super::sequencer::Length;
+
+use packed_struct::prelude::*;
+
+/// FF30..=FF3F, 32 4-bit samples, the upper nibble played first
+pub const WAVE_RAM_SIZE: usize = 16;
 
 //         Wave
 // NR30 FF1A E--- ---- DAC power
@@ -7,24 +14,266 @@ use sdl2::audio::AudioQueue;
 // NR32 FF1C -VV- ---- Volume code (00=0%, 01=100%, 10=50%, 11=25%)
 // NR33 FF1D FFFF FFFF Frequency LSB
 // NR34 FF1E TL-- -FFF Trigger, Length enable, Frequency MSB
-#[allow(dead_code)]
-#[derive(Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Wave {
     pub left_enable: bool,
     pub right_enable: bool,
 
     playing: bool,
-    restart: bool,
-    repeat: bool,
-    frequency: u16,
+    dac_power: bool,
+
+    length_load: u8,
+    length: Length,
+
+    volume_code: u8,
+
+    fparam: u32,
+    timer: u32,
+    position: usize,
+
+    pub ram: [u8; WAVE_RAM_SIZE],
+}
+
+impl Channel for Wave {
+    fn next_event(&self) -> Option<u32> {
+        if self.playing { Some(self.timer) } else { None }
+    }
+
+    fn step(&mut self, ticks: u32) {
+        if !self.playing {
+            return;
+        }
+
+        let mut ticks = ticks;
+        while ticks >= self.timer {
+            ticks -= self.timer;
+            self.timer = self.period();
+            self.position = (self.position + 1) % 32;
+        }
+        self.timer -= ticks;
+    }
+
+    /// Sample shifted right by the volume code
+    fn output(&self) -> u8 {
+        if !self.playing {
+            return 0;
+        }
+        match self.volume_code {
+            0 => 0,
+            code => self.sample(self.position) >> (code - 1),
+        }
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.dac_power
+    }
+}
+
+impl Wave {
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Sample being played, 0-31
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Tone, in Hz, the 32 samples take twice as long as the 8 duty steps of a square
+    pub fn frequency(&self) -> u32 {
+        calculate_frequency(self.fparam) / 2
+    }
+
+    /// Volume code on the 0-15 scale of the envelopes
+    pub fn volume(&self) -> u8 {
+        match self.volume_code {
+            0 => 0,
+            code => 0xF >> (code - 1),
+        }
+    }
+
+    /// Ticks per sample, the 32 samples at 65536/(2048-x) Hz
+    fn period(&self) -> u32 {
+        (2048 - self.fparam) * 2
+    }
+
+    pub fn step_length(&mut self) {
+        if self.length.step() {
+            self.playing = false;
+        }
+    }
+
+    /// 4-bit sample at the position, upper nibble first
+    pub fn sample(&self, position: usize) -> u8 {
+        let data = self.ram[(position % 32) / 2];
+        if position % 2 == 0 { data >> 4 } else { data & 0x0F }
+    }
+
+    pub fn r0(&self) -> u8 {
+        let mut r = Channel3SoundOnOffStatus::default();
+        r.enable = self.dac_power;
+        r.pack().unwrap()[0]
+    }
+
+    pub fn set_r0(&mut self, r: u8) {
+        let r: [u8; 1] = [r];
+        let r = Channel3SoundOnOffStatus::unpack(&r).unwrap();
+
+        // Without the DAC the channel is off, until triggered again
+        self.dac_power = r.enable;
+        if !self.dac_power {
+            self.playing = false;
+        }
+    }
+
+    pub fn r1(&self) -> u8 {
+        self.length_load
+    }
+
+    pub fn set_r1(&mut self, r: u8) {
+        let r: [u8; 1] = [r];
+        let r = Channel3SoundSequenceLength::unpack(&r).unwrap();
+
+        self.length_load = r.data_length;
+        self.length.load(256, r.data_length);
+    }
+
+    pub fn r2(&self) -> u8 {
+        let mut r = Channel3VolumeSelection::default();
+        r.volume = self.volume_code;
+        r.pack().unwrap()[0]
+    }
+
+    pub fn set_r2(&mut self, r: u8) {
+        let r: [u8; 1] = [r];
+        let r = Channel3VolumeSelection::unpack(&r).unwrap();
+
+        self.volume_code = r.volume;
+    }
+
+    pub fn r3(&self) -> u8 {
+        (self.fparam & 0xFF) as u8
+    }
+
+    pub fn set_r3(&mut self, data: u8) {
+        self.fparam = set_low_frequency_param(self.fparam, data as u32);
+    }
+
+    pub fn r4(&self) -> u8 {
+        let mut r = Channel3FrequencyHigherData::default();
+        r.stop_on_complete = self.length.enable;
+        r.frequency_higher = ((self.fparam & 0x0700) >> 8) as u8;
+        r.pack().unwrap()[0]
+    }
+
+    pub fn set_r4(&mut self, r: u8) {
+        let r: [u8; 1] = [r];
+        let r = Channel3FrequencyHigherData::unpack(&r).unwrap();
+
+        self.fparam = set_high_frequency_param(self.fparam, r.frequency_higher as u32);
+        self.length.enable = r.stop_on_complete;
+
+        if r.restart_sequence {
+            self.trigger();
+        }
+    }
 
-    wave_length_load: u8,
-    wave_volume: u8,
-    pub wave_samples: [i8; 32],
+    /// Restart from the first sample, with the full length if it had expired
+    fn trigger(&mut self) {
+        self.length.trigger(256);
+        self.timer = self.period();
+        self.position = 0;
+        self.playing = self.dac_power;
+    }
 }
 
-impl Sampler for Wave {
-    fn enqueue_audio_samples(&mut self, _queue: &mut AudioQueue<i8>) {
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sawtooth() -> Wave {
+        let mut wave = Wave { left_enable: true, right_enable: true, ..Default::default() };
+        for (i, data) in wave.ram.iter_mut().enumerate() {
+            *data = ((i * 2) as u8 % 16) << 4 | ((i * 2 + 1) as u8 % 16);
+        }
+        wave.set_r0(0x80);
+        wave.set_r2(0x20);
+        wave
+    }
+
+    #[test]
+    fn wave_ram_test() {
+        let wave = sawtooth();
+        assert_eq!(0x0, wave.sample(0));
+        assert_eq!(0x1, wave.sample(1));
+        assert_eq!(0xF, wave.sample(15));
+        assert_eq!(0x0, wave.sample(16));
+        assert_eq!(0xF, wave.sample(31));
+    }
+
+    #[test]
+    fn volume_test() {
+        let mut wave = sawtooth();
+        wave.set_r4(0x80);
+        wave.position = 15;
+        assert_eq!(0xF, wave.output());
+
+        wave.set_r2(0x40);
+        assert_eq!(0x7, wave.output());
+        wave.set_r2(0x60);
+        assert_eq!(0x3, wave.output());
+        wave.set_r2(0x00);
+        assert_eq!(0, wave.output());
+    }
+
+    #[test]
+    fn playback_test() {
+        // 64 ticks a sample, 2048Hz
+        let mut wave = sawtooth();
+        wave.set_r3(0xE0);
+        wave.set_r4(0x87);
+        assert!(wave.is_playing());
+        assert_eq!(Some(64), wave.next_event());
 
+        let mut samples = vec![wave.output()];
+        for _ in 0..32 {
+            wave.step(64);
+            samples.push(wave.output());
+        }
+        let expected: Vec<u8> = (0..=32).map(|i| wave.sample(i)).collect();
+        assert_eq!(expected, samples);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn length_test() {
+        let mut wave = sawtooth();
+        wave.set_r1(252);
+        wave.set_r4(0xC0);
+        assert!(wave.is_playing());
+
+        for _ in 0..4 {
+            wave.step_length();
+        }
+        assert!(!wave.is_playing());
+
+        // Triggering again reloads the expired length
+        wave.set_r4(0xC0);
+        assert!(wave.is_playing());
+        assert_eq!(256, wave.length.counter());
+    }
+
+    #[test]
+    fn dac_test() {
+        let mut wave = sawtooth();
+        wave.set_r0(0x00);
+        wave.set_r4(0x80);
+        assert!(!wave.is_playing());
+
+        wave.set_r0(0x80);
+        wave.set_r4(0x80);
+        assert!(wave.is_playing());
+
+        wave.set_r0(0x00);
+        assert!(!wave.is_playing());
+    }
+}
@@ -0,0 +1,14 @@
+//! Hand assembled programs for the tests, on a blank ROM only cartridge
+
+/// 32KiB ROM starting `code` at $0150, after the entry point jumps there
+pub(crate) fn test_rom(code: &[u8]) -> Vec<u8> {
+    let mut rom = vec![0; 0x8000];
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP $0150
+    rom[0x0150..0x0150 + code.len()].copy_from_slice(code);
+    rom
+}
+
+/// LD A, data; LDH (register), A for each write, the registers as offsets from $FF00
+pub(crate) fn ldh_writes(writes: &[(u8, u8)]) -> Vec<u8> {
+    writes.iter().flat_map(|&(register, data)| [0x3E, data, 0xE0, register]).collect()
+}
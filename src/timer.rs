@@ -1,6 +1,10 @@
 use crate::MemoryBus;
 
-const COUNTER_DIV: [u16; 4] = [1024, 16, 64, 256];
+/// Divider bit clocking TIMA on its falling edge, every 1024, 16, 64 and 256 ticks by TAC clock select
+const COUNTER_MASKS: [u16; 4] = [0x0200, 0x0008, 0x0020, 0x0080];
+
+/// DIV bit 4, clocking the APU frame sequencer at 512Hz on its falling edge
+const FRAME_SEQUENCER_MASK: u16 = 0x1000;
 
 const DIV_ADDR: u16 = 0xFF04;
 const TIMA_ADDR: u16 = 0xFF05;
@@ -10,6 +14,7 @@ const TAC_ADDR: u16 = 0xFF07;
 #[derive(Debug, Clone)]
 pub struct Timer {
     overflow_interrupt_requested: bool,
+    frame_sequencer_clocked: bool,
     control: u8,
 
     // divider
@@ -28,6 +33,7 @@ impl Default for Timer {
     fn default() -> Self {
         Self {
             overflow_interrupt_requested: false,
+            frame_sequencer_clocked: false,
 
             control: 0xff,
 
@@ -37,7 +43,7 @@ impl Default for Timer {
             timer_acc: 0,
             timer_modulo: 0,
             timer_in_bit: false,
-            timer_in_mask: COUNTER_DIV[3],
+            timer_in_mask: COUNTER_MASKS[3],
             timer_enable: true,
         }
     }
@@ -72,7 +78,7 @@ impl Timer {
 
     pub fn set_control(&mut self, control: u8) {
         self.timer_enable = control & 1 << 2 != 0;
-        self.timer_in_mask = COUNTER_DIV[(control & 0x3) as usize];
+        self.timer_in_mask = COUNTER_MASKS[(control & 0x3) as usize];
         self.control = control
     }
 
@@ -88,6 +94,12 @@ impl Timer {
         (self.ticks_acc >> 8) as u8
     }
 
+    /// Preset DIV, as left by the boot rom
+    pub fn set_divider(&mut self, divider: u8) {
+        self.ticks_acc = (divider as u16) << 8;
+        self.timer_in_bit = (self.ticks_acc & self.timer_in_mask) != 0;
+    }
+
     pub fn modulo(&self) -> u8 {
         self.timer_modulo
     }
@@ -100,7 +112,13 @@ impl Timer {
         self.overflow_interrupt_requested
     }
 
+    /// DIV bit 4 fell during the last step, resetting DIV included
+    pub fn frame_sequencer_clocked(&self) -> bool {
+        self.frame_sequencer_clocked
+    }
+
     pub fn step(&mut self, ticks: u64) {
+        let previous = self.ticks_acc;
         self.ticks_acc = if self.ticks_reset {
             0
         } else {
@@ -108,6 +126,8 @@ impl Timer {
         };
         self.ticks_reset = false;
 
+        self.frame_sequencer_clocked = previous & FRAME_SEQUENCER_MASK != 0 && self.ticks_acc & FRAME_SEQUENCER_MASK == 0;
+
         self.overflow_interrupt_requested = false;
         if self.timer_enable {
             let next_in_bit = (self.ticks_acc & self.timer_in_mask) != 0;
@@ -130,7 +150,46 @@ fn sync_test() {
         assert_eq!(0, timer.divider());
         timer.step(4)
     }
-    assert_eq!(0, timer.counter());
+
+    // At 16384Hz, TIMA counts along with DIV
+    assert_eq!(1, timer.counter());
     assert_eq!(1, timer.divider());
     assert_eq!(256, timer.ticks_acc);
 }
+
+#[test]
+fn counter_rate_test() {
+    // Overflows after 256 increments of TIMA, at each clock select
+    for &(control, period) in &[(0x04, 1024), (0x05, 16), (0x06, 64), (0x07, 256)] {
+        let mut timer = Timer::default();
+        timer.set_control(control);
+
+        let mut ticks = 0;
+        while !timer.overflow_interrupt_requested() {
+            timer.step(4);
+            ticks += 4;
+        }
+        assert_eq!(256 * period, ticks, "TAC {:02X}", control);
+    }
+}
+
+#[test]
+fn frame_sequencer_test() {
+    let mut timer = Timer::default();
+
+    // 512Hz, every 8192 ticks
+    let mut clocks = 0;
+    for _ in 0..8192 {
+        timer.step(4);
+        if timer.frame_sequencer_clocked() {
+            clocks += 1;
+        }
+    }
+    assert_eq!(4, clocks);
+
+    // Resetting DIV while bit 4 is set clocks it early
+    timer.set_divider(0x10);
+    timer.write(DIV_ADDR, 0);
+    timer.step(4);
+    assert!(timer.frame_sequencer_clocked());
+}
@@ -0,0 +1,89 @@
+//! Minimal WAV writer, 16-bit PCM streamed to disk
+
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const HEADER_SIZE: u32 = 44;
+const BITS_PER_SAMPLE: u16 = 16;
+const FORMAT_PCM: u16 = 1;
+
+/// Interleaved samples written as they come, the sizes in the header are patched on `finish`
+#[derive(Debug)]
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    data_size: u32,
+}
+
+impl WavWriter<BufWriter<File>> {
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), sample_rate, channels)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(mut writer: W, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        let block_align = channels * BITS_PER_SAMPLE / 8;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(HEADER_SIZE - 8).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&FORMAT_PCM.to_le_bytes())?;
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self { writer, data_size: 0 })
+    }
+
+    pub fn write_samples(&mut self, samples: &[i16]) -> io::Result<()> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        self.writer.write_all(&bytes)?;
+        self.data_size += bytes.len() as u32;
+        Ok(())
+    }
+
+    /// Fill in the sizes, giving the writer back
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&(HEADER_SIZE - 8 + self.data_size).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(HEADER_SIZE as u64 - 4))?;
+        self.writer.write_all(&self.data_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn header_test() {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new()), 48_000, 2).unwrap();
+        wav.write_samples(&[1, -1, 0x1234, -0x1234]).unwrap();
+        let data = wav.finish().unwrap().into_inner();
+
+        assert_eq!(HEADER_SIZE as usize + 8, data.len());
+        assert_eq!(b"RIFF", &data[0..4]);
+        assert_eq!(&44u32.to_le_bytes(), &data[4..8]);
+        assert_eq!(b"WAVEfmt ", &data[8..16]);
+        assert_eq!(&[1, 0, 2, 0], &data[20..24]);
+        assert_eq!(&48_000u32.to_le_bytes(), &data[24..28]);
+        assert_eq!(&192_000u32.to_le_bytes(), &data[28..32]);
+        assert_eq!(&[4, 0, 16, 0], &data[32..36]);
+        assert_eq!(b"data", &data[36..40]);
+        assert_eq!(&8u32.to_le_bytes(), &data[40..44]);
+        assert_eq!(&[0x01, 0x00, 0xFF, 0xFF, 0x34, 0x12, 0xCC, 0xED], &data[44..]);
+    }
+}